@@ -0,0 +1,80 @@
+//! Benchmarks of parsing the input and rendering the report, using a generated input.
+//!
+//! ```shell
+//! cargo bench --bench pipeline
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use markdown_test_report::{
+    event,
+    processor::{self, ProcessOptions},
+};
+use std::fmt::Write;
+
+/// The input of a large workspace: mostly passing tests, and every 50th one failing with some
+/// output.
+fn input(tests: usize) -> String {
+    let mut input = String::new();
+    let _ = writeln!(
+        input,
+        r#"{{ "type": "suite", "event": "started", "test_count": {} }}"#,
+        tests
+    );
+    for i in 0..tests {
+        let name = format!("module_{}::tests::test_case_{}", i % 100, i);
+        let _ = writeln!(
+            input,
+            r#"{{ "type": "test", "event": "started", "name": "{}" }}"#,
+            name
+        );
+        match i % 50 {
+            0 => writeln!(
+                input,
+                r#"{{ "type": "test", "name": "{}", "event": "failed", "exec_time": 0.01, "stdout": "{}" }}"#,
+                name,
+                r"thread 'tests::a' panicked at src/lib.rs:10:5:\nassertion failed\n".repeat(5)
+            ),
+            _ => writeln!(
+                input,
+                r#"{{ "type": "test", "name": "{}", "event": "ok", "exec_time": 0.{:03} }}"#,
+                name,
+                i % 1000
+            ),
+        }
+        .unwrap();
+    }
+    let _ = writeln!(
+        input,
+        r#"{{ "type": "suite", "event": "failed", "passed": {}, "failed": {}, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 12.5 }}"#,
+        tests - tests.div_ceil(50),
+        tests.div_ceil(50)
+    );
+    input
+}
+
+fn pipeline(c: &mut Criterion) {
+    let input = input(20_000);
+
+    let mut group = c.benchmark_group("pipeline");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("parse", |b| {
+        b.iter(|| event::iter_records(input.as_bytes()).count())
+    });
+    group.bench_function("render", |b| {
+        b.iter(|| {
+            processor::render_str(
+                &input,
+                ProcessOptions {
+                    disable_front_matter: true,
+                    footer: false,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, pipeline);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+/*
+ * The C interface of markdown-test-report, enabled by its feature `ffi`.
+ */
+
+#ifndef MARKDOWN_TEST_REPORT_H
+#define MARKDOWN_TEST_REPORT_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Render the markdown report of the JSON output of the tests.
+ *
+ * Returns the report, or NULL on failure, setting `error` to the message, unless it's NULL. Both
+ * must be released using `mtr_free_string`.
+ */
+char *mtr_render_markdown(const char *json, char **error);
+
+/*
+ * Release a string returned by the library. NULL is ignored.
+ */
+void mtr_free_string(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
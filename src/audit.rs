@@ -0,0 +1,317 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The JSON output of `cargo audit --json`.
+#[derive(Debug, Deserialize)]
+struct Report {
+    vulnerabilities: Vulnerabilities,
+    #[serde(default)]
+    warnings: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Vulnerabilities {
+    list: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Vulnerability {
+    advisory: Advisory,
+    package: Package,
+}
+
+#[derive(Debug, Deserialize)]
+struct Advisory {
+    id: String,
+    title: String,
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    name: String,
+    version: String,
+}
+
+/// A diagnostic line of `cargo deny --format json check`.
+#[derive(Debug, Deserialize)]
+struct Diagnostic {
+    r#type: String,
+    fields: DiagnosticFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiagnosticFields {
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    message: String,
+    code: Option<String>,
+    /// The advisory, for diagnostics of the `advisories` check.
+    advisory: Option<DenyAdvisory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DenyAdvisory {
+    id: String,
+    url: Option<String>,
+}
+
+/// The codes of the diagnostics of the `advisories` check of cargo deny.
+const ADVISORY_CODES: &[&str] = &[
+    "vulnerability",
+    "unmaintained",
+    "unsound",
+    "notice",
+    "yanked",
+];
+
+impl DiagnosticFields {
+    /// Check if the diagnostic is about an advisory, not about licenses, bans, or sources
+    fn is_advisory(&self) -> bool {
+        self.advisory.is_some()
+            || self
+                .code
+                .as_deref()
+                .is_some_and(|code| code.starts_with("RUSTSEC-") || ADVISORY_CODES.contains(&code))
+    }
+}
+
+/// A finding of the audit.
+#[derive(Debug, Eq, PartialEq)]
+struct Finding {
+    id: String,
+    summary: String,
+    url: Option<String>,
+}
+
+/// The options of the `[addon.audit]` section of the configuration.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Options {
+    pub file: PathBuf,
+    pub position: Option<crate::processor::Position>,
+}
+
+/// A summary of `cargo audit` or `cargo deny` results.
+#[derive(Debug)]
+pub struct Audit {
+    vulnerabilities: Vec<Finding>,
+    warnings: usize,
+    /// The errors of cargo deny about licenses, bans, or sources.
+    other: usize,
+}
+
+impl Audit {
+    /// Load the JSON output of `cargo audit` or `cargo deny`.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut content = String::new();
+        crate::input::open(path)?
+            .read_to_string(&mut content)
+            .with_context(|| format!("Failed to read audit results from {}", path.display()))?;
+
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse audit results from {}", path.display()))
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        if let Ok(report) = serde_json::from_str::<Report>(content) {
+            return Ok(Self::from_audit(report));
+        }
+
+        // cargo deny writes one JSON object per line
+        let mut vulnerabilities = vec![];
+        let mut warnings = 0;
+        let mut other = 0;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let diagnostic: Diagnostic = serde_json::from_str(line)?;
+            if diagnostic.r#type != "diagnostic" {
+                continue;
+            }
+            let fields = diagnostic.fields;
+            match (fields.is_advisory(), fields.severity.as_str()) {
+                (true, "error") => vulnerabilities.push(match fields.advisory {
+                    Some(advisory) => Finding {
+                        id: advisory.id,
+                        summary: fields.message,
+                        url: advisory.url,
+                    },
+                    None => Finding {
+                        id: fields.code.unwrap_or_default(),
+                        summary: fields.message,
+                        url: None,
+                    },
+                }),
+                (true, "warning") => warnings += 1,
+                (false, "error") => other += 1,
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            vulnerabilities,
+            warnings,
+            other,
+        })
+    }
+
+    fn from_audit(report: Report) -> Self {
+        let vulnerabilities = report
+            .vulnerabilities
+            .list
+            .into_iter()
+            .map(|vulnerability| Finding {
+                id: vulnerability.advisory.id,
+                summary: format!(
+                    "{} {}: {}",
+                    vulnerability.package.name,
+                    vulnerability.package.version,
+                    vulnerability.advisory.title
+                ),
+                url: vulnerability.advisory.url,
+            })
+            .collect();
+
+        let warnings = report
+            .warnings
+            .values()
+            .filter_map(|warnings| warnings.as_array())
+            .map(|warnings| warnings.len())
+            .sum();
+
+        Self {
+            vulnerabilities,
+            warnings,
+            other: 0,
+        }
+    }
+}
+
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    match count {
+        1 => format!("1 {}", singular),
+        count => format!("{} {}", count, plural),
+    }
+}
+
+impl super::Addon for Audit {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        let marker = match (self.vulnerabilities.len(), self.warnings) {
+            (0, 0) => "✅",
+            (0, _) => "⚠️",
+            _ => "❌",
+        };
+
+        write!(
+            write,
+            "**Audit:** {} {}, {}",
+            marker,
+            plural(
+                self.vulnerabilities.len(),
+                "vulnerability",
+                "vulnerabilities"
+            ),
+            plural(self.warnings, "warning", "warnings")
+        )?;
+        if self.other > 0 {
+            write!(
+                write,
+                " · {} about licenses, bans, or sources",
+                plural(self.other, "error", "errors")
+            )?;
+        }
+        writeln!(write)?;
+        writeln!(write)?;
+
+        if self.vulnerabilities.is_empty() {
+            return Ok(());
+        }
+
+        for finding in &self.vulnerabilities {
+            match &finding.url {
+                Some(url) => writeln!(write, "* [`{}`]({}) {}", finding.id, url, finding.summary)?,
+                None => writeln!(write, "* `{}` {}", finding.id, finding.summary)?,
+            }
+        }
+        writeln!(write)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+
+    #[test]
+    fn test_cargo_audit() {
+        let audit = Audit::parse(
+            r#"{
+  "database": {"advisory-count": 500},
+  "vulnerabilities": {
+    "found": true,
+    "count": 1,
+    "list": [{
+      "advisory": {
+        "id": "RUSTSEC-2020-0071",
+        "package": "time",
+        "title": "Potential segfault in the time crate",
+        "url": "https://github.com/time-rs/time/issues/293"
+      },
+      "package": {"name": "time", "version": "0.1.45"}
+    }]
+  },
+  "warnings": {"unmaintained": [{}, {}], "yanked": []}
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            audit.vulnerabilities,
+            vec![Finding {
+                id: "RUSTSEC-2020-0071".into(),
+                summary: "time 0.1.45: Potential segfault in the time crate".into(),
+                url: Some("https://github.com/time-rs/time/issues/293".into()),
+            }]
+        );
+        assert_eq!(audit.warnings, 2);
+    }
+
+    #[test]
+    fn test_cargo_deny() {
+        let audit = Audit::parse(
+            r#"{"type":"diagnostic","fields":{"severity":"error","message":"Potential segfault","code":"vulnerability","labels":[]}}
+{"type":"diagnostic","fields":{"severity":"warning","message":"unmaintained","code":"unmaintained","labels":[]}}
+{"type":"diagnostic","fields":{"severity":"error","message":"Potential segfault in the time crate","code":"RUSTSEC-2020-0071","advisory":{"id":"RUSTSEC-2020-0071","title":"Potential segfault","url":"https://github.com/time-rs/time/issues/293"},"labels":[]}}
+{"type":"diagnostic","fields":{"severity":"error","message":"failed to satisfy license requirements","code":"rejected","labels":[]}}
+{"type":"diagnostic","fields":{"severity":"error","message":"crate 'openssl' is explicitly banned","code":"banned","labels":[]}}
+{"type":"diagnostic","fields":{"severity":"warning","message":"found 2 duplicate entries for crate 'syn'","code":"duplicate","labels":[]}}
+{"type":"summary","fields":{}}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(audit.vulnerabilities.len(), 2);
+        assert_eq!(
+            audit.vulnerabilities[1],
+            Finding {
+                id: "RUSTSEC-2020-0071".into(),
+                summary: "Potential segfault in the time crate".into(),
+                url: Some("https://github.com/time-rs/time/issues/293".into()),
+            }
+        );
+        assert_eq!(audit.warnings, 1);
+        assert_eq!(audit.other, 2);
+
+        let mut out = vec![];
+        audit.render(&mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with(
+            "**Audit:** ❌ 2 vulnerabilities, 1 warning · 2 errors about licenses, bans, or sources\n\n"
+        ));
+    }
+}
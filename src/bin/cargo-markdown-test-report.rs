@@ -0,0 +1 @@
+include!("cargo-test-report.rs");
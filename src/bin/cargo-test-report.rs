@@ -0,0 +1,28 @@
+// Allows running the report generator as cargo subcommand, like `cargo test-report`.
+//
+// Delegates to the main binary, which is installed next to this one.
+
+use std::process::{exit, Command};
+
+fn main() {
+    let binary = std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            let name = format!("markdown-test-report{}", std::env::consts::EXE_SUFFIX);
+            exe.parent().map(|dir| dir.join(name))
+        })
+        .filter(|binary| binary.is_file())
+        .unwrap_or_else(|| "markdown-test-report".into());
+
+    // the main binary strips the subcommand name, which cargo passes as first argument
+    match Command::new(&binary)
+        .args(std::env::args_os().skip(1))
+        .status()
+    {
+        Ok(status) => exit(status.code().unwrap_or(1)),
+        Err(err) => {
+            eprintln!("Failed to run {}: {}", binary.display(), err);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::http::{self, truncate, Secret};
+use crate::model;
+use crate::processor::{Notification, Notifier};
+use serde_json::{json, Value};
+
+const API: &str = "https://api.bitbucket.org/2.0";
+/// The maximum number of annotations, which can be created with a single request.
+const MAX_ANNOTATIONS: usize = 100;
+const MAX_SUMMARY: usize = 450;
+const MAX_DETAILS: usize = 2000;
+
+/// Pushes the results to the Code Insights reports of a commit in Bitbucket Cloud.
+#[derive(Debug)]
+pub struct BitbucketReport {
+    /// The endpoint of the report.
+    url: String,
+    token: Secret,
+}
+
+impl BitbucketReport {
+    /// Create for a repository, like `workspace/repo`, and a commit.
+    ///
+    /// The labels are part of the ID of the report, so that several jobs can report on the same
+    /// commit.
+    pub fn new(
+        repository: &str,
+        commit: &str,
+        labels: &[String],
+        token: Secret,
+    ) -> anyhow::Result<Self> {
+        if repository.split('/').count() != 2 {
+            anyhow::bail!(
+                "Invalid Bitbucket repository '{}', expected '<workspace>/<repo>'",
+                repository
+            );
+        }
+
+        let mut id = String::from("markdown-test-report");
+        for label in labels {
+            id.push('-');
+            id.extend(label.chars().map(|c| match c.is_ascii_alphanumeric() {
+                true => c,
+                false => '-',
+            }));
+        }
+
+        Ok(Self {
+            url: format!(
+                "{}/repositories/{}/commit/{}/reports/{}",
+                API, repository, commit, id
+            ),
+            token,
+        })
+    }
+
+    fn headers(&self) -> [(&'static str, String); 1] {
+        [("Authorization", format!("Bearer {}", self.token.0))]
+    }
+}
+
+/// The report, with the totals over all runs
+fn report(report: &model::Report) -> Value {
+    let (passed, failed, ignored) = (
+        report.count("ok"),
+        report.count("failed"),
+        report.count("ignored"),
+    );
+    let duration = report
+        .runs
+        .iter()
+        .filter_map(|run| run.summary.as_ref())
+        .map(|summary| summary.duration)
+        .sum::<f64>();
+
+    let mut title = String::from("Test results");
+    if !report.labels.is_empty() {
+        title.push_str(&format!(" ({})", report.labels.join(", ")));
+    }
+
+    json!({
+        "title": title,
+        "details": format!("{} passed, {} failed, {} ignored", passed, failed, ignored),
+        "report_type": "TEST",
+        "reporter": "markdown-test-report",
+        "result": match report.outcome {
+            "failed" => "FAILED",
+            _ => "PASSED",
+        },
+        "data": [
+            { "title": "Passed", "type": "NUMBER", "value": passed },
+            { "title": "Failed", "type": "NUMBER", "value": failed },
+            { "title": "Ignored", "type": "NUMBER", "value": ignored },
+            { "title": "Duration", "type": "DURATION", "value": (duration * 1000.0).round() as u64 },
+        ],
+    })
+}
+
+/// An annotation for each failed test
+fn annotations(report: &model::Report) -> Vec<Value> {
+    report
+        .tests()
+        .filter(|test| test.outcome == "failed")
+        .take(MAX_ANNOTATIONS)
+        .enumerate()
+        .map(|(idx, test)| {
+            let details = test
+                .message
+                .as_deref()
+                .or(test.output.as_deref())
+                .unwrap_or_default();
+            let mut annotation = json!({
+                "external_id": format!("test-{}", idx + 1),
+                "annotation_type": "BUG",
+                "summary": truncate(&format!("{} failed", test.name), MAX_SUMMARY),
+                "details": truncate(details, MAX_DETAILS),
+                "severity": "HIGH",
+                "result": "FAILED",
+            });
+            if let Some(location) = test.location() {
+                annotation["path"] = location.path.into();
+                annotation["line"] = location.line.into();
+            }
+            annotation
+        })
+        .collect()
+}
+
+impl Notifier for BitbucketReport {
+    fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+        // replacing the report removes its previous annotations
+        http::send(
+            "PUT",
+            &self.url,
+            &self.headers(),
+            &report(notification.report),
+        )?;
+
+        let annotations = annotations(notification.report);
+        if !annotations.is_empty() {
+            http::send(
+                "POST",
+                &format!("{}/annotations", self.url),
+                &self.headers(),
+                &Value::Array(annotations),
+            )?;
+        }
+
+        log::info!("Pushed the results to {}", self.url);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let test = |name: &str, outcome| model::Test {
+            name: name.into(),
+            outcome,
+            duration: Some(0.5),
+            label: None,
+            message: None,
+            output: Some("thread 'b' panicked at src/lib.rs:7:5:\nboom".into()),
+        };
+        let report = model::Report {
+            version: model::VERSION,
+            outcome: "failed",
+            labels: vec!["linux".into()],
+            partial: false,
+            runs: vec![model::Run {
+                name: None,
+                summary: None,
+                suites: vec![],
+                tests: vec![test("a", "ok"), test("b", "failed")],
+            }],
+        };
+
+        let value = super::report(&report);
+        assert_eq!(value["title"], "Test results (linux)");
+        assert_eq!(value["result"], "FAILED");
+        assert_eq!(value["details"], "1 passed, 1 failed, 0 ignored");
+
+        let annotations = annotations(&report);
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0]["summary"], "b failed");
+        assert_eq!(annotations[0]["path"], "src/lib.rs");
+        assert_eq!(annotations[0]["line"], 7);
+
+        assert_eq!(
+            BitbucketReport::new("ws/repo", "abc", &["linux x86".into()], Secret("t".into()))
+                .unwrap()
+                .url,
+            "https://api.bitbucket.org/2.0/repositories/ws/repo/commit/abc/reports/markdown-test-report-linux-x86"
+        );
+        assert!(BitbucketReport::new("repo", "abc", &[], Secret("t".into())).is_err());
+    }
+}
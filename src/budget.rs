@@ -0,0 +1,68 @@
+use crate::processor::SuiteKind;
+use std::time::Duration;
+
+/// What a budget applies to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Scope {
+    /// The duration of all suites
+    Total,
+    /// The duration of all suites of a kind
+    Kind(SuiteKind),
+}
+
+/// A limit of the duration of tests, in the form of `scope=duration`, like `integration=10m`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Budget {
+    pub scope: Scope,
+    pub limit: Duration,
+}
+
+impl Budget {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (scope, limit) = value
+            .split_once('=')
+            .ok_or_else(|| format!("expected SCOPE=DURATION, found: {}", value))?;
+
+        let scope = match scope.trim() {
+            "total" => Scope::Total,
+            "unit" => Scope::Kind(SuiteKind::Unit),
+            "integration" => Scope::Kind(SuiteKind::Integration),
+            "doc" => Scope::Kind(SuiteKind::Doc),
+            scope => {
+                return Err(format!(
+                    "unknown scope '{}', expected one of: total, unit, integration, doc",
+                    scope
+                ))
+            }
+        };
+        let limit = humantime::parse_duration(limit.trim()).map_err(|err| err.to_string())?;
+
+        Ok(Self { scope, limit })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Budget::parse("integration=10m"),
+            Ok(Budget {
+                scope: Scope::Kind(SuiteKind::Integration),
+                limit: Duration::from_secs(600),
+            })
+        );
+        assert_eq!(
+            Budget::parse("total=1h 30s"),
+            Ok(Budget {
+                scope: Scope::Total,
+                limit: Duration::from_secs(3630),
+            })
+        );
+        assert!(Budget::parse("total").is_err());
+        assert!(Budget::parse("bench=1m").is_err());
+        assert!(Budget::parse("unit=fast").is_err());
+    }
+}
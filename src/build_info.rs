@@ -0,0 +1,205 @@
+use crate::input;
+use anyhow::Context;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// A message of `cargo build --message-format json`.
+#[derive(Debug, Deserialize)]
+struct Message {
+    reason: String,
+    #[serde(default)]
+    package_id: String,
+    profile: Option<Profile>,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    filenames: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    test: bool,
+}
+
+/// The name of a package, from its ID.
+///
+/// Supports both `path+file:///dir/name#0.1.0` (or `…/dir#name@0.1.0`) and the older
+/// `name 0.1.0 (path+file:///dir)`.
+fn package_name(id: &str) -> &str {
+    if let Some((name, _)) = id.split_once(' ') {
+        return name;
+    }
+    match id.split_once('#') {
+        Some((_, fragment)) if fragment.contains('@') => {
+            fragment.split('@').next().unwrap_or_default()
+        }
+        Some((url, _)) => url.rsplit('/').next().unwrap_or_default(),
+        None => id,
+    }
+}
+
+/// The name of the profile, from the directory of an artifact, like `target/release/deps/…`.
+fn profile_name(filename: &str) -> Option<String> {
+    let components = Path::new(filename)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    let deps = components.iter().rposition(|c| c == "deps")?;
+    Some(components.get(deps.checked_sub(1)?)?.to_string())
+}
+
+/// The options of the `[addon.build]` section of the configuration.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub profile: Option<String>,
+    pub features: Vec<String>,
+    pub messages: Option<PathBuf>,
+    pub position: Option<crate::processor::Position>,
+}
+
+/// The cargo profile and the features the tests were built with.
+#[derive(Debug, Default)]
+pub struct BuildInfo {
+    profile: Option<String>,
+    /// The enabled features, by package. Features given on the command line use no package.
+    features: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl BuildInfo {
+    pub fn new(profile: Option<String>, features: Vec<String>) -> Self {
+        let mut info = Self {
+            profile,
+            ..Default::default()
+        };
+        if !features.is_empty() {
+            info.features
+                .insert(String::new(), features.into_iter().collect());
+        }
+        info
+    }
+
+    /// Add the test targets of the local packages, from cargo's JSON build messages.
+    ///
+    /// Other lines, like the ones of the test results, are ignored.
+    pub fn load_messages(&mut self, path: &Path) -> anyhow::Result<()> {
+        input::read_lines(input::open(path)?, |line| {
+            self.add_message(line);
+            Ok(())
+        })
+        .with_context(|| format!("Failed to read build messages from {}", path.display()))
+    }
+
+    fn add_message(&mut self, line: &str) {
+        let Ok(message) = serde_json::from_str::<Message>(line) else {
+            return;
+        };
+        if message.reason != "compiler-artifact"
+            || !message.package_id.contains("path+file:")
+            || !message.profile.is_some_and(|profile| profile.test)
+        {
+            return;
+        }
+
+        if self.profile.is_none() {
+            self.profile = message.filenames.iter().find_map(|f| profile_name(f));
+        }
+        self.features
+            .entry(package_name(&message.package_id).to_string())
+            .or_default()
+            .extend(message.features);
+    }
+}
+
+fn format_features(features: &BTreeSet<String>) -> String {
+    match features.is_empty() {
+        true => "*none*".to_string(),
+        false => features
+            .iter()
+            .map(|feature| format!("`{}`", feature))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+impl super::Addon for BuildInfo {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        let mut parts = vec![];
+        if let Some(profile) = &self.profile {
+            parts.push(format!("**Profile:** `{}`", profile));
+        }
+        // a single set of features fits on the same line
+        let single = match self.features.len() {
+            1 => self.features.values().next(),
+            _ => None,
+        };
+        if let Some(features) = single {
+            parts.push(format!("**Features:** {}", format_features(features)));
+        }
+        if !parts.is_empty() {
+            writeln!(write, "{}", parts.join(" · "))?;
+            writeln!(write)?;
+        }
+
+        if single.is_none() && !self.features.is_empty() {
+            writeln!(write, "| Package | Features |")?;
+            writeln!(write, "| ------- | -------- |")?;
+            for (package, features) in &self.features {
+                writeln!(write, "| `{}` | {} |", package, format_features(features))?;
+            }
+            writeln!(write)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("path+file:///src/app#0.1.0"), "app");
+        assert_eq!(package_name("path+file:///src/dir#app@0.1.0"), "app");
+        assert_eq!(package_name("app 0.1.0 (path+file:///src/app)"), "app");
+        assert_eq!(
+            profile_name("/src/target/release/deps/app-0123").as_deref(),
+            Some("release")
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(br#"{"reason":"compiler-artifact","package_id":"registry+https://github.com/rust-lang/crates.io-index#serde@1.0.0","profile":{"test":false},"features":["std"],"filenames":[]}
+{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","profile":{"test":false},"features":["default"],"filenames":["/src/target/debug/libapp.rlib"]}
+{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","profile":{"test":true},"features":["default","json"],"filenames":["/src/target/debug/deps/app-0123"]}
+{ "type": "suite", "event": "started", "test_count": 1 }
+"#).unwrap();
+
+        let mut info = BuildInfo::new(None, vec![]);
+        info.load_messages(file.path()).unwrap();
+        let mut out = vec![];
+        info.render(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "**Profile:** `debug` · **Features:** `default`, `json`\n\n"
+        );
+
+        let mut info = BuildInfo::new(Some("release".into()), vec![]);
+        info.load_messages(file.path()).unwrap();
+        info.add_message(r#"{"reason":"compiler-artifact","package_id":"path+file:///src/cli#0.1.0","profile":{"test":true},"features":[],"filenames":[]}"#);
+        let mut out = vec![];
+        info.render(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "**Profile:** `release`\n\n| Package | Features |\n| ------- | -------- |\n| `app` | `default`, `json` |\n| `cli` | *none* |\n\n"
+        );
+    }
+}
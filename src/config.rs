@@ -0,0 +1,123 @@
+use crate::locale::CustomLabels;
+use crate::{audit, build_info, coverage, criterion, env, host, images, raw};
+#[cfg(feature = "git")]
+use crate::{git, lockfile};
+use anyhow::Context;
+use serde::Deserialize;
+use std::path::Path;
+
+/// The configuration file used when `--config` is not given, if it exists.
+pub const DEFAULT_FILE: &str = "markdown-test-report.toml";
+
+/// The configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub addon: Addons,
+    /// Labels replacing the fixed ones, like `[labels]` with `index = "Test cases"`.
+    pub labels: CustomLabels,
+}
+
+/// The sections of the addons, like `[addon.git]`. Having a section enables its addon.
+///
+/// Each section accepts a `position`, where its addon is rendered, overriding the default of the addon.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Addons {
+    pub audit: Option<audit::Options>,
+    pub build: Option<build_info::Options>,
+    #[cfg(feature = "git")]
+    pub cargo_lock: Option<lockfile::Options>,
+    pub coverage: Option<coverage::Options>,
+    pub criterion: Option<criterion::Options>,
+    pub env: Option<env::Options>,
+    #[cfg(feature = "git")]
+    pub git: Option<git::Options>,
+    pub host: Option<host::Options>,
+    pub images: Option<images::Options>,
+    #[cfg(feature = "kube")]
+    pub kube: Option<crate::kube::Options>,
+    pub raw: Option<raw::Options>,
+}
+
+impl Config {
+    /// Load the configuration, from the given file or the default one, if that exists.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_FILE).is_file() => Path::new(DEFAULT_FILE),
+            None => return Ok(Self::default()),
+        };
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the configuration from {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse the configuration from {}", path.display()))
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+#[cfg(all(test, feature = "git"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r#"
+[addon.git]
+changelog = true
+
+[addon.env]
+vars = ["RUST_LOG", "DATABASE_URL:redact"]
+
+[addon.coverage]
+file = "lcov.info"
+per-crate = true
+
+[addon.cargo-lock]
+
+[addon.host]
+position = "footer"
+
+[labels]
+index = "Test cases"
+passed = "Green"
+"#,
+        )
+        .unwrap();
+
+        let git = config.addon.git.unwrap();
+        assert!(git.changelog && !git.changes);
+        assert_eq!(
+            config.addon.env.unwrap().vars,
+            vec![
+                env::EnvVar::parse("RUST_LOG").unwrap(),
+                env::EnvVar::parse("DATABASE_URL:redact").unwrap()
+            ]
+        );
+        let coverage = config.addon.coverage.unwrap();
+        assert_eq!(coverage.file, Path::new("lcov.info"));
+        assert!(coverage.per_crate);
+        assert_eq!(
+            config.addon.cargo_lock.unwrap().file,
+            Path::new("Cargo.lock")
+        );
+        assert_eq!(
+            config.addon.host.unwrap().position,
+            Some(crate::processor::Position::Footer)
+        );
+        assert!(config.addon.audit.is_none());
+        assert_eq!(config.labels.index.as_deref(), Some("Test cases"));
+        assert_eq!(config.labels.passed.as_deref(), Some("Green"));
+        assert!(config.labels.details.is_none());
+
+        assert!(Config::parse("[addon.git]\nchangelogs = true\n").is_err());
+        assert!(Config::parse("[addon.env]\nvars = [\":redact\"]\n").is_err());
+        assert!(Config::parse("[addon.coverage]\nper-crate = true\n").is_err());
+        assert!(Config::parse("[addon.git]\nposition = \"top\"\n").is_err());
+        assert!(Config::parse("[labels]\nsummary = \"Overview\"\n").is_err());
+    }
+}
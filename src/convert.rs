@@ -0,0 +1,186 @@
+use crate::event::{suite, test, Parser, Record};
+use crate::input::{self, InputFormat};
+use crate::invocation::Invocation;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::{io::Write, path::Path, time::Duration};
+
+/// The format to convert test results into.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// The JSON output of the libtest harness, one record per line
+    Libtest,
+    /// The Common Test Report Format, see <https://ctrf.io>
+    Ctrf,
+}
+
+/// Convert an input into another format, without rendering a report.
+pub fn convert(
+    from: InputFormat,
+    to: OutputFormat,
+    path: &Path,
+    write: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let mut lines = vec![];
+    let mut converter = from.converter();
+    input::read_lines(input::open(path)?, |line| {
+        match &mut converter {
+            Some(converter) => converter.line(line, &mut lines)?,
+            None => lines.push(line.to_string()),
+        }
+        Ok(())
+    })?;
+    if let Some(converter) = &mut converter {
+        converter.finish(&mut lines)?;
+    }
+
+    match to {
+        OutputFormat::Libtest => {
+            // re-emit the records, filling in the fields custom harnesses may omit
+            let mut parser = Parser::default();
+            for line in lines {
+                match parser.parse(&line) {
+                    Ok(record) => writeln!(write, "{}", record.to_line())?,
+                    Err(_) => writeln!(write, "{}", line)?,
+                }
+            }
+        }
+        OutputFormat::Ctrf => {
+            serde_json::to_writer_pretty(&mut *write, &ctrf(&lines))?;
+            writeln!(write)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a CTRF report from lines in the libtest format
+fn ctrf(lines: &[String]) -> Value {
+    let mut parser = Parser::default();
+    let mut suite = None;
+    let mut tests = vec![];
+    let mut duration = Duration::ZERO;
+
+    for line in lines {
+        let record = match parser.parse(line) {
+            Ok(record) => record,
+            Err(_) => {
+                if let Some(invocation) = Invocation::parse(line) {
+                    suite = invocation.target.or(invocation.binary);
+                }
+                continue;
+            }
+        };
+
+        let (name, status, exec_time, message, trace, start_time) = match record {
+            Record::Suite(suite::Event::Ok { exec_time, .. })
+            | Record::Suite(suite::Event::Failed { exec_time, .. }) => {
+                duration += exec_time;
+                continue;
+            }
+            Record::Suite(suite::Event::Started { .. }) | Record::Unknown(_) => continue,
+            Record::Test(test::Event::Started { .. }) => continue,
+            Record::Test(test::Event::Ok {
+                name,
+                exec_time,
+                start_time,
+            }) => (name, "passed", exec_time, None, None, start_time),
+            Record::Test(test::Event::Failed {
+                name,
+                exec_time,
+                stdout,
+                message,
+                start_time,
+            }) => (name, "failed", exec_time, message, Some(stdout), start_time),
+            Record::Test(test::Event::Ignored { name, message }) => {
+                (name, "skipped", Duration::ZERO, message, None, None)
+            }
+        };
+
+        let mut entry = json!({
+            "name": name,
+            "status": status,
+            "duration": exec_time.as_millis() as u64,
+        });
+        if let Some(message) = message {
+            entry["message"] = message.into();
+        }
+        if let Some(trace) = trace.filter(|trace| !trace.is_empty()) {
+            entry["trace"] = trace.into();
+        }
+        if let Some(suite) = &suite {
+            entry["suite"] = suite.as_str().into();
+        }
+        if let Some(start_time) = start_time {
+            let start = start_time.timestamp_millis();
+            entry["start"] = start.into();
+            entry["stop"] = (start + exec_time.as_millis() as i64).into();
+        }
+        tests.push(entry);
+    }
+
+    let count = |status: &str| tests.iter().filter(|t| t["status"] == status).count();
+    let stop = Utc::now().timestamp_millis();
+
+    json!({
+        "results": {
+            "tool": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "summary": {
+                "tests": tests.len(),
+                "passed": count("passed"),
+                "failed": count("failed"),
+                "pending": 0,
+                "skipped": count("skipped"),
+                "other": 0,
+                // the results only contain the duration
+                "start": stop - duration.as_millis() as i64,
+                "stop": stop,
+            },
+            "tests": tests,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ctrf() {
+        let lines = r#"     Running unittests src/lib.rs (target/debug/deps/foo-1234)
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.25, "start_time": "2024-05-01T10:00:00.5+02:00" }
+{ "type": "test", "name": "tests::b", "event": "failed", "exec_time": 1.5, "stdout": "boom" }
+{ "type": "test", "name": "tests::c", "event": "ignored", "message": "slow" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 2.0 }"#
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        let report = ctrf(&lines);
+        let results = &report["results"];
+        assert_eq!(results["summary"]["tests"], 3);
+        assert_eq!(results["summary"]["failed"], 1);
+        assert_eq!(
+            results["summary"]["stop"].as_i64().unwrap()
+                - results["summary"]["start"].as_i64().unwrap(),
+            2000
+        );
+        assert_eq!(
+            results["tests"][1],
+            json!({
+                "name": "tests::b",
+                "status": "failed",
+                "duration": 1500,
+                "trace": "boom",
+                "suite": "unittests src/lib.rs",
+            })
+        );
+        assert_eq!(results["tests"][2]["message"], "slow");
+        assert_eq!(results["tests"][0]["start"], 1714550400500i64);
+        assert_eq!(results["tests"][0]["stop"], 1714550400750i64);
+    }
+}
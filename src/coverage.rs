@@ -0,0 +1,333 @@
+use crate::xml::attribute;
+use anyhow::Context;
+use quick_xml::events::{BytesStart, Event};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    io::{Read, Write},
+    ops::AddAssign,
+    path::{Component, Path, PathBuf},
+};
+
+/// Line and branch coverage counts.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+struct Totals {
+    lines_found: u64,
+    lines_hit: u64,
+    branches_found: u64,
+    branches_hit: u64,
+}
+
+impl AddAssign for Totals {
+    fn add_assign(&mut self, rhs: Self) {
+        self.lines_found += rhs.lines_found;
+        self.lines_hit += rhs.lines_hit;
+        self.branches_found += rhs.branches_found;
+        self.branches_hit += rhs.branches_hit;
+    }
+}
+
+/// Renders a ratio as percentage, along with the absolute numbers.
+struct Ratio(u64, u64);
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.1 {
+            0 => write!(f, "–"),
+            total => write!(
+                f,
+                "{:.2}% ({}/{})",
+                self.0 as f64 * 100.0 / total as f64,
+                self.0,
+                total
+            ),
+        }
+    }
+}
+
+/// The options of the `[addon.coverage]` section of the configuration.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Options {
+    pub file: PathBuf,
+    #[serde(default)]
+    pub per_crate: bool,
+    pub position: Option<crate::processor::Position>,
+}
+
+/// Coverage totals from an lcov or cobertura file.
+#[derive(Debug)]
+pub struct Coverage {
+    /// Totals per crate, or a single entry when not split by crate.
+    crates: BTreeMap<String, Totals>,
+    per_crate: bool,
+}
+
+impl Coverage {
+    /// Load the coverage information from a file, detecting its format.
+    pub fn load(path: &Path, per_crate: bool) -> anyhow::Result<Self> {
+        let mut content = String::new();
+        crate::input::open(path)?
+            .read_to_string(&mut content)
+            .with_context(|| format!("Failed to read coverage from {}", path.display()))?;
+
+        let files = match content.trim_start().starts_with('<') {
+            true => parse_cobertura(&content),
+            false => parse_lcov(&content),
+        }
+        .with_context(|| format!("Failed to parse coverage from {}", path.display()))?;
+
+        let mut crates = BTreeMap::<String, Totals>::new();
+        for (file, totals) in files {
+            *crates.entry(crate_name(&file)).or_default() += totals;
+        }
+
+        Ok(Self { crates, per_crate })
+    }
+
+    fn total(&self) -> Totals {
+        let mut total = Totals::default();
+        for totals in self.crates.values() {
+            total += *totals;
+        }
+        total
+    }
+}
+
+impl super::Addon for Coverage {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        let total = self.total();
+
+        write!(
+            write,
+            "**Coverage:** Lines: {}",
+            Ratio(total.lines_hit, total.lines_found)
+        )?;
+        if total.branches_found > 0 {
+            write!(
+                write,
+                ", Branches: {}",
+                Ratio(total.branches_hit, total.branches_found)
+            )?;
+        }
+        writeln!(write)?;
+        writeln!(write)?;
+
+        if self.per_crate && self.crates.len() > 1 {
+            writeln!(write, "| Crate | Lines | Branches |")?;
+            writeln!(write, "| ----- | ----- | -------- |")?;
+            for (name, totals) in &self.crates {
+                writeln!(
+                    write,
+                    "| {} | {} | {} |",
+                    name,
+                    Ratio(totals.lines_hit, totals.lines_found),
+                    Ratio(totals.branches_hit, totals.branches_found)
+                )?;
+            }
+            writeln!(write)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Guess the crate a source file belongs to, from the directory containing `src`.
+fn crate_name(file: &str) -> String {
+    let components = Path::new(file)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    components
+        .iter()
+        .rposition(|c| c == "src")
+        .and_then(|idx| idx.checked_sub(1))
+        .map(|idx| components[idx].to_string())
+        .unwrap_or_else(|| "<unknown>".to_string())
+}
+
+/// Parse an lcov tracefile, returning the totals per source file.
+fn parse_lcov(content: &str) -> anyhow::Result<Vec<(String, Totals)>> {
+    let mut files = vec![];
+    let mut current: Option<(String, Totals)> = None;
+
+    for line in content.lines() {
+        let (key, value) = line.trim().split_once(':').unwrap_or((line.trim(), ""));
+        match (key, &mut current) {
+            ("SF", _) => current = Some((value.to_string(), Totals::default())),
+            ("LF", Some((_, totals))) => totals.lines_found = value.parse()?,
+            ("LH", Some((_, totals))) => totals.lines_hit = value.parse()?,
+            ("BRF", Some((_, totals))) => totals.branches_found = value.parse()?,
+            ("BRH", Some((_, totals))) => totals.branches_hit = value.parse()?,
+            ("end_of_record", _) => files.extend(current.take()),
+            _ => {}
+        }
+    }
+
+    files.extend(current);
+
+    Ok(files)
+}
+
+/// Parse a cobertura XML file, returning the totals per source file.
+fn parse_cobertura(content: &str) -> anyhow::Result<Vec<(String, Totals)>> {
+    let mut reader = quick_xml::Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut files = BTreeMap::<String, Totals>::new();
+    let mut path = Vec::<Vec<u8>>::new();
+    let mut file = None;
+
+    loop {
+        let (element, empty) = match reader.read_event()? {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::End(_) => {
+                path.pop();
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match element.name().as_ref() {
+            b"class" => file = attribute(&element, "filename")?,
+            // lines are repeated in the methods of a class, only count those of the class itself
+            b"line" if path.ends_with(&[b"class".to_vec(), b"lines".to_vec()]) => {
+                if let Some(file) = &file {
+                    *files.entry(file.clone()).or_default() += line_totals(&element)?;
+                }
+            }
+            _ => {}
+        }
+
+        if !empty {
+            path.push(element.name().as_ref().to_vec());
+        }
+    }
+
+    Ok(files.into_iter().collect())
+}
+
+/// Count the coverage of a single cobertura `line` element.
+fn line_totals(element: &BytesStart) -> anyhow::Result<Totals> {
+    let hits = attribute(element, "hits")?.unwrap_or_default();
+    let mut totals = Totals {
+        lines_found: 1,
+        lines_hit: (hits.parse::<u64>().unwrap_or_default() > 0) as u64,
+        ..Default::default()
+    };
+
+    // in the form of: 50% (1/2)
+    if let Some(condition) = attribute(element, "condition-coverage")? {
+        if let Some((hit, found)) = condition
+            .split_once('(')
+            .and_then(|(_, rest)| rest.trim_end_matches(')').split_once('/'))
+        {
+            totals.branches_hit = hit.trim().parse()?;
+            totals.branches_found = found.trim().parse()?;
+        }
+    }
+
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lcov() {
+        let files = parse_lcov(
+            r#"TN:
+SF:/work/foo/src/lib.rs
+FN:1,foo
+LF:10
+LH:8
+BRF:4
+BRH:1
+end_of_record
+SF:/work/bar/src/main.rs
+LF:5
+LH:5
+end_of_record
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                (
+                    "/work/foo/src/lib.rs".to_string(),
+                    Totals {
+                        lines_found: 10,
+                        lines_hit: 8,
+                        branches_found: 4,
+                        branches_hit: 1,
+                    }
+                ),
+                (
+                    "/work/bar/src/main.rs".to_string(),
+                    Totals {
+                        lines_found: 5,
+                        lines_hit: 5,
+                        ..Default::default()
+                    }
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cobertura() {
+        let files = parse_cobertura(
+            r#"<?xml version="1.0"?>
+<coverage line-rate="0.5" branch-rate="0.5">
+  <packages>
+    <package name="foo">
+      <classes>
+        <class name="lib" filename="foo/src/lib.rs">
+          <methods>
+            <method name="foo">
+              <lines><line number="1" hits="1"/></lines>
+            </method>
+          </methods>
+          <lines>
+            <line number="1" hits="1"/>
+            <line number="2" hits="0" branch="true" condition-coverage="50% (1/2)"/>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![(
+                "foo/src/lib.rs".to_string(),
+                Totals {
+                    lines_found: 2,
+                    lines_hit: 1,
+                    branches_found: 2,
+                    branches_hit: 1,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_crate_name() {
+        assert_eq!(crate_name("/work/foo/src/lib.rs"), "foo");
+        assert_eq!(crate_name("bar/src/bin/src.rs"), "bar");
+        assert_eq!(crate_name("main.rs"), "<unknown>");
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::Context;
+use askama_escape::{escape, Html};
+use serde::Deserialize;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Changes within this ratio are considered noise, like criterion does by default.
+const NOISE_THRESHOLD: f64 = 0.01;
+
+#[derive(Debug, Deserialize)]
+struct Estimates {
+    mean: Estimate,
+}
+
+#[derive(Debug, Deserialize)]
+struct Estimate {
+    point_estimate: f64,
+    confidence_interval: ConfidenceInterval,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfidenceInterval {
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Benchmark {
+    full_id: String,
+}
+
+/// A benchmark result, the mean time in nanoseconds and the change against the baseline.
+#[derive(Debug)]
+struct Measurement {
+    name: String,
+    mean: f64,
+    change: Option<Estimate>,
+}
+
+/// The options of the `[addon.criterion]` section of the configuration.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Options {
+    pub dir: PathBuf,
+    pub position: Option<crate::processor::Position>,
+}
+
+/// The benchmark results found in a criterion output directory.
+#[derive(Debug)]
+pub struct Benchmarks {
+    measurements: Vec<Measurement>,
+}
+
+impl Benchmarks {
+    /// Load all benchmark results, usually located in `target/criterion`.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut dirs = vec![];
+        find_benchmarks(path, &mut dirs)
+            .with_context(|| format!("Failed to read benchmarks from {}", path.display()))?;
+
+        let mut measurements = dirs
+            .into_iter()
+            .map(|dir| load_measurement(path, &dir))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        measurements.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self { measurements })
+    }
+}
+
+/// Collect all directories containing the results of a benchmark.
+fn find_benchmarks(dir: &Path, result: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    if dir.join("new").join("estimates.json").is_file() {
+        result.push(dir.to_path_buf());
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // skip the HTML reports
+        if entry.file_type()?.is_dir() && entry.file_name() != "report" {
+            find_benchmarks(&entry.path(), result)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn load_measurement(base: &Path, dir: &Path) -> anyhow::Result<Measurement> {
+    let read = |path: PathBuf| {
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+    };
+
+    let estimates: Estimates =
+        serde_json::from_str(&read(dir.join("new").join("estimates.json"))?)?;
+
+    let name = match read(dir.join("new").join("benchmark.json")) {
+        Ok(benchmark) => serde_json::from_str::<Benchmark>(&benchmark)?.full_id,
+        Err(_) => dir
+            .strip_prefix(base)
+            .unwrap_or(dir)
+            .to_string_lossy()
+            .into_owned(),
+    };
+
+    // only present when there is a previous run to compare against
+    let change = match read(dir.join("change").join("estimates.json")) {
+        Ok(change) => Some(serde_json::from_str::<Estimates>(&change)?.mean),
+        Err(_) => None,
+    };
+
+    Ok(Measurement {
+        name,
+        mean: estimates.mean.point_estimate,
+        change,
+    })
+}
+
+/// Format a duration in nanoseconds, using the same units as criterion.
+fn format_time(ns: f64) -> String {
+    if ns < 1.0e3 {
+        format!("{:.4} ns", ns)
+    } else if ns < 1.0e6 {
+        format!("{:.4} µs", ns / 1.0e3)
+    } else if ns < 1.0e9 {
+        format!("{:.4} ms", ns / 1.0e6)
+    } else {
+        format!("{:.4} s", ns / 1.0e9)
+    }
+}
+
+fn format_change(change: &Estimate) -> String {
+    let marker = if change.confidence_interval.lower_bound > NOISE_THRESHOLD {
+        " ⚠️"
+    } else if change.confidence_interval.upper_bound < -NOISE_THRESHOLD {
+        " ✅"
+    } else {
+        ""
+    };
+    format!("{:+.2}%{}", change.point_estimate * 100.0, marker)
+}
+
+impl super::Addon for Benchmarks {
+    fn position(&self) -> super::Position {
+        super::Position::Footer
+    }
+
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        writeln!(write)?;
+        writeln!(write, "# Benchmarks")?;
+        writeln!(write)?;
+
+        if self.measurements.is_empty() {
+            writeln!(write, "No benchmark results found.")?;
+            return Ok(());
+        }
+
+        writeln!(write, "| Name | Mean | Change |")?;
+        writeln!(write, "| ---- | ---- | ------ |")?;
+        for measurement in &self.measurements {
+            writeln!(
+                write,
+                "| {} | {} | {} |",
+                escape(&measurement.name, Html),
+                format_time(measurement.mean),
+                measurement
+                    .change
+                    .as_ref()
+                    .map(format_change)
+                    .unwrap_or_else(|| "–".to_string())
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ESTIMATES: &str = r#"{"mean":{"confidence_interval":{"confidence_level":0.95,"lower_bound":1200.0,"upper_bound":1300.0},"point_estimate":1250.0,"standard_error":10.0}}"#;
+    const CHANGE: &str = r#"{"mean":{"confidence_interval":{"confidence_level":0.95,"lower_bound":0.05,"upper_bound":0.15},"point_estimate":0.1,"standard_error":0.01}}"#;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let bench = dir.path().join("parse").join("small");
+        fs::create_dir_all(bench.join("new")).unwrap();
+        fs::create_dir_all(bench.join("change")).unwrap();
+        fs::create_dir_all(dir.path().join("report")).unwrap();
+        fs::write(bench.join("new").join("estimates.json"), ESTIMATES).unwrap();
+        fs::write(
+            bench.join("new").join("benchmark.json"),
+            r#"{"full_id":"parse/small"}"#,
+        )
+        .unwrap();
+        fs::write(bench.join("change").join("estimates.json"), CHANGE).unwrap();
+
+        let benchmarks = Benchmarks::load(dir.path()).unwrap();
+        assert_eq!(benchmarks.measurements.len(), 1);
+
+        let measurement = &benchmarks.measurements[0];
+        assert_eq!(measurement.name, "parse/small");
+        assert_eq!(format_time(measurement.mean), "1.2500 µs");
+        assert_eq!(
+            measurement.change.as_ref().map(format_change).as_deref(),
+            Some("+10.00% ⚠️")
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use crate::event::{test, Parser, Record};
+use crate::input;
+use std::{collections::BTreeMap, io::Write, path::Path, time::Duration};
+
+/// When a slower test is considered a regression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Thresholds {
+    /// The minimum increase, relative to the baseline.
+    pub percent: u64,
+    /// The minimum increase of the duration, ignoring the noise of fast tests.
+    pub absolute: Duration,
+}
+
+/// The last result of each test.
+type Results = BTreeMap<String, (bool, Duration)>;
+
+fn results(path: &Path) -> anyhow::Result<Results> {
+    let mut parser = Parser::default();
+    let mut results = Results::new();
+    input::read_records(path, |line| {
+        match parser.parse(line) {
+            Ok(Record::Test(test::Event::Ok {
+                name, exec_time, ..
+            })) => {
+                results.insert(name, (false, exec_time));
+            }
+            Ok(Record::Test(test::Event::Failed {
+                name, exec_time, ..
+            })) => {
+                results.insert(name, (true, exec_time));
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(results)
+}
+
+/// A test which got slower than the thresholds allow.
+#[derive(Debug, PartialEq)]
+struct Regression<'a> {
+    name: &'a str,
+    baseline: Duration,
+    current: Duration,
+}
+
+impl Regression<'_> {
+    fn percent(&self) -> f64 {
+        (self.current.as_secs_f64() / self.baseline.as_secs_f64() - 1.0) * 100.0
+    }
+}
+
+fn regressions<'a>(
+    baseline: &Results,
+    current: &'a Results,
+    thresholds: Thresholds,
+) -> Vec<Regression<'a>> {
+    let mut regressions = current
+        .iter()
+        .filter_map(|(name, (_, current))| {
+            let (_, baseline) = baseline.get(name)?;
+            let increase = current.checked_sub(*baseline)?;
+            // tests without a duration, like of custom harnesses, can't regress
+            (!baseline.is_zero()
+                && increase > thresholds.absolute
+                && increase.as_secs_f64() * 100.0
+                    > baseline.as_secs_f64() * thresholds.percent as f64)
+                .then_some(Regression {
+                    name,
+                    baseline: *baseline,
+                    current: *current,
+                })
+        })
+        .collect::<Vec<_>>();
+    regressions.sort_by(|a, b| b.percent().total_cmp(&a.percent()));
+    regressions
+}
+
+fn format_duration(duration: Duration) -> String {
+    humantime::format_duration(Duration::from_millis(duration.as_millis() as u64)).to_string()
+}
+
+/// Compare a result against a baseline, rendering the changed outcomes and the performance
+/// regressions.
+///
+/// Returns the number of regressions.
+pub fn diff(
+    baseline: &Path,
+    current: &Path,
+    thresholds: Thresholds,
+    write: &mut dyn Write,
+) -> anyhow::Result<usize> {
+    let baseline = results(baseline)?;
+    let current = results(current)?;
+
+    let changed = |failed: bool| {
+        current
+            .iter()
+            .filter(|(name, (now, _))| {
+                *now == failed && baseline.get(*name).is_some_and(|(then, _)| *then != failed)
+            })
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+    };
+    let failing = changed(true);
+    let fixed = changed(false);
+    let regressions = regressions(&baseline, &current, thresholds);
+
+    writeln!(write, "# Comparison with the baseline")?;
+    writeln!(write)?;
+    writeln!(
+        write,
+        "**Baseline:** {} tests · **Current:** {} tests · **New failures:** {} · **Fixed:** {} · **Performance regressions:** {}",
+        baseline.len(),
+        current.len(),
+        failing.len(),
+        fixed.len(),
+        regressions.len(),
+    )?;
+
+    for (title, names) in [("New failures", failing), ("Fixed", fixed)] {
+        if names.is_empty() {
+            continue;
+        }
+        writeln!(write)?;
+        writeln!(write, "## {}", title)?;
+        writeln!(write)?;
+        for name in names {
+            writeln!(write, "* `{}`", name)?;
+        }
+    }
+
+    if !regressions.is_empty() {
+        writeln!(write)?;
+        writeln!(write, "## Performance regressions")?;
+        writeln!(write)?;
+        writeln!(
+            write,
+            "Tests which got more than {}% and {} slower.",
+            thresholds.percent,
+            humantime::format_duration(thresholds.absolute)
+        )?;
+        writeln!(write)?;
+        writeln!(write, "| Test | Baseline | Current | Change |")?;
+        writeln!(write, "| ---- | -------: | ------: | -----: |")?;
+        for regression in &regressions {
+            writeln!(
+                write,
+                "| `{}` | {} | {} | +{:.0}% |",
+                regression.name,
+                format_duration(regression.baseline),
+                format_duration(regression.current),
+                regression.percent()
+            )?;
+        }
+    }
+
+    Ok(regressions.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regressions() {
+        let results = |entries: &[(&str, u64)]| {
+            entries
+                .iter()
+                .map(|(name, millis)| (name.to_string(), (false, Duration::from_millis(*millis))))
+                .collect::<Results>()
+        };
+        let baseline = results(&[("a", 1000), ("b", 1000), ("c", 10), ("d", 0), ("e", 1000)]);
+        let current = results(&[("a", 1500), ("b", 1100), ("c", 30), ("d", 500), ("f", 5000)]);
+
+        let thresholds = Thresholds {
+            percent: 20,
+            absolute: Duration::from_millis(100),
+        };
+        assert_eq!(
+            regressions(&baseline, &current, thresholds),
+            vec![Regression {
+                name: "a",
+                baseline: Duration::from_millis(1000),
+                current: Duration::from_millis(1500),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline = dir.path().join("baseline.json");
+        let current = dir.path().join("current.json");
+        std::fs::write(
+            &baseline,
+            r#"{ "type": "test", "name": "a", "event": "ok", "exec_time": 1.0 }
+{ "type": "test", "name": "b", "event": "failed", "exec_time": 0.5 }
+{ "type": "test", "name": "c", "event": "ok", "exec_time": 0.5 }
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &current,
+            r#"{ "type": "test", "name": "a", "event": "ok", "exec_time": 2.5 }
+{ "type": "test", "name": "b", "event": "ok", "exec_time": 0.5 }
+{ "type": "test", "name": "c", "event": "failed", "exec_time": 0.5 }
+"#,
+        )
+        .unwrap();
+
+        let mut out = vec![];
+        let thresholds = Thresholds {
+            percent: 20,
+            absolute: Duration::from_millis(100),
+        };
+        assert_eq!(diff(&baseline, &current, thresholds, &mut out).unwrap(), 1);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("## New failures\n\n* `c`\n"));
+        assert!(out.contains("## Fixed\n\n* `b`\n"));
+        assert!(out.contains("| `a` | 1s | 2s 500ms | +150% |"));
+    }
+}
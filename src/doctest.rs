@@ -0,0 +1,149 @@
+/// How a doc-test is run, when it differs from simply running the example.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    /// The example must fail to compile
+    CompileFail,
+    /// The example is compiled, but not run
+    NoRun,
+    /// The example must panic
+    ShouldPanic,
+}
+
+impl Mode {
+    /// Parse an attribute of the test name, as added by rustdoc
+    fn from_attribute(attribute: &str) -> Option<Self> {
+        match attribute {
+            "compile fail" | "compile_fail" => Some(Self::CompileFail),
+            "compile" | "no_run" => Some(Self::NoRun),
+            "should panic" | "should_panic" => Some(Self::ShouldPanic),
+            _ => None,
+        }
+    }
+
+    /// The attribute of the code block
+    pub fn attribute(&self) -> &'static str {
+        match self {
+            Self::CompileFail => "compile_fail",
+            Self::NoRun => "no_run",
+            Self::ShouldPanic => "should_panic",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::CompileFail => "The example is expected to fail to compile, it doesn't get run.",
+            Self::NoRun => "The example is only compiled, it doesn't get run.",
+            Self::ShouldPanic => "The example is expected to panic.",
+        }
+    }
+}
+
+/// The name of a doc-test, like `src/lib.rs - module::func (line 42)`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DocTest<'a> {
+    /// The source file, relative to the crate.
+    pub file: &'a str,
+    /// The documented item, empty for the crate or module level documentation.
+    pub symbol: &'a str,
+    pub line: u32,
+    /// Additional attributes, like `compile fail`.
+    pub attributes: Vec<&'a str>,
+}
+
+impl<'a> DocTest<'a> {
+    /// Parse the name of a test, returning `None` if it isn't a doc-test.
+    pub fn parse(name: &'a str) -> Option<Self> {
+        let (file, rest) = name.split_once(" - ")?;
+        let (symbol, rest) = rest.rsplit_once("(line ")?;
+        let (line, rest) = rest.split_once(')')?;
+
+        let attributes = rest
+            .split(" - ")
+            .map(str::trim)
+            .filter(|attribute| !attribute.is_empty())
+            .collect();
+
+        Some(Self {
+            file: file.trim(),
+            symbol: symbol.trim(),
+            line: line.trim().parse().ok()?,
+            attributes,
+        })
+    }
+
+    /// The name of the test, relative to its file.
+    pub fn title(&self) -> String {
+        match self.symbol.is_empty() {
+            true => format!("line {}", self.line),
+            false => format!("{} (line {})", self.symbol, self.line),
+        }
+    }
+
+    /// How the test is run, derived from its attributes
+    pub fn modes(&self) -> impl Iterator<Item = Mode> + '_ {
+        self.attributes
+            .iter()
+            .filter_map(|attribute| Mode::from_attribute(attribute))
+    }
+
+    /// A name to create the anchor from, keeping the file and symbol separators distinct.
+    pub fn anchor_name(&self) -> String {
+        let mut name = format!(
+            "doctest {} {} {}",
+            self.file.replace(['/', '\\', '.'], "-"),
+            self.symbol.replace("::", "-"),
+            self.line
+        );
+        for attribute in &self.attributes {
+            name.push(' ');
+            name.push_str(attribute);
+        }
+        name
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            DocTest::parse("src/lib.rs - module::func (line 42)"),
+            Some(DocTest {
+                file: "src/lib.rs",
+                symbol: "module::func",
+                line: 42,
+                attributes: vec![],
+            })
+        );
+        assert_eq!(
+            DocTest::parse("src/lib.rs - (line 1) - compile fail"),
+            Some(DocTest {
+                file: "src/lib.rs",
+                symbol: "",
+                line: 1,
+                attributes: vec!["compile fail"],
+            })
+        );
+        assert_eq!(DocTest::parse("tests::foo"), None);
+        assert_eq!(DocTest::parse("tests::foo - bar (line x)"), None);
+    }
+
+    #[test]
+    fn test_anchor_name() {
+        let test = DocTest::parse("src/lib.rs - module::func (line 42)").unwrap();
+        assert_eq!(test.anchor_name(), "doctest src-lib-rs module-func 42");
+        assert_eq!(test.title(), "module::func (line 42)");
+    }
+
+    #[test]
+    fn test_modes() {
+        let test = DocTest::parse("src/lib.rs - func (line 1) - compile fail").unwrap();
+        assert_eq!(test.modes().collect::<Vec<_>>(), vec![Mode::CompileFail]);
+        let test = DocTest::parse("src/lib.rs - func (line 1) - compile").unwrap();
+        assert_eq!(test.modes().collect::<Vec<_>>(), vec![Mode::NoRun]);
+        let test = DocTest::parse("src/lib.rs - func (line 1)").unwrap();
+        assert_eq!(test.modes().count(), 0);
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::Write;
+
+/// Parts of names of variables, which are always redacted.
+const SECRET_NAMES: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "PASSWD", "KEY", "CREDENTIAL"];
+
+/// An environment variable to record, like `RUST_LOG`, or `DATABASE_URL:redact`.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct EnvVar {
+    name: String,
+    /// Only record if the variable is set, not its value.
+    redact: bool,
+}
+
+impl EnvVar {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (name, redact) = match value.trim().split_once(':') {
+            Some((name, "redact")) => (name, true),
+            Some(_) => return Err(format!("expected NAME or NAME:redact, found: {}", value)),
+            None => (value.trim(), false),
+        };
+        if name.is_empty() {
+            return Err(format!("missing the name of the variable: {}", value));
+        }
+        let upper = name.to_uppercase();
+        Ok(Self {
+            name: name.to_string(),
+            redact: redact || SECRET_NAMES.iter().any(|secret| upper.contains(secret)),
+        })
+    }
+}
+
+impl TryFrom<String> for EnvVar {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+/// The options of the `[addon.env]` section of the configuration.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub vars: Vec<EnvVar>,
+    pub position: Option<crate::processor::Position>,
+}
+
+/// Selected environment variables of the test run.
+#[derive(Debug)]
+pub struct EnvVars {
+    vars: Vec<(EnvVar, Option<String>)>,
+}
+
+impl EnvVars {
+    pub fn capture(vars: Vec<EnvVar>) -> Self {
+        Self {
+            vars: vars
+                .into_iter()
+                .map(|var| {
+                    let value = std::env::var(&var.name).ok();
+                    (var, value)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl super::Addon for EnvVars {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        writeln!(write, "**Environment:**")?;
+        writeln!(write)?;
+        writeln!(write, "| Variable | Value |")?;
+        writeln!(write, "| -------- | ----- |")?;
+        for (var, value) in &self.vars {
+            let value = match value {
+                None => "*not set*".to_string(),
+                Some(_) if var.redact => "*redacted*".to_string(),
+                Some(value) if value.is_empty() => "*empty*".to_string(),
+                Some(value) => format!("`{}`", value.replace('`', "'").replace('|', "\\|")),
+            };
+            writeln!(write, "| `{}` | {} |", var.name, value)?;
+        }
+        writeln!(write)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+
+    #[test]
+    fn test_parse() {
+        let var = |name: &str, redact| EnvVar {
+            name: name.into(),
+            redact,
+        };
+        assert_eq!(EnvVar::parse("RUST_LOG"), Ok(var("RUST_LOG", false)));
+        assert_eq!(
+            EnvVar::parse("DATABASE_URL:redact"),
+            Ok(var("DATABASE_URL", true))
+        );
+        assert_eq!(EnvVar::parse("api_token"), Ok(var("api_token", true)));
+        assert!(EnvVar::parse("RUST_LOG:other").is_err());
+        assert!(EnvVar::parse(":redact").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let vars = EnvVars {
+            vars: vec![
+                (
+                    EnvVar::parse("RUST_LOG").unwrap(),
+                    Some("debug|trace".into()),
+                ),
+                (
+                    EnvVar::parse("DATABASE_URL:redact").unwrap(),
+                    Some("postgres://a:b@db".into()),
+                ),
+                (EnvVar::parse("FEATURES").unwrap(), None),
+            ],
+        };
+        let mut out = vec![];
+        vars.render(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"**Environment:**
+
+| Variable | Value |
+| -------- | ----- |
+| `RUST_LOG` | `debug\|trace` |
+| `DATABASE_URL` | *redacted* |
+| `FEATURES` | *not set* |
+
+"#
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt::{Display, Formatter};
+
+/// An error of processing test results into a report.
+#[derive(Debug)]
+pub enum Error {
+    /// A line of the input is broken, like a record missing the name of its test.
+    Parse {
+        /// The line of the current input, starting with 1.
+        line: u64,
+        message: String,
+    },
+    /// Reading or writing failed, like writing the report.
+    Io(std::io::Error),
+    /// Rendering the report failed, like an addon.
+    Render(anyhow::Error),
+}
+
+impl Error {
+    /// The exit code of the command line tool, following `sysexits.h`.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Parse { .. } => 65,
+            Self::Io(_) => 74,
+            Self::Render(_) => 1,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse { line, message } => write!(f, "Invalid input, line {}: {}", line, message),
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            Self::Render(err) => write!(f, "Failed to render the report: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse { .. } => None,
+            Self::Io(err) => Some(err),
+            Self::Render(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Errors of rendering, which are caused by failing I/O, are I/O errors.
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<Self>() {
+            Ok(err) => err,
+            Err(err) => match err.downcast::<std::io::Error>() {
+                Ok(err) => Self::Io(err),
+                Err(err) => Self::Render(err),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_anyhow() {
+        let io = std::io::Error::new(std::io::ErrorKind::StorageFull, "disk full");
+        assert!(matches!(Error::from(anyhow::Error::from(io)), Error::Io(_)));
+
+        let parse = Error::Parse {
+            line: 3,
+            message: "missing field `name`".into(),
+        };
+        let err = Error::from(anyhow::Error::from(parse));
+        assert_eq!(
+            err.to_string(),
+            "Invalid input, line 3: missing field `name`"
+        );
+        assert_eq!(err.exit_code(), 65);
+
+        let err = Error::from(anyhow::anyhow!("addon failed"));
+        assert_eq!(err.to_string(), "Failed to render the report: addon failed");
+        assert_eq!(err.exit_code(), 1);
+        let source = std::error::Error::source(&err).map(ToString::to_string);
+        assert_eq!(source.as_deref(), Some("addon failed"));
+    }
+}
@@ -1,16 +1,179 @@
 use serde::{
     de::{self, Error},
-    Deserialize, Deserializer,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
-use std::{fmt, time::Duration};
+use std::{fmt, io::BufRead, time::Duration};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Record {
     Suite(suite::Event),
     Test(test::Event),
+    /// A record this version doesn't know, like of a newer test harness. Can't be serialized.
+    #[serde(skip)]
+    Unknown(Kind),
 }
 
+/// The kind of a record, its type and event.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize)]
+pub struct Kind {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub event: Option<String>,
+}
+
+/// The event of the known types of records.
+const KNOWN: &[(&str, &[&str])] = &[
+    ("suite", &["started", "ok", "failed"]),
+    ("test", &["started", "ok", "failed", "ignored"]),
+];
+
+impl Kind {
+    /// Parse the kind of a record, if the line is one.
+    fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str(line).ok()
+    }
+
+    fn is_known(&self) -> bool {
+        KNOWN.iter().any(|(kind, events)| {
+            *kind == self.kind
+                && self
+                    .event
+                    .as_deref()
+                    .is_some_and(|event| events.contains(&event))
+        })
+    }
+}
+
+/// Check if a line, which failed to parse, is a broken record of a known kind, rather than some
+/// other output.
+pub fn is_broken(line: &str) -> bool {
+    Kind::parse(line).is_some_and(|kind| kind.is_known())
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.event {
+            Some(event) => write!(f, "{}/{}", self.kind, event),
+            None => f.write_str(&self.kind),
+        }
+    }
+}
+
+#[cfg(not(feature = "simd-json"))]
+type JsonError = serde_json::Error;
+#[cfg(feature = "simd-json")]
+type JsonError = simd_json::Error;
+
+/// An error parsing a record, which is displayed the same way by both parsers.
+#[derive(Debug)]
+pub struct ParseError(JsonError);
+
+impl fmt::Display for ParseError {
+    #[cfg(not(feature = "simd-json"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+
+    #[cfg(feature = "simd-json")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use simd_json::ErrorType;
+        // the position of serde_json, a record being a single line
+        match self.0.error() {
+            ErrorType::Serde(message) => f.write_str(message),
+            ErrorType::Io(err) => fmt::Display::fmt(err, f),
+            error => write!(f, "{:?} at line 1 column {}", error, self.0.index() + 1),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Parses lines of JSON test output into records.
+#[derive(Debug, Default)]
+pub struct Parser {
+    #[cfg(feature = "simd-json")]
+    buffer: Vec<u8>,
+}
+
+impl Parser {
+    /// Parse a line, which fails if it isn't a record, or is a broken one of a known kind.
+    pub fn parse(&mut self, line: &str) -> Result<Record, ParseError> {
+        self.parse_known(line)
+            .or_else(|err| match Kind::parse(line) {
+                Some(kind) if !kind.is_known() => Ok(Record::Unknown(kind)),
+                _ => Err(err),
+            })
+    }
+
+    #[cfg(not(feature = "simd-json"))]
+    fn parse_known(&mut self, line: &str) -> Result<Record, ParseError> {
+        serde_json::from_str(line).map_err(ParseError)
+    }
+
+    #[cfg(feature = "simd-json")]
+    fn parse_known(&mut self, line: &str) -> Result<Record, ParseError> {
+        // simd-json parses in place, so we need a mutable copy, re-using the buffer
+        self.buffer.clear();
+        self.buffer.extend_from_slice(line.as_bytes());
+        simd_json::serde::from_slice(&mut self.buffer).map_err(ParseError)
+    }
+}
+
+#[cfg(not(feature = "simd-json"))]
+fn io_error(err: std::io::Error) -> ParseError {
+    ParseError(JsonError::io(err))
+}
+
+#[cfg(feature = "simd-json")]
+fn io_error(err: std::io::Error) -> ParseError {
+    ParseError(JsonError::from(err))
+}
+
+/// Iterate over the records of JSON test output, without processing them any further.
+///
+/// Lines which aren't records, like the output of cargo, are skipped. Broken records of a known
+/// kind, and failing to read, are errors.
+pub fn iter_records(read: impl BufRead) -> impl Iterator<Item = Result<Record, ParseError>> {
+    let mut parser = Parser::default();
+    read.lines().filter_map(move |line| match line {
+        Ok(line) => match parser.parse(&line) {
+            Ok(record) => Some(Ok(record)),
+            Err(err) if is_broken(&line) => Some(Err(err)),
+            Err(_) => None,
+        },
+        Err(err) => Some(Err(io_error(err))),
+    })
+}
+
+/// Parse a duration like `0.123s`, `15ms`, `20µs`, `100ns`, or plain seconds like `0.123`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    const UNITS: &[(&str, f64)] = &[
+        ("ns", 1e-9),
+        ("µs", 1e-6),
+        ("us", 1e-6),
+        ("ms", 1e-3),
+        ("s", 1.0),
+    ];
+
+    let value = value.trim();
+    let (value, factor) = UNITS
+        .iter()
+        .find_map(|(unit, factor)| Some((value.strip_suffix(unit)?, *factor)))
+        .unwrap_or((value, 1.0));
+    let value = value.trim_end().parse::<f64>().ok()?;
+    Duration::try_from_secs_f64(value * factor).ok()
+}
+
+/// Deserialize a duration, which depending on the toolchain is fractional seconds like libtest
+/// uses, integer nanoseconds, or a string like `"0.123s"`.
+///
+/// libtest would only print an integer for a whole number of seconds, which is next to
+/// impossible at nanosecond resolution.
 fn from_duration<'de, D>(d: D) -> Result<Duration, D::Error>
 where
     D: Deserializer<'de>,
@@ -21,24 +184,64 @@ where
         type Value = Duration;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a string containing json data")
+            formatter.write_str("a duration in seconds or nanoseconds, or a string like \"0.123s\"")
         }
 
         fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
         where
             E: Error,
         {
-            Ok(Duration::from_secs_f64(v))
+            Duration::try_from_secs_f64(v)
+                .map_err(|_| E::invalid_value(de::Unexpected::Float(v), &self))
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(Duration::from_nanos(v))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            u64::try_from(v)
+                .map(Duration::from_nanos)
+                .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            parse_duration(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
         }
     }
 
-    d.deserialize_f64(DurationVisitor)
+    d.deserialize_any(DurationVisitor)
+}
+
+/// Serialize a duration as fractional seconds, like libtest does
+fn to_duration<S>(duration: &Duration, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_f64(duration.as_secs_f64())
+}
+
+impl Record {
+    /// Serialize the record into a line of libtest JSON
+    pub fn to_line(&self) -> String {
+        // serializing plain data into a string can't fail
+        serde_json::to_string(self).unwrap_or_default()
+    }
 }
 
 pub mod suite {
     use super::*;
 
-    #[derive(Clone, Debug, Deserialize)]
+    #[derive(Clone, Debug, Deserialize, Serialize)]
     #[serde(tag = "event", rename_all = "lowercase")]
     pub enum Event {
         Started {
@@ -47,22 +250,37 @@ pub mod suite {
         Ok {
             passed: u64,
             failed: u64,
-            #[serde(default)]
+            #[serde(default, skip_serializing)]
+            #[allow(dead_code)]
             allowed_fail: u64,
             ignored: u64,
+            #[serde(default)]
             measured: u64,
+            #[serde(default)]
             filtered_out: u64,
-            #[serde(deserialize_with = "from_duration")]
+            #[serde(
+                default,
+                deserialize_with = "from_duration",
+                serialize_with = "to_duration"
+            )]
             exec_time: Duration,
         },
         Failed {
             passed: u64,
             failed: u64,
-            #[serde(default)]
+            #[serde(default, skip_serializing)]
+            #[allow(dead_code)]
             allowed_fail: u64,
             ignored: u64,
+            #[serde(default)]
+            measured: u64,
+            #[serde(default)]
             filtered_out: u64,
-            #[serde(deserialize_with = "from_duration")]
+            #[serde(
+                default,
+                deserialize_with = "from_duration",
+                serialize_with = "to_duration"
+            )]
             exec_time: Duration,
         },
     }
@@ -70,8 +288,9 @@ pub mod suite {
 
 pub mod test {
     use super::*;
+    use chrono::{DateTime, FixedOffset};
 
-    #[derive(Clone, Debug, Deserialize)]
+    #[derive(Clone, Debug, Deserialize, Serialize)]
     #[serde(tag = "event", rename_all = "lowercase")]
     pub enum Event {
         Started {
@@ -79,15 +298,136 @@ pub mod test {
         },
         Ok {
             name: String,
-            #[serde(deserialize_with = "from_duration")]
+            /// Missing with custom harnesses, like libtest-mimic
+            #[serde(
+                default,
+                deserialize_with = "from_duration",
+                serialize_with = "to_duration"
+            )]
             exec_time: Duration,
+            /// When the test started, only reported by some harnesses, like nextest
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            start_time: Option<DateTime<FixedOffset>>,
         },
         Failed {
             name: String,
-            #[serde(deserialize_with = "from_duration")]
+            #[serde(
+                default,
+                deserialize_with = "from_duration",
+                serialize_with = "to_duration"
+            )]
             exec_time: Duration,
-            #[serde(default)]
+            #[serde(default, skip_serializing_if = "String::is_empty")]
             stdout: String,
+            /// The reason of the failure, like a `should_panic` test which didn't panic
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            message: Option<String>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            start_time: Option<DateTime<FixedOffset>>,
+        },
+        Ignored {
+            name: String,
+            /// The reason, like of `#[ignore = "reason"]`
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            message: Option<String>,
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let lines = [
+            r#"{"type":"suite","event":"started","test_count":2}"#,
+            r#"{"type":"test","event":"started","name":"tests::a"}"#,
+            r#"{"type":"test","event":"ok","name":"tests::a","exec_time":0.25}"#,
+            r#"{"type":"test","event":"failed","name":"tests::b","exec_time":0.5,"stdout":"boom"}"#,
+            r#"{"type":"test","event":"ignored","name":"tests::c","message":"slow"}"#,
+            r#"{"type":"suite","event":"failed","passed":1,"failed":1,"ignored":1,"measured":0,"filtered_out":0,"exec_time":0.75}"#,
+        ];
+        let mut parser = Parser::default();
+        for line in lines {
+            assert_eq!(parser.parse(line).unwrap().to_line(), line);
+        }
+    }
+
+    #[test]
+    fn test_unknown() {
+        let mut parser = Parser::default();
+        let unknown = |line| match Parser::default().parse(line) {
+            Ok(Record::Unknown(unknown)) => Some(unknown.to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            unknown(r#"{"type":"bench","name":"b","median":10,"deviation":1}"#).as_deref(),
+            Some("bench")
+        );
+        assert_eq!(
+            unknown(r#"{"type":"test","event":"timeout","name":"tests::a"}"#).as_deref(),
+            Some("test/timeout")
+        );
+        // broken records of a known kind, and other JSON, are errors
+        assert!(parser.parse(r#"{"type":"test","event":"ok"}"#).is_err());
+        assert!(is_broken(r#"{"type":"test","event":"ok"}"#));
+        assert!(!is_broken(r#"{"reason":"compiler-artifact"}"#));
+        assert!(parser.parse(r#"{"reason":"compiler-artifact"}"#).is_err());
+        assert!(parser.parse("test tests::a ... ok").is_err());
+    }
+
+    #[test]
+    fn test_durations() {
+        let exec_time = |value: &str| {
+            let line = format!(
+                r#"{{"type":"test","event":"ok","name":"tests::a","exec_time":{}}}"#,
+                value
+            );
+            match Parser::default().parse(&line) {
+                Ok(Record::Test(test::Event::Ok { exec_time, .. })) => Some(exec_time),
+                _ => None,
+            }
+        };
+        assert_eq!(exec_time("0.25"), Some(Duration::from_millis(250)));
+        assert_eq!(exec_time("1.0"), Some(Duration::from_secs(1)));
+        assert_eq!(exec_time("123000000"), Some(Duration::from_millis(123)));
+        assert_eq!(exec_time("0"), Some(Duration::ZERO));
+        assert_eq!(exec_time(r#""0.123s""#), Some(Duration::from_millis(123)));
+        assert_eq!(exec_time(r#""15ms""#), Some(Duration::from_millis(15)));
+        assert_eq!(exec_time(r#""20µs""#), Some(Duration::from_micros(20)));
+        assert_eq!(exec_time(r#""100ns""#), Some(Duration::from_nanos(100)));
+        assert_eq!(exec_time(r#""2""#), Some(Duration::from_secs(2)));
+        assert_eq!(exec_time("-1.5"), None);
+        assert_eq!(exec_time("-1"), None);
+        assert_eq!(exec_time(r#""fast""#), None);
+    }
+
+    #[test]
+    fn test_iter_records() {
+        let input = r#"{"type":"suite","event":"started","test_count":1}
+running 1 test
+{"type":"bench","name":"b","median":10,"deviation":1}
+{"type":"test","event":"ok","name":"tests::a"}
+{"type":"test","event":"ok"}
+"#;
+        let records = iter_records(input.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(records.len(), 4);
+        assert!(matches!(
+            records[0],
+            Ok(Record::Suite(suite::Event::Started { test_count: 1 }))
+        ));
+        assert!(matches!(records[1], Ok(Record::Unknown(_))));
+        assert!(
+            matches!(&records[2], Ok(Record::Test(test::Event::Ok { name, .. })) if name == "tests::a")
+        );
+        // the same message with either parser
+        assert_eq!(
+            records[3].as_ref().unwrap_err().to_string(),
+            "missing field `name`"
+        );
+
+        // not UTF-8
+        assert!(iter_records(&b"\xff\n"[..]).next().unwrap().is_err());
+    }
+}
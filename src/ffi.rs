@@ -0,0 +1,100 @@
+use crate::processor::{self, ProcessOptions};
+use std::{
+    ffi::{c_char, CStr, CString},
+    ptr,
+};
+
+/// Pass a string to the caller, who releases it using [`mtr_free_string`].
+fn into_raw(string: String) -> *mut c_char {
+    // C strings can't contain a null byte, and the output of the tests might
+    CString::new(string.replace('\0', ""))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// Render the markdown report of the JSON output of the tests.
+///
+/// Returns the report, or null on failure, setting `error` to the message, unless it's null. Both
+/// must be released using [`mtr_free_string`].
+///
+/// # Safety
+///
+/// `json` must be a null-terminated string, and `error` either null or valid to be written to.
+#[no_mangle]
+pub unsafe extern "C" fn mtr_render_markdown(
+    json: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut c_char {
+    let result = match json.is_null() {
+        true => Err("The input is null".to_string()),
+        false => CStr::from_ptr(json)
+            .to_str()
+            .map_err(|err| format!("The input isn't UTF-8: {}", err)),
+    }
+    .and_then(|json| {
+        processor::render_str(
+            json,
+            ProcessOptions {
+                disable_front_matter: true,
+                ..Default::default()
+            },
+        )
+        .map_err(|err| err.to_string())
+    });
+
+    match result {
+        Ok(report) => into_raw(report),
+        Err(message) => {
+            if !error.is_null() {
+                *error = into_raw(message);
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Release a string returned by the library. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must be returned by the library, and not be released before.
+#[no_mangle]
+pub unsafe extern "C" fn mtr_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let json = CString::new(
+            r#"{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.1 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.1 }
+"#,
+        )
+        .unwrap();
+        let mut error = ptr::null_mut();
+        unsafe {
+            let report = mtr_render_markdown(json.as_ptr(), &mut error);
+            assert!(error.is_null());
+            let markdown = CStr::from_ptr(report).to_str().unwrap();
+            assert!(markdown.contains("## ✅ tests::a"), "{}", markdown);
+            mtr_free_string(report);
+
+            let json = CString::new(r#"{ "type": "test", "event": "failed" }"#).unwrap();
+            assert!(mtr_render_markdown(json.as_ptr(), &mut error).is_null());
+            assert_eq!(
+                CStr::from_ptr(error).to_str().unwrap(),
+                "Invalid input, line 1: missing field `name`"
+            );
+            mtr_free_string(error);
+
+            assert!(mtr_render_markdown(ptr::null(), ptr::null_mut()).is_null());
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::event::{suite, test, Parser, Record};
+use crate::input;
+use crate::invocation::Invocation;
+use regex::Regex;
+use std::{io::Write, path::Path, time::Duration};
+
+/// Which tests to keep.
+#[derive(Debug, Default)]
+pub struct Filter {
+    /// Only keep failed tests.
+    pub failed_only: bool,
+    /// Only keep tests with a matching name.
+    pub name: Option<Regex>,
+}
+
+impl Filter {
+    fn matches(&self, event: &test::Event) -> bool {
+        let name = match event {
+            test::Event::Failed { name, .. } => name,
+            _ if self.failed_only => return false,
+            test::Event::Started { name }
+            | test::Event::Ok { name, .. }
+            | test::Event::Ignored { name, .. } => name,
+        };
+        self.name.as_ref().is_none_or(|regex| regex.is_match(name))
+    }
+}
+
+/// The current suite, collecting the selected tests until it finished.
+#[derive(Debug, Default)]
+struct State {
+    /// The line announcing the suite, if there was one.
+    invocation: Option<String>,
+    started: bool,
+    /// The tests which started, but didn't finish yet.
+    running: Vec<String>,
+    tests: Vec<test::Event>,
+    /// The number of tests which were not selected.
+    removed: u64,
+}
+
+impl State {
+    /// Write the suite, if it contains any selected tests
+    fn write(&mut self, finished: Option<Duration>, write: &mut dyn Write) -> anyhow::Result<()> {
+        let state = std::mem::take(self);
+        if state.tests.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(invocation) = state.invocation {
+            writeln!(write, "{}", invocation)?;
+        }
+        let count =
+            |f: fn(&test::Event) -> bool| state.tests.iter().filter(|t| f(t)).count() as u64;
+        let passed = count(|test| matches!(test, test::Event::Ok { .. }));
+        let failed = count(|test| matches!(test, test::Event::Failed { .. }));
+        let ignored = count(|test| matches!(test, test::Event::Ignored { .. }));
+
+        let mut records = vec![Record::Suite(suite::Event::Started {
+            test_count: passed + failed + ignored,
+        })];
+        for test in state.tests {
+            if let test::Event::Ok { name, .. } | test::Event::Failed { name, .. } = &test {
+                records.push(Record::Test(test::Event::Started { name: name.clone() }));
+            }
+            records.push(Record::Test(test));
+        }
+        if let Some(exec_time) = finished {
+            let filtered_out = state.removed;
+            records.push(Record::Suite(match failed {
+                0 => suite::Event::Ok {
+                    passed,
+                    failed,
+                    allowed_fail: 0,
+                    ignored,
+                    measured: 0,
+                    filtered_out,
+                    exec_time,
+                },
+                _ => suite::Event::Failed {
+                    passed,
+                    failed,
+                    allowed_fail: 0,
+                    ignored,
+                    measured: 0,
+                    filtered_out,
+                    exec_time,
+                },
+            }));
+        }
+
+        for record in records {
+            writeln!(write, "{}", record.to_line())?;
+        }
+        Ok(())
+    }
+}
+
+/// Filter the tests of a libtest JSON input, writing the selected ones as libtest JSON.
+///
+/// The counts of the suites are adjusted to the selected tests, and suites without any are
+/// dropped. Lines announcing the suites are kept, so that the output can be rendered as before.
+pub fn filter(filter: &Filter, path: &Path, write: &mut dyn Write) -> anyhow::Result<()> {
+    let mut parser = Parser::default();
+    let mut state = State::default();
+
+    input::read_lines(input::open(path)?, |line| {
+        let record = match parser.parse(line) {
+            Ok(record) => record,
+            Err(_) => {
+                if Invocation::parse(line).is_some() {
+                    if state.started {
+                        state.write(None, write)?;
+                    }
+                    state.invocation = Some(line.trim_end().to_string());
+                }
+                return Ok(());
+            }
+        };
+
+        match record {
+            Record::Suite(suite::Event::Started { .. }) => {
+                if state.started {
+                    state.write(None, write)?;
+                }
+                state.started = true;
+            }
+            Record::Suite(
+                suite::Event::Ok {
+                    filtered_out,
+                    exec_time,
+                    ..
+                }
+                | suite::Event::Failed {
+                    filtered_out,
+                    exec_time,
+                    ..
+                },
+            ) => {
+                state.removed += filtered_out + state.running.len() as u64;
+                state.write(Some(exec_time), write)?;
+            }
+            Record::Test(test::Event::Started { name }) => state.running.push(name),
+            Record::Test(event) => {
+                if let test::Event::Ok { name, .. } | test::Event::Failed { name, .. } = &event {
+                    state.running.retain(|running| running != name);
+                }
+                match filter.matches(&event) {
+                    true => state.tests.push(event),
+                    false => state.removed += 1,
+                }
+            }
+            Record::Unknown(_) => {}
+        }
+        Ok(())
+    })?;
+
+    if state.started {
+        state.write(None, write)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(filter: &Filter, input: &str) -> String {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(input.as_bytes()).unwrap();
+        let mut out = vec![];
+        super::filter(filter, file.path(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_failed_only() {
+        let input = r#"     Running unittests src/lib.rs (target/debug/deps/foo-1234)
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::a" }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.25 }
+{ "type": "test", "event": "started", "name": "tests::b" }
+{ "type": "test", "name": "tests::b", "event": "failed", "exec_time": 0.5, "stdout": "boom" }
+{ "type": "test", "name": "tests::c", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 1, "exec_time": 1.0 }
+     Running tests/api.rs (target/debug/deps/api-1234)
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "d", "event": "ok", "exec_time": 0.25 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.25 }
+"#;
+        let filter = Filter {
+            failed_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            run(&filter, input),
+            r#"     Running unittests src/lib.rs (target/debug/deps/foo-1234)
+{"type":"suite","event":"started","test_count":1}
+{"type":"test","event":"started","name":"tests::b"}
+{"type":"test","event":"failed","name":"tests::b","exec_time":0.5,"stdout":"boom"}
+{"type":"suite","event":"failed","passed":0,"failed":1,"ignored":0,"measured":0,"filtered_out":3,"exec_time":1.0}
+"#
+        );
+    }
+
+    #[test]
+    fn test_name() {
+        let input = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "name": "api::a", "event": "ok", "exec_time": 0.25 }
+{ "type": "test", "name": "db::b", "event": "ok", "exec_time": 0.25 }
+"#;
+        let filter = Filter {
+            name: Some(Regex::new("^api::").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            run(&filter, input),
+            r#"{"type":"suite","event":"started","test_count":1}
+{"type":"test","event":"started","name":"api::a"}
+{"type":"test","event":"ok","name":"api::a","exec_time":0.25}
+"#
+        );
+    }
+}
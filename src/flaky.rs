@@ -0,0 +1,174 @@
+use crate::event::{test, Parser, Record};
+use crate::input;
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// The history of a test, across all results.
+#[derive(Debug, Default)]
+struct History {
+    /// The outcomes of the test, in the order of the results, `true` if the test failed.
+    failed: Vec<bool>,
+}
+
+impl History {
+    fn failures(&self) -> usize {
+        self.failed.iter().filter(|failed| **failed).count()
+    }
+
+    fn failure_rate(&self) -> f64 {
+        self.failures() as f64 / self.failed.len() as f64
+    }
+
+    /// The number of times the outcome changed from one result to the next
+    fn flips(&self) -> usize {
+        self.failed.windows(2).filter(|w| w[0] != w[1]).count()
+    }
+
+    /// How often the outcome changed, relative to the possible changes
+    fn intermittency(&self) -> f64 {
+        match self.failed.len() {
+            0 | 1 => 0.0,
+            len => self.flips() as f64 / (len - 1) as f64,
+        }
+    }
+
+    /// A test is flaky if it both passed and failed
+    fn is_flaky(&self) -> bool {
+        let failures = self.failures();
+        failures > 0 && failures < self.failed.len()
+    }
+}
+
+/// The past results in a directory, ordered by their names.
+fn results(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+    Ok(paths)
+}
+
+/// Record the outcomes of all tests of a single result
+fn record(
+    path: &Path,
+    parser: &mut Parser,
+    tests: &mut HashMap<String, History>,
+) -> anyhow::Result<()> {
+    input::read_records(path, |line| {
+        let (name, failed) = match parser.parse(line) {
+            Ok(Record::Test(test::Event::Ok { name, .. })) => (name, false),
+            Ok(Record::Test(test::Event::Failed { name, .. })) => (name, true),
+            _ => return Ok(()),
+        };
+        tests.entry(name).or_default().failed.push(failed);
+        Ok(())
+    })
+}
+
+/// Render a report of the flaky tests in the past results of a directory.
+///
+/// Tests are ranked by how often their outcome changed between results, then by their failure
+/// rate. Tests which always failed are broken rather than flaky, and not listed.
+pub fn flaky(dir: &Path, top: usize, write: &mut dyn Write) -> anyhow::Result<()> {
+    let paths = results(dir)?;
+    let mut parser = Parser::default();
+    let mut tests = HashMap::new();
+    for path in &paths {
+        log::debug!("Reading from: {}", path.display());
+        record(path, &mut parser, &mut tests)?;
+    }
+
+    let mut flaky = tests
+        .iter()
+        .filter(|(_, history)| history.is_flaky())
+        .collect::<Vec<_>>();
+    flaky.sort_by(|(a_name, a), (b_name, b)| {
+        b.intermittency()
+            .total_cmp(&a.intermittency())
+            .then(b.failure_rate().total_cmp(&a.failure_rate()))
+            .then(a_name.cmp(b_name))
+    });
+
+    writeln!(write, "# Top flaky tests")?;
+    writeln!(write)?;
+    writeln!(
+        write,
+        "Out of {} tests in {} results, {} are flaky.",
+        tests.len(),
+        paths.len(),
+        flaky.len()
+    )?;
+
+    if flaky.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(write)?;
+    writeln!(
+        write,
+        "| Test | Runs | Failures | Failure rate | Flips | Intermittency |"
+    )?;
+    writeln!(
+        write,
+        "| ---- | ---: | -------: | -----------: | ----: | ------------: |"
+    )?;
+    for (name, history) in flaky.into_iter().take(top) {
+        writeln!(
+            write,
+            "| `{}` | {} | {} | {:.0}% | {} | {:.0}% |",
+            name,
+            history.failed.len(),
+            history.failures(),
+            history.failure_rate() * 100.0,
+            history.flips(),
+            history.intermittency() * 100.0,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(outcomes: &[(&str, bool)]) -> String {
+        outcomes
+            .iter()
+            .map(|(name, failed)| {
+                format!(
+                    r#"{{ "type": "test", "name": "{}", "event": "{}", "exec_time": 0.25 }}"#,
+                    name,
+                    if *failed { "failed" } else { "ok" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_flaky() {
+        let dir = tempfile::tempdir().unwrap();
+        let results = [
+            result(&[("a", false), ("b", true), ("c", true), ("d", false)]),
+            result(&[("a", true), ("b", true), ("c", true), ("d", false)]),
+            result(&[("a", false), ("b", false), ("c", true), ("d", false)]),
+            result(&[("a", true), ("b", false), ("c", true)]),
+        ];
+        for (idx, content) in results.iter().enumerate() {
+            std::fs::write(dir.path().join(format!("{}.json", idx)), content).unwrap();
+        }
+
+        let mut out = vec![];
+        flaky(dir.path(), 10, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("Out of 4 tests in 4 results, 2 are flaky."));
+        assert!(out.contains("| `a` | 4 | 2 | 50% | 3 | 100% |\n| `b` | 4 | 2 | 50% | 1 | 33% |\n"));
+        assert!(!out.contains("`c`"));
+    }
+}
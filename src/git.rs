@@ -1,18 +1,38 @@
 use anyhow::anyhow;
 use chrono::{DateTime, FixedOffset, Utc};
-use git2::{Commit, Repository};
+use git2::{
+    Commit, DescribeFormatOptions, DescribeOptions, Oid, Reference, Repository, Status,
+    StatusOptions,
+};
 use std::{
+    collections::HashMap,
     io::Write,
     path::{Path, PathBuf},
     time::{Duration, UNIX_EPOCH},
 };
 
+/// The maximum number of commits listed in the changelog
+const MAX_CHANGELOG: usize = 50;
+
+/// The options of the `[addon.git]` section of the configuration.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub changes: bool,
+    pub changelog: bool,
+    pub position: Option<crate::processor::Position>,
+}
+
 #[derive(Debug)]
 pub struct GitInfo {
     /// Path to the repository.
     path: PathBuf,
     /// If the operation is required. If not, it will fail silently.
     required: bool,
+    /// If the paths with uncommitted changes should be listed.
+    changes: bool,
+    /// If the commits since the last tag should be listed.
+    changelog: bool,
 }
 
 impl GitInfo {
@@ -20,24 +40,239 @@ impl GitInfo {
         Self {
             path: path.into(),
             required,
+            changes: false,
+            changelog: false,
+        }
+    }
+
+    pub fn with_changes(mut self, changes: bool) -> Self {
+        self.changes = changes;
+        self
+    }
+
+    pub fn with_changelog(mut self, changelog: bool) -> Self {
+        self.changelog = changelog;
+        self
+    }
+
+    fn render_changelog(
+        &self,
+        write: &mut dyn Write,
+        repo: &Repository,
+        head: Oid,
+        web_url: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let tags = tag_targets(repo)?.into_iter().collect::<HashMap<_, _>>();
+
+        let mut walk = repo.revwalk()?;
+        walk.push(head)?;
+        let mut walk: Box<dyn Iterator<Item = Result<Commit, git2::Error>>> =
+            Box::new(walk.map(|id| id.and_then(|id| repo.find_commit(id))));
+
+        let mut commits = Vec::<Commit>::new();
+        let mut since = None;
+        let mut truncated = false;
+        while let Some(commit) = walk.next() {
+            let commit = match commit {
+                Ok(commit) => commit,
+                Err(err) => {
+                    // the walk fails at the end of a shallow history, before reaching it, so
+                    // follow the first parents as far as they are available
+                    log::debug!("The history is truncated: {}", err);
+                    truncated = true;
+                    let start = match commits.last() {
+                        Some(commit) => commit.parent(0).ok(),
+                        None => repo.find_commit(head).ok(),
+                    };
+                    walk = Box::new(
+                        std::iter::successors(start, |commit| commit.parent(0).ok()).map(Ok),
+                    );
+                    continue;
+                }
+            };
+            // skip tags on HEAD itself, listing the changes that lead up to it
+            if commit.id() != head {
+                if let Some(tag) = tags.get(&commit.id()) {
+                    since = Some(tag.clone());
+                    break;
+                }
+            }
+            commits.push(commit);
+            if commits.len() > MAX_CHANGELOG {
+                break;
+            }
+        }
+
+        writeln!(write)?;
+        match &since {
+            Some(tag) => writeln!(write, "**Changes since `{}`:**", tag)?,
+            None => writeln!(write, "**Changes:**")?,
+        }
+        writeln!(write)?;
+
+        for commit in commits.iter().take(MAX_CHANGELOG) {
+            let id = commit.as_object().short_id()?;
+            let id = id.as_str().unwrap_or_default();
+            let summary = String::from_utf8_lossy(commit.summary_bytes().unwrap_or_default());
+            match web_url.and_then(|url| commit_url(url, commit.id())) {
+                Some(url) => writeln!(write, "* [`{}`]({}) {}", id, url, summary.trim())?,
+                None => writeln!(write, "* `{}` {}", id, summary.trim())?,
+            }
+        }
+        if commits.len() > MAX_CHANGELOG {
+            writeln!(write, "* …")?;
+        } else if truncated {
+            writeln!(write, "* … (the history is truncated)")?;
+        }
+
+        Ok(())
+    }
+
+    fn render_submodules(&self, write: &mut dyn Write, repo: &Repository) -> anyhow::Result<()> {
+        let submodules = repo.submodules()?;
+        if submodules.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(write)?;
+        writeln!(write, "**Submodules:**")?;
+        writeln!(write)?;
+        writeln!(write, "| Path | Commit | URL |")?;
+        writeln!(write, "| ---- | ------ | --- |")?;
+
+        for submodule in submodules {
+            let commit = match (submodule.head_id(), submodule.workdir_id()) {
+                (Some(head), Some(workdir)) if head != workdir => {
+                    format!("`{}` (⚠️ checked out `{}`)", head, workdir)
+                }
+                (Some(head), _) => format!("`{}`", head),
+                (None, Some(workdir)) => format!("`{}`", workdir),
+                (None, None) => "<unknown>".to_string(),
+            };
+            writeln!(
+                write,
+                "| `{}` | {} | `{}` |",
+                submodule.path().display(),
+                commit,
+                submodule.url().unwrap_or("<unknown>")
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn render_worktree(&self, write: &mut dyn Write, repo: &Repository) -> anyhow::Result<()> {
+        if repo.is_bare() {
+            return Ok(());
+        }
+
+        // untracked files are left out, like the input and the output of this tool
+        let statuses = repo.statuses(Some(
+            StatusOptions::new()
+                .include_untracked(false)
+                .include_ignored(false),
+        ))?;
+
+        let changes = statuses
+            .iter()
+            .filter(|entry| entry.status() != Status::CURRENT)
+            .collect::<Vec<_>>();
+
+        writeln!(write)?;
+        if changes.is_empty() {
+            writeln!(write, "**Worktree:** clean")?;
+            return Ok(());
+        }
+
+        writeln!(
+            write,
+            "**Worktree:** ⚠️ uncommitted changes in {} {}",
+            changes.len(),
+            if changes.len() == 1 { "file" } else { "files" }
+        )?;
+
+        if self.changes {
+            writeln!(write)?;
+            for entry in changes {
+                writeln!(
+                    write,
+                    "* `{}` ({})",
+                    String::from_utf8_lossy(entry.path_bytes()),
+                    describe_status(entry.status())
+                )?;
+            }
         }
+
+        Ok(())
     }
 
-    fn render_commit(&self, write: &mut dyn Write, commit: &Commit) -> anyhow::Result<()> {
+    fn render_refs(
+        &self,
+        write: &mut dyn Write,
+        repo: &Repository,
+        head: &Reference,
+    ) -> anyhow::Result<()> {
+        let mut refs = vec![];
+
+        if head.is_branch() {
+            if let Some(branch) = head.shorthand() {
+                refs.push(format!("**Branch:** `{}`", branch));
+            }
+        }
+
+        if let Some(id) = head.target() {
+            let tags = tags_pointing_at(repo, id).unwrap_or_else(|err| {
+                log::debug!("Unable to list tags: {}", err);
+                vec![]
+            });
+            if !tags.is_empty() {
+                let tags = tags
+                    .iter()
+                    .map(|tag| format!("`{}`", tag))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                refs.push(format!("**Tags:** {}", tags));
+            }
+        }
+
+        // fails when there are no tags to describe the commit with
+        if let Ok(describe) = repo.describe(DescribeOptions::new().describe_tags()) {
+            let describe =
+                describe.format(Some(DescribeFormatOptions::new().dirty_suffix("-dirty")))?;
+            refs.push(format!("**Describe:** `{}`", describe));
+        }
+
+        if !refs.is_empty() {
+            writeln!(write, "{}", refs.join(" · "))?;
+            writeln!(write)?;
+        }
+
+        Ok(())
+    }
+
+    fn render_commit(
+        &self,
+        write: &mut dyn Write,
+        commit: &Commit,
+        web_url: Option<&str>,
+    ) -> anyhow::Result<()> {
         let tz = FixedOffset::west_opt(commit.time().offset_minutes() * 60)
             .ok_or_else(|| anyhow!("Unable to calculate commit date timestamp"))?;
         let time =
             DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_secs(commit.time().seconds() as u64))
                 .with_timezone(&tz);
 
-        writeln!(write, "    Commit: {}", commit.id())?;
-        writeln!(write, "    Author: {}", commit.author())?;
-        writeln!(write, "    Date: {}", time.to_rfc2822())?;
+        match web_url.and_then(|url| commit_url(url, commit.id())) {
+            Some(url) => writeln!(write, "**Commit:** [`{}`]({})  ", commit.id(), url)?,
+            None => writeln!(write, "**Commit:** `{}`  ", commit.id())?,
+        }
+        writeln!(write, "**Author:** `{}`  ", commit.author())?;
+        writeln!(write, "**Date:** {}", time.to_rfc2822())?;
 
         writeln!(write)?;
 
         for line in String::from_utf8_lossy(commit.message_bytes()).lines() {
-            writeln!(write, "        {}", line)?;
+            writeln!(write, "    {}", line)?;
         }
 
         Ok(())
@@ -46,29 +281,170 @@ impl GitInfo {
     fn render_git(&self, write: &mut dyn Write) -> anyhow::Result<()> {
         let repo = Repository::open(&self.path)?;
 
-        let remote = repo.find_remote("origin")?;
-        writeln!(
-            write,
-            "**Git:** `{repo}` @ `{ref}`",
-            repo = remote.url().unwrap_or("<unknown>"),
-            ref = repo.head()?.name().unwrap_or("<unknown>")
-        )?;
+        // CI checkouts may be shallow, detached, or lack a remote called "origin"
+        let remote_url = remote_url(&repo);
+        let head = match repo.head() {
+            Ok(head) => Some(head),
+            Err(err) => {
+                log::debug!("Unable to resolve HEAD: {}", err);
+                None
+            }
+        };
+
+        let name = match &head {
+            Some(_) if repo.head_detached().unwrap_or_default() => "HEAD (detached)",
+            Some(head) => head.name().unwrap_or("<unknown>"),
+            None => "<no commits>",
+        };
+
+        write!(write, "**Git:** ")?;
+        if let Some(url) = &remote_url {
+            write!(write, "`{}` @ ", url)?;
+        }
+        write!(write, "`{}`", name)?;
+
+        let mut notes = vec![];
+        if repo.is_shallow() {
+            notes.push("shallow clone");
+        }
+        if repo.is_worktree() {
+            notes.push("worktree");
+        }
+        if !notes.is_empty() {
+            write!(write, " ({})", notes.join(", "))?;
+        }
+        writeln!(write)?;
         writeln!(write)?;
 
-        let commit = repo
-            .head()?
-            .target()
-            .map(|id| repo.find_commit(id))
-            .transpose()?;
+        if let Some(head) = &head {
+            self.render_refs(write, &repo, head)?;
 
-        if let Some(commit) = commit {
-            self.render_commit(write, &commit)?;
+            let web_url = remote_url.as_deref().and_then(web_url);
+            if let Ok(commit) = head.peel_to_commit() {
+                self.render_commit(write, &commit, web_url.as_deref())?;
+
+                if self.changelog {
+                    if let Err(err) =
+                        self.render_changelog(write, &repo, commit.id(), web_url.as_deref())
+                    {
+                        log::debug!("Unable to list the changes: {}", err);
+                    }
+                }
+            }
+        }
+
+        if let Err(err) = self.render_submodules(write, &repo) {
+            log::debug!("Unable to list submodules: {}", err);
+        }
+
+        if let Err(err) = self.render_worktree(write, &repo) {
+            log::debug!("Unable to determine worktree status: {}", err);
         }
 
         Ok(())
     }
 }
 
+/// Find the URL of the remote, preferring "origin" over any other
+fn remote_url(repo: &Repository) -> Option<String> {
+    if let Ok(remote) = repo.find_remote("origin") {
+        return remote.url().map(ToString::to_string);
+    }
+
+    let remotes = repo.remotes().ok()?;
+    remotes
+        .iter()
+        .flatten()
+        .filter_map(|name| repo.find_remote(name).ok())
+        .find_map(|remote| remote.url().map(ToString::to_string))
+}
+
+/// Derive the web URL of a repository from the URL of its remote
+fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+
+    let (host, path) = if let Some((_, rest)) = remote.split_once("://") {
+        // https://host/owner/repo or ssh://git@host:port/owner/repo
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?;
+        let host = match remote.starts_with("http") {
+            true => host,
+            false => host.split(':').next()?,
+        };
+        (host, path)
+    } else {
+        // git@host:owner/repo
+        let (authority, path) = remote.split_once(':')?;
+        (authority.rsplit('@').next()?, path)
+    };
+
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+
+    Some(format!("https://{}/{}", host, path))
+}
+
+/// Create the link to a commit, using the URL patterns of the known forges
+fn commit_url(web_url: &str, id: Oid) -> Option<String> {
+    let host = web_url.strip_prefix("https://")?.split('/').next()?;
+
+    if host.contains("gitlab") {
+        Some(format!("{}/-/commit/{}", web_url, id))
+    } else if host.contains("github") || host.contains("gitea") || host.contains("codeberg") {
+        Some(format!("{}/commit/{}", web_url, id))
+    } else {
+        None
+    }
+}
+
+/// List all tags with the commit they point at
+fn tag_targets(repo: &Repository) -> anyhow::Result<Vec<(Oid, String)>> {
+    let mut tags = vec![];
+    for name in repo.tag_names(None)?.iter().flatten() {
+        if let Ok(commit) = repo
+            .revparse_single(&format!("refs/tags/{}", name))
+            .and_then(|object| object.peel_to_commit())
+        {
+            tags.push((commit.id(), name.to_string()));
+        }
+    }
+    Ok(tags)
+}
+
+/// Find the names of all tags pointing at a commit
+fn tags_pointing_at(repo: &Repository, id: Oid) -> anyhow::Result<Vec<String>> {
+    Ok(tag_targets(repo)?
+        .into_iter()
+        .filter(|(target, _)| *target == id)
+        .map(|(_, name)| name)
+        .collect())
+}
+
+/// The abbreviated id of the HEAD commit
+pub fn head_sha(path: &Path) -> Option<String> {
+    let repo = Repository::open(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    let id = commit.as_object().short_id().ok()?;
+    id.as_str().map(ToString::to_string)
+}
+
+/// Describe the status of a changed file
+fn describe_status(status: Status) -> &'static str {
+    if status.intersects(Status::INDEX_NEW) {
+        "added"
+    } else if status.intersects(Status::WT_DELETED | Status::INDEX_DELETED) {
+        "deleted"
+    } else if status.intersects(Status::WT_RENAMED | Status::INDEX_RENAMED) {
+        "renamed"
+    } else if status.intersects(Status::CONFLICTED) {
+        "conflicted"
+    } else {
+        "modified"
+    }
+}
+
 impl super::Addon for GitInfo {
     fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
         match self.render_git(write) {
@@ -77,3 +453,284 @@ impl super::Addon for GitInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use git2::{RepositoryInitOptions, Signature};
+
+    /// Create a repository, with its default branch `main`
+    fn init(path: &Path) -> Repository {
+        Repository::init_opts(path, RepositoryInitOptions::new().initial_head("main")).unwrap()
+    }
+
+    /// Commit a file to HEAD
+    fn commit(repo: &Repository, file: &str, message: &str) -> Oid {
+        std::fs::write(repo.workdir().unwrap().join(file), message).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    fn render(info: &GitInfo) -> String {
+        let mut output = vec![];
+        info.render_git(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_shallow() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init(dir.path());
+        let first = commit(&repo, "a.txt", "first");
+        let second = commit(&repo, "a.txt", "second");
+        commit(&repo, "a.txt", "third");
+
+        // like a clone of depth 2, which lacks the parent of its oldest commit
+        let git = dir.path().join(".git");
+        std::fs::write(git.join("shallow"), format!("{}\n", second)).unwrap();
+        let first = first.to_string();
+        std::fs::remove_file(git.join("objects").join(&first[..2]).join(&first[2..])).unwrap();
+
+        let output = render(&GitInfo::new(dir.path(), true).with_changelog(true));
+        assert!(output.contains("(shallow clone)"), "{}", output);
+        assert!(output.contains(" second\n"), "{}", output);
+        assert!(output.contains(" third\n"), "{}", output);
+        assert!(!output.contains(" first\n"), "{}", output);
+        assert!(
+            output.contains("* … (the history is truncated)\n"),
+            "{}",
+            output
+        );
+        assert!(output.contains("**Worktree:** clean"), "{}", output);
+    }
+
+    #[test]
+    fn test_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init(dir.path());
+        let first = commit(&repo, "a.txt", "first");
+        let second = commit(&repo, "a.txt", "second");
+
+        // no tags to describe the commit with
+        let output = render(&GitInfo::new(dir.path(), true));
+        assert!(output.contains("\n**Branch:** `main`\n"), "{}", output);
+
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repo.tag_lightweight("v1.0", &repo.find_object(first, None).unwrap(), false)
+            .unwrap();
+        let output = render(&GitInfo::new(dir.path(), true));
+        assert!(
+            output.contains("\n**Branch:** `main` · **Describe:** `v1.0-1-g"),
+            "{}",
+            output
+        );
+
+        let head = repo.find_object(second, None).unwrap();
+        repo.tag("v1.1", &head, &signature, "release", false)
+            .unwrap();
+        repo.tag_lightweight("latest", &head, false).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "patched").unwrap();
+        let output = render(&GitInfo::new(dir.path(), true));
+        assert!(
+            output.contains(
+                "\n**Branch:** `main` · **Tags:** `latest`, `v1.1` · **Describe:** `v1.1-dirty`\n"
+            ),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_submodules() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixtures = init(&dir.path().join("fixtures"));
+        let pinned = commit(&fixtures, "a.txt", "first");
+        let url = dir.path().join("fixtures").to_string_lossy().into_owned();
+
+        let repo = init(&dir.path().join("repo"));
+        commit(&repo, "a.txt", "first");
+        let mut submodule = repo.submodule(&url, Path::new("fixtures"), true).unwrap();
+        let checkout = submodule.clone(None).unwrap();
+        submodule.add_finalize().unwrap();
+        commit(&repo, "a.txt", "add fixtures");
+
+        let info = GitInfo::new(&dir.path().join("repo"), true);
+        let output = render(&info);
+        assert!(
+            output.contains(&format!(
+                "**Submodules:**\n\n| Path | Commit | URL |\n| ---- | ------ | --- |\n| `fixtures` | `{}` | `{}` |\n",
+                pinned, url
+            )),
+            "{}",
+            output
+        );
+
+        // a different commit checked out than pinned
+        let other = commit(&checkout, "a.txt", "second");
+        let output = render(&info);
+        assert!(
+            output.contains(&format!(
+                "| `fixtures` | `{}` (⚠️ checked out `{}`) |",
+                pinned, other
+            )),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_detached_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init(dir.path());
+        let id = commit(&repo, "a.txt", "first");
+        repo.set_head_detached(id).unwrap();
+
+        let output = render(&GitInfo::new(dir.path(), true));
+        assert!(
+            output.starts_with("**Git:** `HEAD (detached)`\n"),
+            "{}",
+            output
+        );
+        assert!(!output.contains("**Branch:**"), "{}", output);
+        assert!(
+            output.contains(&format!("**Commit:** `{}`", id)),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_remotes() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init(dir.path());
+        let id = commit(&repo, "a.txt", "first");
+
+        // without any remote
+        let output = render(&GitInfo::new(dir.path(), true));
+        assert!(
+            output.starts_with("**Git:** `refs/heads/main`\n"),
+            "{}",
+            output
+        );
+
+        // without "origin", another remote is used
+        repo.remote("upstream", "https://github.com/foo/bar.git")
+            .unwrap();
+        let output = render(&GitInfo::new(dir.path(), true));
+        assert!(
+            output.starts_with("**Git:** `https://github.com/foo/bar.git` @ `refs/heads/main`\n"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains(&format!("(https://github.com/foo/bar/commit/{})", id)),
+            "{}",
+            output
+        );
+
+        repo.remote("origin", "git@gitlab.com:foo/baz.git").unwrap();
+        let output = render(&GitInfo::new(dir.path(), true));
+        assert!(
+            output.starts_with("**Git:** `git@gitlab.com:foo/baz.git` @ "),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_linked_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init(&dir.path().join("repo"));
+        commit(&repo, "a.txt", "first");
+        let path = dir.path().join("linked");
+        repo.worktree("linked", &path, None).unwrap();
+
+        let output = render(&GitInfo::new(&path, true));
+        assert!(
+            output.starts_with("**Git:** `refs/heads/linked` (worktree)\n"),
+            "{}",
+            output
+        );
+        assert!(output.contains("    first\n"), "{}", output);
+        assert!(output.contains("**Worktree:** clean"), "{}", output);
+    }
+
+    #[test]
+    fn test_no_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        init(dir.path());
+
+        let output = render(&GitInfo::new(dir.path(), true).with_changelog(true));
+        assert_eq!(output, "**Git:** `<no commits>`\n\n\n**Worktree:** clean\n");
+    }
+
+    #[test]
+    fn test_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init(dir.path());
+        commit(&repo, "a.txt", "first");
+        commit(&repo, "b.txt", "first");
+
+        // the files written by the workflow of `init` are not changes
+        std::fs::write(dir.path().join("test-output.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("test-report.md"), "").unwrap();
+        let info = GitInfo::new(dir.path(), true).with_changes(true);
+        assert!(render(&info).contains("**Worktree:** clean"));
+
+        std::fs::write(dir.path().join("a.txt"), "patched").unwrap();
+        std::fs::remove_file(dir.path().join("b.txt")).unwrap();
+        let output = render(&info);
+        assert!(
+            output.contains(
+                "**Worktree:** ⚠️ uncommitted changes in 2 files\n\n* `a.txt` (modified)\n* `b.txt` (deleted)\n"
+            ),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_web_url() {
+        for remote in [
+            "https://github.com/ctron/markdown-test-report.git",
+            "https://github.com/ctron/markdown-test-report",
+            "git@github.com:ctron/markdown-test-report.git",
+            "ssh://git@github.com:22/ctron/markdown-test-report.git",
+        ] {
+            assert_eq!(
+                web_url(remote).as_deref(),
+                Some("https://github.com/ctron/markdown-test-report"),
+                "{}",
+                remote
+            );
+        }
+        assert_eq!(web_url("/local/path"), None);
+    }
+
+    #[test]
+    fn test_commit_url() {
+        let id = Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap();
+        assert_eq!(
+            commit_url("https://github.com/foo/bar", id).as_deref(),
+            Some("https://github.com/foo/bar/commit/0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(
+            commit_url("https://gitlab.com/foo/bar", id).as_deref(),
+            Some("https://gitlab.com/foo/bar/-/commit/0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(commit_url("https://example.com/foo/bar", id), None);
+    }
+}
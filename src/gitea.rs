@@ -0,0 +1,79 @@
+use crate::http::{self, Secret};
+use crate::processor::{Notification, Notifier};
+use serde_json::json;
+
+/// Posts the summary as a comment to a pull request of Gitea or Forgejo.
+#[derive(Debug)]
+pub struct GiteaComment {
+    /// The API endpoint of the comments.
+    url: String,
+    token: Secret,
+}
+
+impl GiteaComment {
+    /// Create from the URL of a pull request, like `https://codeberg.org/owner/repo/pulls/1`.
+    pub fn new(pull_request: &str, token: Secret) -> anyhow::Result<Self> {
+        let invalid = || {
+            anyhow::anyhow!(
+                "Invalid pull request URL '{}', expected '<server>/<owner>/<repo>/pulls/<number>'",
+                pull_request
+            )
+        };
+
+        let (repo, number) = pull_request
+            .trim_end_matches('/')
+            .rsplit_once("/pulls/")
+            .ok_or_else(invalid)?;
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let mut parts = repo.rsplitn(3, '/');
+        let (Some(repo), Some(owner), Some(server)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if !server.contains("://") {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            url: format!(
+                "{}/api/v1/repos/{}/{}/issues/{}/comments",
+                server, owner, repo, number
+            ),
+            token,
+        })
+    }
+}
+
+impl Notifier for GiteaComment {
+    fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+        http::send(
+            "POST",
+            &self.url,
+            &[("Authorization", format!("token {}", self.token.0))],
+            &json!({ "body": notification.comment }),
+        )?;
+        log::info!("Posted the summary to {}", self.url);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let token = || Secret("token".into());
+        assert_eq!(
+            GiteaComment::new("https://git.example.com/sub/owner/repo/pulls/12/", token())
+                .unwrap()
+                .url,
+            "https://git.example.com/sub/api/v1/repos/owner/repo/issues/12/comments"
+        );
+        assert!(GiteaComment::new("https://git.example.com/owner/repo", token()).is_err());
+        assert!(GiteaComment::new("https://git.example.com/owner/repo/pulls/x", token()).is_err());
+        assert!(GiteaComment::new("owner/repo/pulls/1", token()).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::http::{self, truncate, Secret};
+use crate::model;
+use crate::processor::{Notification, Notifier};
+use serde_json::{json, Value};
+
+/// The maximum number of annotations, which can be added with a single request.
+const MAX_ANNOTATIONS: usize = 50;
+const MAX_SUMMARY: usize = 65535;
+const MAX_MESSAGE: usize = 64 * 1024;
+
+/// Creates a check run of a commit on GitHub, with the failed tests as annotations.
+#[derive(Debug)]
+pub struct GithubCheck {
+    /// The endpoint of the check runs.
+    url: String,
+    sha: String,
+    name: String,
+    token: Secret,
+}
+
+impl GithubCheck {
+    /// Create for a repository, like `owner/repo`, and a commit.
+    ///
+    /// The labels are part of the name of the check, so that several jobs can report on the same
+    /// commit.
+    pub fn new(
+        api: &str,
+        repository: &str,
+        sha: String,
+        labels: &[String],
+        token: Secret,
+    ) -> anyhow::Result<Self> {
+        if repository.split('/').count() != 2 {
+            anyhow::bail!(
+                "Invalid GitHub repository '{}', expected '<owner>/<repo>'",
+                repository
+            );
+        }
+
+        let mut name = String::from("Test results");
+        if !labels.is_empty() {
+            name.push_str(&format!(" ({})", labels.join(", ")));
+        }
+
+        Ok(Self {
+            url: format!(
+                "{}/repos/{}/check-runs",
+                api.trim_end_matches('/'),
+                repository
+            ),
+            sha,
+            name,
+            token,
+        })
+    }
+
+    fn headers(&self) -> [(&'static str, String); 3] {
+        [
+            ("Authorization", format!("Bearer {}", self.token.0)),
+            ("Accept", "application/vnd.github+json".into()),
+            ("X-GitHub-Api-Version", "2022-11-28".into()),
+        ]
+    }
+}
+
+/// An annotation for each failed test with a known location
+fn annotations(report: &model::Report) -> Vec<Value> {
+    report
+        .tests()
+        .filter(|test| test.outcome == "failed")
+        .filter_map(|test| {
+            let location = test.location()?;
+            let message = test
+                .message
+                .as_deref()
+                .or(test.output.as_deref())
+                .filter(|message| !message.is_empty())
+                .unwrap_or("The test failed");
+            Some(json!({
+                "path": location.path,
+                "start_line": location.line,
+                "end_line": location.line,
+                "annotation_level": "failure",
+                "title": format!("{} failed", test.name),
+                "message": truncate(message, MAX_MESSAGE),
+            }))
+        })
+        .collect()
+}
+
+/// The output of the check run
+fn output(notification: &Notification, annotations: &[Value]) -> Value {
+    let report = notification.report;
+    json!({
+        "title": format!(
+            "{} passed, {} failed, {} ignored",
+            report.count("ok"),
+            report.count("failed"),
+            report.count("ignored")
+        ),
+        "summary": truncate(notification.comment, MAX_SUMMARY),
+        "annotations": annotations,
+    })
+}
+
+impl Notifier for GithubCheck {
+    fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+        let annotations = annotations(notification.report);
+        let mut batches = annotations.chunks(MAX_ANNOTATIONS);
+
+        let check = http::send(
+            "POST",
+            &self.url,
+            &self.headers(),
+            &json!({
+                "name": self.name,
+                "head_sha": self.sha,
+                "status": "completed",
+                "conclusion": match notification.report.outcome {
+                    "failed" => "failure",
+                    _ => "success",
+                },
+                "output": output(notification, batches.next().unwrap_or_default()),
+            }),
+        )?;
+
+        // further annotations are added by updating the check run
+        let Some(id) = check["id"].as_u64() else {
+            anyhow::bail!("Missing the ID of the created check run");
+        };
+        for batch in batches {
+            http::send(
+                "PATCH",
+                &format!("{}/{}", self.url, id),
+                &self.headers(),
+                &json!({ "output": output(notification, batch) }),
+            )?;
+        }
+
+        log::info!("Created the check run {}", check["html_url"]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_annotations() {
+        let test = |name: &str, output: &str| model::Test {
+            name: name.into(),
+            outcome: "failed",
+            duration: Some(0.5),
+            label: None,
+            message: None,
+            output: Some(output.into()),
+        };
+        let report = model::Report {
+            version: model::VERSION,
+            outcome: "failed",
+            labels: vec![],
+            partial: false,
+            runs: vec![model::Run {
+                name: None,
+                summary: None,
+                suites: vec![],
+                tests: vec![
+                    test("a", "thread 'a' panicked at src/lib.rs:7:5:\nboom"),
+                    test("b", "no location"),
+                ],
+            }],
+        };
+
+        assert_eq!(
+            annotations(&report),
+            vec![json!({
+                "path": "src/lib.rs",
+                "start_line": 7,
+                "end_line": 7,
+                "annotation_level": "failure",
+                "title": "a failed",
+                "message": "thread 'a' panicked at src/lib.rs:7:5:\nboom",
+            })]
+        );
+
+        let check = GithubCheck::new(
+            "https://api.github.com/",
+            "owner/repo",
+            "abc".into(),
+            &["linux".into()],
+            Secret("t".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            check.url,
+            "https://api.github.com/repos/owner/repo/check-runs"
+        );
+        assert_eq!(check.name, "Test results (linux)");
+    }
+}
@@ -0,0 +1,201 @@
+use crate::input::Converter;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// An event of `go test -json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Event {
+    action: String,
+    #[serde(default)]
+    package: String,
+    test: Option<String>,
+    elapsed: Option<f64>,
+    output: Option<String>,
+}
+
+/// The state of a package, which is converted into a suite once it finished.
+#[derive(Debug, Default)]
+struct Package {
+    /// The captured output of each running test.
+    output: HashMap<String, String>,
+    tests: Vec<String>,
+    passed: u64,
+    failed: u64,
+    ignored: u64,
+}
+
+/// Converts the events of `go test -json`, turning each package into a suite.
+///
+/// The events of packages may be interleaved, so they are collected until the package finished.
+#[derive(Debug, Default)]
+pub struct GoTest {
+    packages: HashMap<String, Package>,
+    /// The order in which packages started.
+    order: Vec<String>,
+}
+
+impl GoTest {
+    fn package(&mut self, name: &str) -> &mut Package {
+        if !self.packages.contains_key(name) {
+            self.order.push(name.to_string());
+        }
+        self.packages.entry(name.to_string()).or_default()
+    }
+
+    fn test(&mut self, event: Event, test: String) {
+        let package = self.package(&event.package);
+        let name = format!("{}::{}", event.package, test);
+        let exec_time = event.elapsed.unwrap_or_default();
+
+        let line = match event.action.as_str() {
+            "output" => {
+                let output = event.output.unwrap_or_default();
+                // the lines announcing the state of the test are noise in the report
+                if !output.starts_with("=== ") && !output.trim_start().starts_with("--- ") {
+                    package.output.entry(test).or_default().push_str(&output);
+                }
+                return;
+            }
+            "pass" => {
+                package.output.remove(&test);
+                package.passed += 1;
+                json!({ "type": "test", "event": "ok", "name": name, "exec_time": exec_time })
+            }
+            "fail" => {
+                let stdout = package.output.remove(&test).unwrap_or_default();
+                package.failed += 1;
+                json!({ "type": "test", "event": "failed", "name": name, "exec_time": exec_time, "stdout": stdout })
+            }
+            "skip" => {
+                let message = package.output.remove(&test).unwrap_or_default();
+                package.ignored += 1;
+                json!({ "type": "test", "event": "ignored", "name": name, "message": message.trim() })
+            }
+            _ => return,
+        };
+        package.tests.push(line.to_string());
+    }
+
+    /// Write the suite of a package, finished if the result of the package is known
+    fn suite(package: &str, state: Package, result: Option<(&str, f64)>, out: &mut Vec<String>) {
+        out.push(format!("     Running unittests {}", package));
+        out.push(
+            json!({ "type": "suite", "event": "started", "test_count": state.tests.len() })
+                .to_string(),
+        );
+        out.extend(state.tests);
+        if let Some((event, exec_time)) = result {
+            out.push(
+                json!({
+                    "type": "suite",
+                    "event": event,
+                    "passed": state.passed,
+                    "failed": state.failed,
+                    "ignored": state.ignored,
+                    "measured": 0,
+                    "filtered_out": 0,
+                    "exec_time": exec_time,
+                })
+                .to_string(),
+            );
+        }
+    }
+}
+
+impl Converter for GoTest {
+    fn line(&mut self, line: &str, out: &mut Vec<String>) -> anyhow::Result<()> {
+        let event: Event = match serde_json::from_str(line) {
+            Ok(event) => event,
+            Err(err) => {
+                log::debug!("Ignoring line: {:?} -> {}", err, line);
+                return Ok(());
+            }
+        };
+
+        if let Some(test) = event.test.clone() {
+            self.test(event, test);
+            return Ok(());
+        }
+
+        let result = match event.action.as_str() {
+            "pass" => "ok",
+            "fail" => "failed",
+            // no test files
+            "skip" => {
+                self.packages.remove(&event.package);
+                self.order.retain(|name| *name != event.package);
+                return Ok(());
+            }
+            _ => {
+                self.package(&event.package);
+                return Ok(());
+            }
+        };
+
+        if let Some(state) = self.packages.remove(&event.package) {
+            self.order.retain(|name| *name != event.package);
+            Self::suite(
+                &event.package,
+                state,
+                Some((result, event.elapsed.unwrap_or_default())),
+                out,
+            );
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<String>) -> anyhow::Result<()> {
+        // packages which didn't finish, because the output ended
+        for name in std::mem::take(&mut self.order) {
+            if let Some(state) = self.packages.remove(&name) {
+                Self::suite(&name, state, None, out);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let input = r#"{"Time":"2024-01-01T10:00:00Z","Action":"start","Package":"example.com/foo"}
+{"Time":"2024-01-01T10:00:00Z","Action":"run","Package":"example.com/foo","Test":"TestA"}
+{"Time":"2024-01-01T10:00:00Z","Action":"output","Package":"example.com/foo","Test":"TestA","Output":"=== RUN   TestA\n"}
+{"Time":"2024-01-01T10:00:00Z","Action":"output","Package":"example.com/foo","Test":"TestA","Output":"    foo_test.go:12: boom\n"}
+{"Time":"2024-01-01T10:00:00Z","Action":"output","Package":"example.com/foo","Test":"TestA","Output":"--- FAIL: TestA (0.50s)\n"}
+{"Time":"2024-01-01T10:00:00Z","Action":"fail","Package":"example.com/foo","Test":"TestA","Elapsed":0.5}
+{"Time":"2024-01-01T10:00:00Z","Action":"run","Package":"example.com/foo","Test":"TestB"}
+{"Time":"2024-01-01T10:00:00Z","Action":"output","Package":"example.com/foo","Test":"TestB","Output":"    foo_test.go:20: not on linux\n"}
+{"Time":"2024-01-01T10:00:00Z","Action":"skip","Package":"example.com/foo","Test":"TestB","Elapsed":0}
+{"Time":"2024-01-01T10:00:00Z","Action":"skip","Package":"example.com/empty","Elapsed":0}
+{"Time":"2024-01-01T10:00:00Z","Action":"fail","Package":"example.com/foo","Elapsed":0.7}
+{"Time":"2024-01-01T10:00:00Z","Action":"run","Package":"example.com/bar","Test":"TestC"}
+{"Time":"2024-01-01T10:00:00Z","Action":"pass","Package":"example.com/bar","Test":"TestC","Elapsed":0.1}
+"#;
+        let mut converter = GoTest::default();
+        let mut out = vec![];
+        for line in input.lines() {
+            converter.line(line, &mut out).unwrap();
+        }
+        converter.finish(&mut out).unwrap();
+
+        let out = out.join("\n");
+        assert_eq!(
+            out,
+            r#"     Running unittests example.com/foo
+{"event":"started","test_count":2,"type":"suite"}
+{"event":"failed","exec_time":0.5,"name":"example.com/foo::TestA","stdout":"    foo_test.go:12: boom\n","type":"test"}
+{"event":"ignored","message":"foo_test.go:20: not on linux","name":"example.com/foo::TestB","type":"test"}
+{"event":"failed","exec_time":0.7,"failed":1,"filtered_out":0,"ignored":1,"measured":0,"passed":0,"type":"suite"}
+     Running unittests example.com/bar
+{"event":"started","test_count":1,"type":"suite"}
+{"event":"ok","exec_time":0.1,"name":"example.com/bar::TestC","type":"test"}"#
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::Write;
+
+/// Environment variables of CI systems, containing the name of the runner.
+const RUNNER_VARS: &[&str] = &[
+    // GitHub Actions
+    "RUNNER_NAME",
+    // GitLab CI
+    "CI_RUNNER_DESCRIPTION",
+    // Azure Pipelines
+    "AGENT_NAME",
+    "BUILDKITE_AGENT_NAME",
+];
+
+/// The `[addon.host]` section of the configuration.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub position: Option<crate::processor::Position>,
+}
+
+/// The machine the report was created on, and the parallelism of the tests.
+#[derive(Debug, Default)]
+pub struct HostInfo {
+    hostname: Option<String>,
+    runner: Option<String>,
+    cpus: Option<usize>,
+    /// The value of `RUST_TEST_THREADS`, if set.
+    test_threads: Option<String>,
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+impl HostInfo {
+    pub fn detect() -> Self {
+        let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .or_else(|| env("HOSTNAME"))
+            .or_else(|| env("COMPUTERNAME"));
+
+        Self {
+            hostname,
+            runner: RUNNER_VARS.iter().find_map(|name| env(name)),
+            cpus: std::thread::available_parallelism()
+                .ok()
+                .map(|cpus| cpus.get()),
+            test_threads: env("RUST_TEST_THREADS"),
+        }
+    }
+}
+
+impl super::Addon for HostInfo {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        let mut parts = vec![];
+        if let Some(hostname) = &self.hostname {
+            parts.push(format!("**Host:** `{}`", hostname));
+        }
+        if let Some(runner) = &self.runner {
+            parts.push(format!("**Runner:** {}", runner));
+        }
+        parts.push(format!(
+            "**Platform:** {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ));
+        if let Some(cpus) = self.cpus {
+            parts.push(format!("**CPUs:** {}", cpus));
+        }
+        // the harness defaults to one thread per CPU
+        match (&self.test_threads, self.cpus) {
+            (Some(threads), _) => parts.push(format!("**Test threads:** {}", threads)),
+            (None, Some(cpus)) => parts.push(format!("**Test threads:** {} (default)", cpus)),
+            (None, None) => {}
+        }
+
+        writeln!(write, "{}", parts.join(" · "))?;
+        writeln!(write)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+
+    #[test]
+    fn test_render() {
+        let render = |host: HostInfo| {
+            let mut out = vec![];
+            host.render(&mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let platform = format!(
+            "**Platform:** {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+
+        assert_eq!(
+            render(HostInfo {
+                hostname: Some("build-1".into()),
+                runner: Some("linux-large".into()),
+                cpus: Some(8),
+                test_threads: None,
+            }),
+            format!(
+                "**Host:** `build-1` · **Runner:** linux-large · {} · **CPUs:** 8 · **Test threads:** 8 (default)\n\n",
+                platform
+            )
+        );
+        assert_eq!(
+            render(HostInfo {
+                cpus: Some(8),
+                test_threads: Some("1".into()),
+                ..Default::default()
+            }),
+            format!("{} · **CPUs:** 8 · **Test threads:** 1\n\n", platform)
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use crate::locale::{CustomLabels, Lang};
+use crate::model;
+use askama_escape::{escape, Html};
+use std::{io::Write, time::Duration};
+
+/// The color theme of the HTML report.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum Theme {
+    /// Follow the preference of the browser
+    #[default]
+    Auto,
+    Light,
+    Dark,
+}
+
+const LIGHT: &str = "color-scheme: light; --fg: #1f2328; --bg: #ffffff; --border: #d0d7de; \
+    --ok: #1a7f37; --failed: #cf222e; --ignored: #9a6700;";
+const DARK: &str = "color-scheme: dark; --fg: #e6edf3; --bg: #0d1117; --border: #30363d; \
+    --ok: #3fb950; --failed: #f85149; --ignored: #d29922;";
+
+/// The styles of the report, inlined so that the file works offline.
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2em; color: var(--fg); background: var(--bg); }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid var(--border); padding: 0.25em 0.75em; text-align: left; vertical-align: top; }
+td.number { text-align: right; }
+pre { margin: 0.5em 0; white-space: pre-wrap; }
+.controls { display: flex; gap: 1em; align-items: center; }
+.ok { color: var(--ok); }
+.failed { color: var(--failed); }
+.ignored { color: var(--ignored); }
+@media print {
+  body { margin: 0; font-size: 10pt; }
+  .controls { display: none; }
+  tr { break-inside: avoid; }
+  h2 { break-after: avoid; }
+}
+"#;
+
+/// The colors of the theme, printing always uses the light one.
+fn theme_style(theme: Theme) -> String {
+    let print = format!("@media print {{ :root {{ {} }} }}", LIGHT);
+    match theme {
+        Theme::Light => format!(":root {{ {} }}\n{}", LIGHT, print),
+        Theme::Dark => format!(":root {{ {} }}\n{}", DARK, print),
+        Theme::Auto => format!(
+            ":root {{ {} }}\n@media screen and (prefers-color-scheme: dark) {{ :root {{ {} }} }}\n{}",
+            LIGHT, DARK, print
+        ),
+    }
+}
+
+/// Filters the tests by their name and outcome.
+const SCRIPT: &str = r#"
+const search = document.getElementById("search");
+const toggles = document.querySelectorAll("input[data-outcome]");
+function update() {
+  const query = search.value.toLowerCase();
+  const shown = new Set([...toggles].filter((t) => t.checked).map((t) => t.dataset.outcome));
+  for (const row of document.querySelectorAll("tr.test")) {
+    row.hidden = !shown.has(row.dataset.outcome) || !row.dataset.name.includes(query);
+  }
+}
+search.addEventListener("input", update);
+toggles.forEach((t) => t.addEventListener("change", update));
+window.addEventListener("beforeprint", () => {
+  document.querySelectorAll("details").forEach((d) => (d.open = true));
+});
+"#;
+
+fn format_duration(seconds: f64) -> String {
+    let duration = Duration::from_secs_f64(seconds);
+    humantime::format_duration(Duration::from_millis(duration.as_millis() as u64)).to_string()
+}
+
+/// Render the report as a single HTML file, with all assets inlined.
+pub fn render(
+    report: &model::Report,
+    lang: Lang,
+    custom: &CustomLabels,
+    theme: Theme,
+    write: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let l = lang.labels();
+    let [total, passed, failed, ignored, filtered, duration] = custom.summary(l);
+    let title = match report.labels.is_empty() {
+        true => l.test_result.to_string(),
+        false => format!("{} ({})", l.test_result, report.labels.join(", ")),
+    };
+
+    writeln!(write, "<!DOCTYPE html>")?;
+    writeln!(write, "<html lang=\"{}\">", lang.code())?;
+    writeln!(write, "<head>")?;
+    writeln!(write, "<meta charset=\"utf-8\">")?;
+    writeln!(write, "<title>{}</title>", escape(&title, Html))?;
+    writeln!(write, "<style>\n{}{}</style>", theme_style(theme), STYLE)?;
+    writeln!(write, "</head>")?;
+    writeln!(write, "<body>")?;
+    writeln!(write, "<h1>{}</h1>", escape(&title, Html))?;
+
+    writeln!(write, "<div class=\"controls\">")?;
+    writeln!(
+        write,
+        "<input type=\"search\" id=\"search\" placeholder=\"{}\">",
+        escape(l.search, Html)
+    )?;
+    for (outcome, label) in [
+        ("ok", l.passed),
+        ("failed", l.failed),
+        ("ignored", l.ignored),
+    ] {
+        writeln!(
+            write,
+            "<label><input type=\"checkbox\" data-outcome=\"{}\" checked> {}</label>",
+            outcome,
+            escape(label, Html)
+        )?;
+    }
+    writeln!(write, "</div>")?;
+
+    for run in &report.runs {
+        if let Some(name) = &run.name {
+            writeln!(write, "<h2>{}</h2>", escape(name, Html))?;
+        }
+
+        if let Some(summary) = &run.summary {
+            writeln!(write, "<table class=\"summary\">")?;
+            writeln!(
+                write,
+                "<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>",
+                escape(total, Html),
+                escape(passed, Html),
+                escape(failed, Html),
+                escape(ignored, Html),
+                escape(filtered, Html),
+                escape(duration, Html)
+            )?;
+            writeln!(
+                write,
+                "<tr><td class=\"number\">{}</td><td class=\"number ok\">{}</td><td class=\"number failed\">{}</td><td class=\"number ignored\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+                summary
+                    .total
+                    .map(|total| lang.format_count(total))
+                    .unwrap_or_default(),
+                lang.format_count(summary.passed),
+                lang.format_count(summary.failed),
+                lang.format_count(summary.ignored),
+                lang.format_count(summary.filtered_out),
+                format_duration(summary.duration),
+            )?;
+            writeln!(write, "</table>")?;
+        }
+
+        writeln!(write, "<table class=\"tests\">")?;
+        writeln!(
+            write,
+            "<caption>{}</caption>",
+            escape(custom.index_title(l), Html)
+        )?;
+        writeln!(
+            write,
+            "<tr><th>{}</th><th>{}</th><th>{}</th></tr>",
+            l.name, l.result, l.duration
+        )?;
+        for test in &run.tests {
+            let label = match &test.label {
+                Some(label) => format!(" ({})", label),
+                None => String::new(),
+            };
+            writeln!(
+                write,
+                "<tr class=\"test\" data-outcome=\"{}\" data-name=\"{}\">",
+                test.outcome,
+                escape(&test.name.to_lowercase(), Html)
+            )?;
+            write!(write, "<td><code>{}</code>", escape(&test.name, Html))?;
+            write!(write, "{}", escape(&label, Html))?;
+            if let Some(message) = &test.message {
+                write!(write, "<br>{}", escape(message, Html))?;
+            }
+            if let Some(output) = &test.output {
+                write!(
+                    write,
+                    "<details><summary>{}</summary><pre>{}</pre></details>",
+                    escape(custom.details_title(l), Html),
+                    escape(output, Html)
+                )?;
+            }
+            writeln!(write, "</td>")?;
+            let result = match test.outcome {
+                "ok" => l.passed,
+                "failed" => l.failed,
+                _ => l.ignored,
+            };
+            writeln!(
+                write,
+                "<td class=\"{}\">{}</td><td class=\"number\">{}</td>",
+                test.outcome,
+                escape(result, Html),
+                test.duration.map(format_duration).unwrap_or_default()
+            )?;
+            writeln!(write, "</tr>")?;
+        }
+        writeln!(write, "</table>")?;
+    }
+
+    writeln!(write, "<script>{}</script>", SCRIPT)?;
+    writeln!(write, "</body>")?;
+    writeln!(write, "</html>")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let report = model::Report {
+            version: model::VERSION,
+            outcome: "failed",
+            labels: vec!["linux".into()],
+            partial: false,
+            runs: vec![model::Run {
+                name: None,
+                summary: Some(model::Summary {
+                    outcome: "failed",
+                    total: Some(1),
+                    passed: 0,
+                    failed: 1,
+                    ignored: 0,
+                    filtered_out: 0,
+                    duration: 0.25,
+                    incomplete: false,
+                }),
+                suites: vec![],
+                tests: vec![model::Test {
+                    name: "tests::<b>".into(),
+                    outcome: "failed",
+                    duration: Some(0.25),
+                    label: None,
+                    message: None,
+                    output: Some("left != right".into()),
+                }],
+            }],
+        };
+
+        let mut out = vec![];
+        render(
+            &report,
+            Lang::En,
+            &CustomLabels::default(),
+            Theme::Auto,
+            &mut out,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("<title>Test Result (linux)</title>"));
+        assert!(out.contains(
+            "<tr class=\"test\" data-outcome=\"failed\" data-name=\"tests::&lt;b&gt;\">"
+        ));
+        assert!(out.contains("<pre>left != right</pre>"));
+        assert!(out.contains("<td class=\"number\">250ms</td>"));
+        assert!(!out.contains("<link") && !out.contains("src="));
+        assert!(out.contains("<caption>Index</caption>"));
+        assert!(out.contains("<details><summary>Details</summary>"));
+
+        let custom = CustomLabels {
+            index: Some("Tests".into()),
+            details: Some("Output".into()),
+            total: Some("All".into()),
+            ..Default::default()
+        };
+        let mut out = vec![];
+        render(&report, Lang::En, &custom, Theme::Auto, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("<caption>Tests</caption>"));
+        assert!(out.contains("<details><summary>Output</summary>"));
+        assert!(out.contains("<tr><th>All</th><th>Passed</th>"));
+    }
+
+    #[test]
+    fn test_theme() {
+        assert!(theme_style(Theme::Auto)
+            .contains("(prefers-color-scheme: dark) { :root { color-scheme: dark;"));
+        assert!(!theme_style(Theme::Light).contains("color-scheme: dark"));
+        // printing is always light
+        assert!(theme_style(Theme::Dark).ends_with("@media print { :root { color-scheme: light; --fg: #1f2328; --bg: #ffffff; --border: #d0d7de; --ok: #1a7f37; --failed: #cf222e; --ignored: #9a6700; } }"));
+    }
+}
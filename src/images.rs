@@ -0,0 +1,144 @@
+use anyhow::Context;
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// A reference to a container image, like `quay.io/org/app:1.0@sha256:…`.
+#[derive(Debug, Eq, PartialEq)]
+struct Image {
+    /// Where the reference was taken from, the file or the environment variable.
+    source: String,
+    reference: String,
+}
+
+impl Image {
+    /// The reference without the digest, and the digest
+    fn split(&self) -> (&str, Option<&str>) {
+        match self.reference.split_once('@') {
+            Some((name, digest)) => (name, Some(digest)),
+            None => (&self.reference, None),
+        }
+    }
+}
+
+/// The options of the `[addon.images]` section of the configuration.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub file: Option<PathBuf>,
+    pub env: Vec<String>,
+    pub position: Option<crate::processor::Position>,
+}
+
+/// The container images used by the test run.
+#[derive(Debug, Default)]
+pub struct Images {
+    images: Vec<Image>,
+}
+
+impl Images {
+    /// Load the images from a file, with one reference per line, and from environment variables.
+    pub fn load(file: Option<&Path>, vars: &[String]) -> anyhow::Result<Self> {
+        let mut images = Self::default();
+
+        if let Some(path) = file {
+            let mut content = String::new();
+            crate::input::open(path)?
+                .read_to_string(&mut content)
+                .with_context(|| format!("Failed to read images from {}", path.display()))?;
+            images.parse(&path.display().to_string(), &content);
+        }
+
+        for var in vars {
+            match std::env::var(var) {
+                Ok(value) => images.parse(&format!("${}", var), &value),
+                Err(_) => log::warn!("The environment variable {} of an image is not set", var),
+            }
+        }
+
+        Ok(images)
+    }
+
+    /// Add the references, separated by whitespace, ignoring comments
+    fn parse(&mut self, source: &str, content: &str) {
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            for reference in line.split_whitespace() {
+                self.images.push(Image {
+                    source: source.to_string(),
+                    reference: reference.to_string(),
+                });
+            }
+        }
+    }
+}
+
+impl super::Addon for Images {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        let unpinned = self
+            .images
+            .iter()
+            .filter(|image| image.split().1.is_none())
+            .count();
+        write!(write, "**Images:** {}", self.images.len())?;
+        if unpinned > 0 {
+            write!(write, ", ⚠️ {} without a digest", unpinned)?;
+        }
+        writeln!(write)?;
+        writeln!(write)?;
+
+        if self.images.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(write, "| Image | Digest | Source |")?;
+        writeln!(write, "| ----- | ------ | ------ |")?;
+        for image in &self.images {
+            let (name, digest) = image.split();
+            writeln!(
+                write,
+                "| `{}` | {} | {} |",
+                name,
+                digest
+                    .map(|digest| format!("`{}`", digest))
+                    .unwrap_or_default(),
+                image.source
+            )?;
+        }
+        writeln!(write)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+
+    #[test]
+    fn test_render() {
+        let mut images = Images::default();
+        images.parse(
+            "images.txt",
+            "# deployed by the operator\nquay.io/org/app:1.0@sha256:abc\n\ndocker.io/postgres:16 # unpinned\n",
+        );
+        images.parse("$SIDECAR_IMAGE", "quay.io/org/sidecar@sha256:def");
+
+        let mut out = vec![];
+        images.render(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"**Images:** 3, ⚠️ 1 without a digest
+
+| Image | Digest | Source |
+| ----- | ------ | ------ |
+| `quay.io/org/app:1.0` | `sha256:abc` | images.txt |
+| `docker.io/postgres:16` |  | images.txt |
+| `quay.io/org/sidecar` | `sha256:def` | $SIDECAR_IMAGE |
+
+"#
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::{bail, Context};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The CI system to create the integration for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ValueEnum)]
+pub enum Target {
+    /// A GitHub Actions workflow
+    Github,
+    /// A GitLab CI job, to be included from `.gitlab-ci.yml`
+    Gitlab,
+}
+
+const GITHUB: &str = r#"name: Test report
+
+on:
+  push:
+  pull_request:
+
+jobs:
+  test-report:
+    runs-on: ubuntu-latest
+    env:
+      # allow the JSON output of the test harness on stable Rust
+      RUSTC_BOOTSTRAP: 1
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@stable
+
+      - name: Install markdown-test-report
+        run: cargo install markdown-test-report
+
+      - name: Run tests
+        shell: bash
+        run: cargo test{args} -- -Z unstable-options --report-time --format json 2>&1 | tee test-output.json
+
+      - name: Create report
+        if: always()
+        run: |
+          markdown-test-report test-output.json -o test-report.md
+          cat test-report.md >> "$GITHUB_STEP_SUMMARY"
+
+      - uses: actions/upload-artifact@v4
+        if: always()
+        with:
+          name: {name}
+          path: |
+            test-output.json
+            test-report.md
+"#;
+
+const GITLAB: &str = r#"test-report:
+  image: rust:latest
+  variables:
+    # allow the JSON output of the test harness on stable Rust
+    RUSTC_BOOTSTRAP: "1"
+  script:
+    - cargo install markdown-test-report
+    - set -o pipefail
+    - cargo test{args} -- -Z unstable-options --report-time --format json 2>&1 | tee test-output.json
+  after_script:
+    - markdown-test-report test-output.json -o test-report.md
+  artifacts:
+    name: {name}
+    when: always
+    paths:
+      - test-output.json
+      - test-report.md
+"#;
+
+/// The layout of the package, as far as it is relevant for running the tests.
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Layout {
+    name: Option<String>,
+    workspace: bool,
+    features: bool,
+}
+
+impl Layout {
+    fn detect(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join("Cargo.toml");
+        let manifest = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&manifest).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn parse(manifest: &str) -> anyhow::Result<Self> {
+        let manifest: toml::Table = manifest.parse()?;
+
+        Ok(Self {
+            name: manifest
+                .get("package")
+                .and_then(|package| package.get("name"))
+                .and_then(|name| name.as_str())
+                .map(ToString::to_string),
+            workspace: manifest.contains_key("workspace"),
+            features: manifest.contains_key("features"),
+        })
+    }
+
+    /// The arguments for `cargo test`.
+    fn args(&self) -> String {
+        let mut args = String::new();
+        if self.workspace {
+            args.push_str(" --workspace");
+        }
+        if self.features {
+            args.push_str(" --all-features");
+        }
+        args
+    }
+
+    fn render(&self, template: &str) -> String {
+        let name = match &self.name {
+            Some(name) => format!("test-report-{}", name),
+            None => "test-report".to_string(),
+        };
+        template
+            .replace("{args}", &self.args())
+            .replace("{name}", &name)
+    }
+}
+
+/// Write the CI integration into the repository, returning the path of the created file.
+pub fn init(target: Target, dir: &Path, force: bool) -> anyhow::Result<PathBuf> {
+    let layout = Layout::detect(dir)?;
+
+    let (path, template) = match target {
+        Target::Github => (
+            dir.join(".github")
+                .join("workflows")
+                .join("test-report.yaml"),
+            GITHUB,
+        ),
+        Target::Gitlab => (dir.join(".gitlab").join("test-report.yml"), GITLAB),
+    };
+
+    if path.exists() && !force {
+        bail!(
+            "{} already exists, use --force to overwrite it",
+            path.display()
+        );
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, layout.render(template))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let layout = Layout::parse(
+            r#"
+[package]
+name = "foo"
+
+[workspace]
+members = ["bar"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            layout,
+            Layout {
+                name: Some("foo".into()),
+                workspace: true,
+                features: false,
+            }
+        );
+
+        let github = layout.render(GITHUB);
+        assert!(github.contains("run: cargo test --workspace -- -Z unstable-options"));
+        assert!(github.contains("name: test-report-foo\n"));
+
+        let layout = Layout::parse("[workspace]\n").unwrap();
+        assert!(layout.render(GITLAB).contains("name: test-report\n"));
+    }
+}
@@ -0,0 +1,501 @@
+use crate::event::{ParseError, Parser, Record};
+use crate::go_test::GoTest;
+use crate::junit::Junit;
+use crate::pytest::Pytest;
+use flate2::bufread::MultiGzDecoder;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    ffi::OsStr,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+};
+
+/// Number of lines handed to a worker at once.
+const BATCH_SIZE: usize = 1024;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The format of the input.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum InputFormat {
+    /// The JSON output of the libtest harness, used by `cargo test`
+    #[default]
+    Libtest,
+    /// The output of `go test -json`
+    GoTest,
+    /// The output of `pytest --report-log`, or of the pytest-json-report plugin
+    Pytest,
+    /// A JUnit XML report
+    Junit,
+}
+
+impl InputFormat {
+    /// A converter into the libtest format, unless the input is already using it
+    pub fn converter(&self) -> Option<Box<dyn Converter>> {
+        match self {
+            Self::Libtest => None,
+            Self::GoTest => Some(Box::<GoTest>::default()),
+            Self::Pytest => Some(Box::<Pytest>::default()),
+            Self::Junit => Some(Box::<Junit>::default()),
+        }
+    }
+}
+
+/// Converts the lines of another input format into the lines of the libtest JSON format.
+pub trait Converter {
+    fn line(&mut self, line: &str, out: &mut Vec<String>) -> anyhow::Result<()>;
+
+    /// Called at the end of the input, writing everything which is still pending
+    fn finish(&mut self, out: &mut Vec<String>) -> anyhow::Result<()>;
+}
+
+/// Open an input file, transparently decompressing gzip or zstd compressed content.
+///
+/// The compression is detected by the magic bytes at the start of the file.
+pub fn open(path: &Path) -> anyhow::Result<Box<dyn BufRead + Send>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let magic = reader.fill_buf()?;
+
+    Ok(if magic.starts_with(GZIP_MAGIC) {
+        log::debug!("Input is gzip compressed");
+        Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+    } else if magic.starts_with(ZSTD_MAGIC) {
+        log::debug!("Input is zstd compressed");
+        #[cfg(feature = "zstd")]
+        {
+            Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?))
+        }
+        #[cfg(not(feature = "zstd"))]
+        anyhow::bail!("Reading zstd compressed input requires the feature zstd")
+    } else {
+        Box::new(reader)
+    })
+}
+
+/// The lines of an input file, read using a buffered reader, or mapped into memory.
+pub enum Lines {
+    Reader(Box<dyn BufRead + Send>),
+    /// An uncompressed file, its lines get parsed in place, without copying them.
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Lines {
+    /// Open an input file, mapping it into memory if requested.
+    ///
+    /// Compressed files, as well as pipes or empty files, which can't be mapped, are read instead.
+    pub fn open(path: &Path, mmap: bool) -> anyhow::Result<Self> {
+        if mmap {
+            #[cfg(feature = "mmap")]
+            if let Some(map) = map(path)? {
+                log::debug!("Input is mapped into memory");
+                return Ok(Self::Mapped(map));
+            }
+            #[cfg(not(feature = "mmap"))]
+            anyhow::bail!("Mapping input into memory requires the feature mmap")
+        }
+        Ok(Self::Reader(open(path)?))
+    }
+
+    /// Read all lines, normalizing them, see [`read_lines`].
+    pub fn read_lines<F>(self, f: F) -> anyhow::Result<()>
+    where
+        F: FnMut(&str) -> anyhow::Result<()>,
+    {
+        match self {
+            Self::Reader(reader) => read_lines(reader, f),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map) => {
+                let mut f = f;
+                split_lines(&map).try_for_each(|line| f(&line))
+            }
+        }
+    }
+
+    /// Read and parse all lines using multiple worker threads, see [`parse_parallel`].
+    pub fn parse_parallel<F>(self, threads: usize, f: F) -> anyhow::Result<()>
+    where
+        F: FnMut(&str, Result<Record, ParseError>) -> anyhow::Result<()>,
+    {
+        match self {
+            Self::Reader(reader) => parse_parallel(reader, threads, f),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(map) => parallel(|push| split_lines(&map).try_for_each(push), threads, f),
+        }
+    }
+}
+
+/// Map an uncompressed regular file into memory, `None` if it can't be mapped.
+#[cfg(feature = "mmap")]
+fn map(path: &Path) -> anyhow::Result<Option<memmap2::Mmap>> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return Ok(None);
+    }
+
+    // SAFETY: the file must not be modified while being mapped, test results are written before
+    // the report gets created. Truncating the file would fail reading, like an I/O error.
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    if map.starts_with(GZIP_MAGIC) || map.starts_with(ZSTD_MAGIC) {
+        return Ok(None);
+    }
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+    Ok(Some(map))
+}
+
+/// Split data into normalized lines, only copying lines which are invalid UTF-8.
+#[cfg_attr(not(feature = "mmap"), allow(dead_code))]
+fn split_lines(data: &[u8]) -> impl Iterator<Item = Cow<'_, str>> {
+    data.split_inclusive(|b| *b == b'\n')
+        .map(|line| match String::from_utf8_lossy(line) {
+            Cow::Borrowed(line) => Cow::Borrowed(normalize_line(line)),
+            Cow::Owned(line) => Cow::Owned(normalize_line(&line).to_string()),
+        })
+}
+
+/// Check if the input is streamed, like a pipe, rather than a regular file.
+pub fn is_streamed(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        std::fs::metadata(path)
+            .map(|metadata| {
+                let file_type = metadata.file_type();
+                file_type.is_fifo() || file_type.is_char_device()
+            })
+            .unwrap_or_default()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Get the file stem of the input, ignoring compression extensions.
+pub fn file_stem(path: &Path) -> Option<&str> {
+    let stem = match path.extension().and_then(OsStr::to_str) {
+        Some("gz" | "zst") => Path::new(path.file_stem()?).file_stem(),
+        _ => path.file_stem(),
+    };
+    stem?.to_str()
+}
+
+/// Check if the path is a markdown report, rather than JSON test data.
+pub fn is_report(path: &Path) -> bool {
+    let name = path.file_name().and_then(OsStr::to_str).unwrap_or_default();
+    let name = name
+        .strip_suffix(".gz")
+        .or_else(|| name.strip_suffix(".zst"))
+        .unwrap_or(name);
+    name.ends_with(".md")
+}
+
+/// Read all lines from the reader, normalizing them.
+///
+/// Lines are read as raw bytes, as test output might contain invalid UTF-8.
+pub fn read_lines<R, F>(mut reader: R, mut f: F) -> anyhow::Result<()>
+where
+    R: BufRead,
+    F: FnMut(&str) -> anyhow::Result<()>,
+{
+    let mut buf = Vec::new();
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        f(normalize_line(&String::from_utf8_lossy(&buf)))?;
+        buf.clear();
+    }
+    Ok(())
+}
+
+/// Read the lines of a result, either JSON test data or the records embedded into a report.
+pub fn read_records<F>(path: &Path, f: F) -> anyhow::Result<()>
+where
+    F: FnMut(&str) -> anyhow::Result<()>,
+{
+    if !is_report(path) {
+        return read_lines(open(path)?, f);
+    }
+
+    let mut report = String::new();
+    open(path)?.read_to_string(&mut report)?;
+    match crate::processor::embedded_records(&report) {
+        Some(records) => records.into_iter().try_for_each(f),
+        None => anyhow::bail!("{} contains no embedded results", path.display()),
+    }
+}
+
+type Parsed<L> = Vec<(L, Result<Record, ParseError>)>;
+
+/// Read and parse all lines, using multiple worker threads for parsing.
+///
+/// The results are handed to the callback in the order of the input.
+pub fn parse_parallel<R, F>(reader: R, threads: usize, f: F) -> anyhow::Result<()>
+where
+    R: BufRead + Send,
+    F: FnMut(&str, Result<Record, ParseError>) -> anyhow::Result<()>,
+{
+    parallel(
+        |push| read_lines(reader, |line| push(line.to_string())),
+        threads,
+        f,
+    )
+}
+
+/// Parse the lines, which the producer hands to its callback, using multiple worker threads.
+fn parallel<L, P, F>(produce: P, threads: usize, mut f: F) -> anyhow::Result<()>
+where
+    L: AsRef<str> + Send,
+    P: FnOnce(&mut dyn FnMut(L) -> anyhow::Result<()>) -> anyhow::Result<()> + Send,
+    F: FnMut(&str, Result<Record, ParseError>) -> anyhow::Result<()>,
+{
+    std::thread::scope(|scope| {
+        let (batch_tx, batch_rx) = mpsc::sync_channel::<(usize, Vec<L>)>(threads * 2);
+        let (result_tx, result_rx) = mpsc::sync_channel::<(usize, Parsed<L>)>(threads * 2);
+
+        let reader = scope.spawn(move || {
+            // the lines might borrow the input, which the error can't keep
+            let send = |batch| {
+                batch_tx
+                    .send(batch)
+                    .map_err(|_| anyhow::anyhow!("The parsing threads stopped"))
+            };
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            let mut idx = 0;
+            produce(&mut |line| {
+                batch.push(line);
+                if batch.len() >= BATCH_SIZE {
+                    let batch = std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE));
+                    send((idx, batch))?;
+                    idx += 1;
+                }
+                Ok(())
+            })?;
+            if !batch.is_empty() {
+                send((idx, batch))?;
+            }
+            Ok::<_, anyhow::Error>(())
+        });
+
+        // when all workers are gone, the receiver gets dropped, stopping the reader
+        let batch_rx = Arc::new(Mutex::new(batch_rx));
+        for _ in 0..threads {
+            let batch_rx = batch_rx.clone();
+            let result_tx = result_tx.clone();
+            scope.spawn(move || {
+                let mut parser = Parser::default();
+                loop {
+                    let next = batch_rx.lock().expect("lock not poisoned").recv();
+                    let Ok((idx, lines)) = next else {
+                        break;
+                    };
+                    let parsed = lines
+                        .into_iter()
+                        .map(|line| {
+                            let result = parser.parse(line.as_ref());
+                            (line, result)
+                        })
+                        .collect();
+                    if result_tx.send((idx, parsed)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(batch_rx);
+        drop(result_tx);
+
+        // bring the batches back into order
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        for (idx, parsed) in result_rx {
+            pending.insert(idx, parsed);
+            while let Some(parsed) = pending.remove(&next) {
+                for (line, result) in parsed {
+                    f(line.as_ref(), result)?;
+                }
+                next += 1;
+            }
+        }
+
+        reader.join().expect("reader thread panicked")
+    })
+}
+
+/// Strip the line ending, as well as a UTF-8 BOM and a trailing carriage return, which are common
+/// for input produced on Windows.
+///
+/// A BOM may not only appear on the first line, but also in the middle, when files got concatenated.
+fn normalize_line(line: &str) -> &str {
+    let line = line.trim_start_matches('\u{feff}');
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_line() {
+        assert_eq!(normalize_line(""), "");
+        assert_eq!(normalize_line("{}"), "{}");
+        assert_eq!(normalize_line("\u{feff}{}\r"), "{}");
+        assert_eq!(normalize_line("{}\r"), "{}");
+        assert_eq!(normalize_line("{}\r\n"), "{}");
+        assert_eq!(normalize_line("{}\n"), "{}");
+    }
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(
+            file_stem(Path::new("test-output.json")),
+            Some("test-output")
+        );
+        assert_eq!(
+            file_stem(Path::new("test-output.json.gz")),
+            Some("test-output")
+        );
+        assert_eq!(file_stem(Path::new("out/test.json.zst")), Some("test"));
+        assert_eq!(file_stem(Path::new("test.gz")), Some("test"));
+    }
+
+    #[test]
+    fn test_is_report() {
+        assert!(is_report(Path::new("linux.md")));
+        assert!(is_report(Path::new("out/linux.md.gz")));
+        assert!(!is_report(Path::new("linux.json")));
+        assert!(!is_report(Path::new("linux.json.zst")));
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_open_compressed() {
+        use std::io::{Read, Write};
+
+        let data = "{ \"type\": \"suite\", \"event\": \"started\", \"test_count\": 1 }\n";
+        let dir = tempfile::tempdir().unwrap();
+
+        let gzip = dir.path().join("input.json.gz");
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&gzip).unwrap(), Default::default());
+        encoder.write_all(data.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let zstd = dir.path().join("input.json.zst");
+        std::fs::write(&zstd, zstd::encode_all(data.as_bytes(), 0).unwrap()).unwrap();
+
+        let plain = dir.path().join("input.json");
+        std::fs::write(&plain, data).unwrap();
+
+        for path in [gzip, zstd, plain] {
+            let mut content = String::new();
+            open(&path).unwrap().read_to_string(&mut content).unwrap();
+            assert_eq!(content, data);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_mapped() {
+        use std::io::Write;
+
+        let data = b"\xef\xbb\xbf{ \"type\": \"suite\", \"event\": \"started\", \"test_count\": 1 }\r\nnoise \xff\n\nlast";
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("input.json");
+        std::fs::write(&plain, data).unwrap();
+
+        let read = |lines: Lines| {
+            let mut result = vec![];
+            lines
+                .read_lines(|line| {
+                    result.push(line.to_string());
+                    Ok(())
+                })
+                .unwrap();
+            result
+        };
+        let mapped = Lines::open(&plain, true).unwrap();
+        assert!(matches!(mapped, Lines::Mapped(_)));
+        let expected = read(Lines::open(&plain, false).unwrap());
+        assert_eq!(
+            expected,
+            vec![
+                r#"{ "type": "suite", "event": "started", "test_count": 1 }"#,
+                "noise \u{fffd}",
+                "",
+                "last"
+            ]
+        );
+        assert_eq!(read(mapped), expected);
+
+        let mut lines = vec![];
+        Lines::open(&plain, true)
+            .unwrap()
+            .parse_parallel(2, |line, result| {
+                lines.push((line.to_string(), result.is_ok()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            lines.iter().map(|(line, _)| line).collect::<Vec<_>>(),
+            expected.iter().collect::<Vec<_>>()
+        );
+        assert!(lines[0].1 && !lines[1].1);
+
+        // compressed and empty files are read
+        let gzip = dir.path().join("input.json.gz");
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&gzip).unwrap(), Default::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap();
+        let empty = dir.path().join("empty.json");
+        std::fs::write(&empty, "").unwrap();
+        assert!(matches!(
+            Lines::open(&gzip, true).unwrap(),
+            Lines::Reader(_)
+        ));
+        assert!(matches!(
+            Lines::open(&empty, true).unwrap(),
+            Lines::Reader(_)
+        ));
+        assert_eq!(read(Lines::open(&gzip, true).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_parse_parallel() {
+        let input = (0..5000)
+            .map(|i| {
+                if i % 3 == 0 {
+                    format!(
+                        r#"{{ "type": "test", "event": "started", "name": "test_{}" }}"#,
+                        i
+                    )
+                } else {
+                    format!("noise {}", i)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut names = vec![];
+        parse_parallel(input.as_bytes(), 4, |_, result| {
+            if let Ok(Record::Test(crate::event::test::Event::Started { name })) = result {
+                names.push(name);
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let expected = (0..5000)
+            .filter(|i| i % 3 == 0)
+            .map(|i| format!("test_{}", i))
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+    }
+}
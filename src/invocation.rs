@@ -0,0 +1,150 @@
+use std::path::Path;
+
+/// Options of the libtest harness, which take a value.
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "--test-threads",
+    "--skip",
+    "--logfile",
+    "--format",
+    "--color",
+    "--shuffle-seed",
+    "-Z",
+];
+
+/// The invocation of a test binary, as reported by cargo.
+///
+/// Cargo reports this on stderr before running a test binary, which ends up in the input if stderr
+/// was redirected too. Running `cargo test -v` contains the full command line, including arguments.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Invocation {
+    /// The test target, like `unittests src/lib.rs`
+    pub target: Option<String>,
+    /// The test binary
+    pub binary: Option<String>,
+    /// The arguments passed to the test binary
+    pub args: Vec<String>,
+}
+
+impl Invocation {
+    /// Parse a line of cargo output, announcing a test binary
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+
+        if let Some(name) = line.strip_prefix("Doc-tests ") {
+            return Some(Self {
+                target: Some(format!("doc-tests {}", name.trim())),
+                ..Default::default()
+            });
+        }
+
+        let running = line.strip_prefix("Running ")?.trim();
+
+        if let Some(command) = running
+            .strip_prefix('`')
+            .and_then(|command| command.strip_suffix('`'))
+        {
+            // verbose: Running `/path/to/binary args…`
+            let mut parts = command.split_whitespace().map(String::from);
+            let binary = parts.next()?;
+            return Some(Self {
+                target: None,
+                binary: Some(binary),
+                args: parts.collect(),
+            });
+        }
+
+        // Running unittests src/lib.rs (target/debug/deps/foo-0123456789abcdef)
+        match running.rsplit_once(" (") {
+            Some((target, binary)) if binary.ends_with(')') => Some(Self {
+                target: Some(target.to_string()),
+                binary: Some(binary.trim_end_matches(')').to_string()),
+                args: vec![],
+            }),
+            _ => Some(Self {
+                target: Some(running.to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// The file name of the binary
+    pub fn binary_name(&self) -> Option<&str> {
+        let binary = self.binary.as_deref()?;
+        Path::new(binary)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .or(Some(binary))
+    }
+
+    /// The value of an option, either as `--option value` or `--option=value`
+    fn option(&self, name: &str) -> Option<&str> {
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            if arg == name {
+                return args.next().map(String::as_str);
+            }
+            if let Some(value) = arg
+                .strip_prefix(name)
+                .and_then(|value| value.strip_prefix('='))
+            {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// The value of `--test-threads`
+    pub fn test_threads(&self) -> Option<&str> {
+        self.option("--test-threads")
+    }
+
+    /// The test name filters, which are the positional arguments
+    pub fn filters(&self) -> Vec<&str> {
+        let mut filters = vec![];
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            if OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+                args.next();
+            } else if !arg.starts_with('-') {
+                filters.push(arg.as_str());
+            }
+        }
+        filters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_running() {
+        assert_eq!(
+            Invocation::parse("     Running unittests src/lib.rs (target/debug/deps/foo-1234)"),
+            Some(Invocation {
+                target: Some("unittests src/lib.rs".into()),
+                binary: Some("target/debug/deps/foo-1234".into()),
+                args: vec![],
+            })
+        );
+        assert_eq!(
+            Invocation::parse("   Doc-tests foo"),
+            Some(Invocation {
+                target: Some("doc-tests foo".into()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(Invocation::parse("Compiling foo v0.1.0"), None);
+    }
+
+    #[test]
+    fn test_parse_verbose() {
+        let invocation = Invocation::parse(
+            "     Running `/work/target/debug/deps/foo-1234 -Z unstable-options --format json --test-threads=2 registry --skip slow`",
+        )
+        .unwrap();
+        assert_eq!(invocation.binary_name(), Some("foo-1234"));
+        assert_eq!(invocation.test_threads(), Some("2"));
+        assert_eq!(invocation.filters(), vec!["registry"]);
+    }
+}
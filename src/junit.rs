@@ -0,0 +1,278 @@
+use crate::input::Converter;
+use crate::xml::attribute;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use quick_xml::events::{BytesStart, Event};
+use serde_json::json;
+
+/// A test case, as far as it was read.
+#[derive(Debug, Default)]
+struct TestCase {
+    name: String,
+    time: f64,
+    /// The message and text of a failure or error.
+    failure: Option<String>,
+    /// The reason a test was skipped, empty if none was given.
+    skipped: Option<String>,
+    output: String,
+    /// When the test started, in RFC 3339.
+    timestamp: Option<String>,
+}
+
+impl TestCase {
+    fn to_line(&self) -> String {
+        let mut line = match (&self.failure, &self.skipped) {
+            (Some(failure), _) => {
+                let mut stdout = failure.clone();
+                if !self.output.is_empty() {
+                    stdout.push_str("\n\n");
+                    stdout.push_str(&self.output);
+                }
+                json!({ "type": "test", "event": "failed", "name": self.name, "exec_time": self.time, "stdout": stdout })
+            }
+            (None, Some(reason)) => {
+                json!({ "type": "test", "event": "ignored", "name": self.name, "message": reason })
+            }
+            (None, None) => {
+                json!({ "type": "test", "event": "ok", "name": self.name, "exec_time": self.time })
+            }
+        };
+        if let (Some(timestamp), None) = (&self.timestamp, &self.skipped) {
+            line["start_time"] = timestamp.as_str().into();
+        }
+        line.to_string()
+    }
+}
+
+/// A test suite, as far as it was read.
+#[derive(Debug, Default)]
+struct TestSuite {
+    name: Option<String>,
+    time: Option<f64>,
+    tests: Vec<TestCase>,
+}
+
+impl TestSuite {
+    fn write(self, out: &mut Vec<String>) {
+        if let Some(name) = &self.name {
+            out.push(format!("     Running {}", name));
+        }
+
+        let failed = self.tests.iter().filter(|t| t.failure.is_some()).count();
+        let ignored = self
+            .tests
+            .iter()
+            .filter(|t| t.failure.is_none() && t.skipped.is_some())
+            .count();
+        out.push(
+            json!({ "type": "suite", "event": "started", "test_count": self.tests.len() })
+                .to_string(),
+        );
+        out.extend(self.tests.iter().map(TestCase::to_line));
+        out.push(
+            json!({
+                "type": "suite",
+                "event": if failed > 0 { "failed" } else { "ok" },
+                "passed": self.tests.len() - failed - ignored,
+                "failed": failed,
+                "ignored": ignored,
+                "measured": 0,
+                "filtered_out": 0,
+                "exec_time": self
+                    .time
+                    .unwrap_or_else(|| self.tests.iter().map(|test| test.time).sum()),
+            })
+            .to_string(),
+        );
+    }
+}
+
+/// What the text content currently read belongs to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Text {
+    Failure,
+    Skipped,
+    Output,
+}
+
+/// Converts a JUnit XML report, turning each `testsuite` into a suite.
+///
+/// The XML document is read completely before converting it.
+#[derive(Debug, Default)]
+pub struct Junit {
+    document: String,
+}
+
+fn time(element: &BytesStart) -> anyhow::Result<Option<f64>> {
+    Ok(attribute(element, "time")?.and_then(|time| time.replace(',', "").parse().ok()))
+}
+
+/// The start of a test case, like nextest reports it. Timestamps without an offset are UTC.
+fn timestamp(element: &BytesStart) -> anyhow::Result<Option<String>> {
+    Ok(attribute(element, "timestamp")?.and_then(|timestamp| {
+        match DateTime::parse_from_rfc3339(&timestamp) {
+            Ok(timestamp) => Some(timestamp.to_rfc3339()),
+            Err(_) => NaiveDateTime::parse_from_str(&timestamp, "%Y-%m-%dT%H:%M:%S%.f")
+                .ok()
+                .map(|timestamp| Utc.from_utc_datetime(&timestamp).to_rfc3339()),
+        }
+    }))
+}
+
+/// Parse the report, returning all suites
+fn parse(document: &str) -> anyhow::Result<Vec<TestSuite>> {
+    let mut reader = quick_xml::Reader::from_str(document);
+    reader.config_mut().trim_text(true);
+
+    let mut suites = Vec::<TestSuite>::new();
+    let mut suite = None::<TestSuite>;
+    let mut case = None::<TestCase>;
+    let mut text = None;
+
+    loop {
+        let event = reader.read_event()?;
+        let content = match &event {
+            Event::Text(content) => Some(content.unescape()?.into_owned()),
+            Event::CData(content) => Some(String::from_utf8_lossy(content).into_owned()),
+            _ => None,
+        };
+        if let (Some(content), Some(case), Some(text)) = (content, &mut case, text) {
+            let target = match text {
+                Text::Failure => case.failure.get_or_insert_with(String::new),
+                Text::Skipped => case.skipped.get_or_insert_with(String::new),
+                Text::Output => &mut case.output,
+            };
+            if !target.is_empty() {
+                target.push('\n');
+            }
+            target.push_str(&content);
+            continue;
+        }
+
+        let (element, empty) = match event {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::End(element) => {
+                match element.name().as_ref() {
+                    b"testcase" => {
+                        if let (Some(suite), Some(case)) = (&mut suite, case.take()) {
+                            suite.tests.push(case);
+                        }
+                    }
+                    b"testsuite" => suites.extend(suite.take()),
+                    _ => {}
+                }
+                text = None;
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match element.name().as_ref() {
+            b"testsuite" => {
+                suites.extend(suite.take());
+                suite = Some(TestSuite {
+                    name: attribute(&element, "name")?,
+                    time: time(&element)?,
+                    tests: vec![],
+                });
+            }
+            b"testcase" => {
+                let name = attribute(&element, "name")?.unwrap_or_default();
+                let test = TestCase {
+                    name: match attribute(&element, "classname")? {
+                        Some(class) if !class.is_empty() => format!("{}::{}", class, name),
+                        _ => name,
+                    },
+                    time: time(&element)?.unwrap_or_default(),
+                    timestamp: timestamp(&element)?,
+                    ..Default::default()
+                };
+                // test cases outside a suite
+                suite.get_or_insert_with(TestSuite::default);
+                match empty {
+                    true => suite.as_mut().unwrap().tests.push(test),
+                    false => case = Some(test),
+                }
+            }
+            b"failure" | b"error" => {
+                if let Some(case) = &mut case {
+                    case.failure = Some(attribute(&element, "message")?.unwrap_or_default());
+                    text = Some(Text::Failure);
+                }
+            }
+            b"skipped" => {
+                if let Some(case) = &mut case {
+                    case.skipped = Some(attribute(&element, "message")?.unwrap_or_default());
+                    text = Some(Text::Skipped);
+                }
+            }
+            b"system-out" | b"system-err" => text = Some(Text::Output),
+            _ => {}
+        }
+
+        if empty {
+            text = None;
+        }
+    }
+    suites.extend(suite);
+
+    Ok(suites)
+}
+
+impl Converter for Junit {
+    fn line(&mut self, line: &str, _: &mut Vec<String>) -> anyhow::Result<()> {
+        self.document.push_str(line);
+        self.document.push('\n');
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<String>) -> anyhow::Result<()> {
+        for suite in parse(&std::mem::take(&mut self.document))? {
+            suite.write(out);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="api" tests="3" failures="1" skipped="1" time="1.5">
+    <testcase classname="api.Users" name="create" time="0.5" timestamp="2024-05-01T10:00:00.250+02:00"/>
+    <testcase classname="api.Users" name="delete" time="1.0" timestamp="2024-05-01T08:00:01">
+      <failure message="expected 204" type="AssertionError">at Users.delete(Users.java:42)</failure>
+      <system-out><![CDATA[deleting <user>]]></system-out>
+    </testcase>
+    <testcase name="legacy">
+      <skipped message="not supported"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        let mut converter = Junit::default();
+        let mut out = vec![];
+        for line in input.lines() {
+            converter.line(line, &mut out).unwrap();
+        }
+        assert!(out.is_empty());
+        converter.finish(&mut out).unwrap();
+
+        assert_eq!(
+            out,
+            vec![
+                "     Running api",
+                r#"{"event":"started","test_count":3,"type":"suite"}"#,
+                r#"{"event":"ok","exec_time":0.5,"name":"api.Users::create","start_time":"2024-05-01T10:00:00.250+02:00","type":"test"}"#,
+                r#"{"event":"failed","exec_time":1.0,"name":"api.Users::delete","start_time":"2024-05-01T08:00:01+00:00","stdout":"expected 204\nat Users.delete(Users.java:42)\n\ndeleting <user>","type":"test"}"#,
+                r#"{"event":"ignored","message":"not supported","name":"legacy","type":"test"}"#,
+                r#"{"event":"failed","exec_time":1.5,"failed":1,"filtered_out":0,"ignored":1,"measured":0,"passed":1,"type":"suite"}"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::{io::Write, process::Command};
+
+#[derive(Debug, Deserialize)]
+struct Version {
+    #[serde(rename = "serverVersion")]
+    server_version: Option<ServerVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerVersion {
+    #[serde(rename = "gitVersion")]
+    git_version: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeList {
+    items: Vec<NodeItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeItem {
+    metadata: Metadata,
+    status: NodeStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeStatus {
+    node_info: NodeInfo,
+    #[serde(default)]
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeInfo {
+    kubelet_version: String,
+    os_image: String,
+    architecture: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Condition {
+    r#type: String,
+    status: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Node {
+    name: String,
+    version: String,
+    os: String,
+    ready: bool,
+}
+
+/// The `[addon.kube]` section of the configuration.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub position: Option<crate::processor::Position>,
+}
+
+/// A snapshot of the Kubernetes cluster the tests ran against, as seen by `kubectl`.
+#[derive(Debug)]
+pub struct Kube {
+    context: String,
+    server_version: Option<String>,
+    nodes: Vec<Node>,
+}
+
+/// Run kubectl, returning its output
+fn kubectl(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("kubectl")
+        .args(args)
+        .output()
+        .map_err(|err| anyhow::anyhow!("Unable to run kubectl: {}", err))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to run kubectl {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_nodes(content: &str) -> anyhow::Result<Vec<Node>> {
+    let list: NodeList = serde_json::from_str(content)?;
+    Ok(list
+        .items
+        .into_iter()
+        .map(|item| Node {
+            name: item.metadata.name,
+            version: item.status.node_info.kubelet_version,
+            os: format!(
+                "{} ({})",
+                item.status.node_info.os_image, item.status.node_info.architecture
+            ),
+            ready: item
+                .status
+                .conditions
+                .iter()
+                .any(|condition| condition.r#type == "Ready" && condition.status == "True"),
+        })
+        .collect())
+}
+
+impl Kube {
+    /// Record the current context of kubectl
+    pub fn load() -> anyhow::Result<Self> {
+        let context = kubectl(&["config", "current-context"])?.trim().to_string();
+        let version: Version = serde_json::from_str(&kubectl(&["version", "-o", "json"])?)
+            .context("Failed to parse the version of the cluster")?;
+        let nodes = parse_nodes(&kubectl(&["get", "nodes", "-o", "json"])?)
+            .context("Failed to parse the nodes of the cluster")?;
+
+        Ok(Self {
+            context,
+            server_version: version.server_version.map(|version| version.git_version),
+            nodes,
+        })
+    }
+}
+
+impl super::Addon for Kube {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        write!(write, "**Kubernetes:** Context `{}`", self.context)?;
+        if let Some(version) = &self.server_version {
+            write!(write, ", Server {}", version)?;
+        }
+        writeln!(write)?;
+        writeln!(write)?;
+
+        if self.nodes.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(write, "| Node | Version | OS | Ready |")?;
+        writeln!(write, "| ---- | ------- | -- | ----- |")?;
+        for node in &self.nodes {
+            writeln!(
+                write,
+                "| `{}` | {} | {} | {} |",
+                node.name,
+                node.version,
+                node.os,
+                if node.ready { "✅" } else { "❌" }
+            )?;
+        }
+        writeln!(write)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+
+    #[test]
+    fn test_render() {
+        let nodes = parse_nodes(
+            r#"{ "items": [{
+  "metadata": { "name": "kind-control-plane" },
+  "status": {
+    "nodeInfo": { "kubeletVersion": "v1.29.2", "osImage": "Debian GNU/Linux 12", "architecture": "amd64" },
+    "conditions": [{ "type": "MemoryPressure", "status": "False" }, { "type": "Ready", "status": "True" }]
+  }
+}] }"#,
+        )
+        .unwrap();
+
+        let mut out = vec![];
+        Kube {
+            context: "kind-kind".into(),
+            server_version: Some("v1.29.2".into()),
+            nodes,
+        }
+        .render(&mut out)
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"**Kubernetes:** Context `kind-kind`, Server v1.29.2
+
+| Node | Version | OS | Ready |
+| ---- | ------- | -- | ----- |
+| `kind-control-plane` | v1.29.2 | Debian GNU/Linux 12 (amd64) | ✅ |
+
+"#
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Generates reports from the JSON output of `cargo test`, and other test harnesses.
+//!
+//! The [`processor::Processor`] consumes the records, line by line, and renders the report when
+//! finished. The command line tool is built on top of it.
+
+pub mod audit;
+pub mod bitbucket;
+pub mod budget;
+pub mod build_info;
+pub mod config;
+pub mod convert;
+pub mod coverage;
+pub mod criterion;
+pub mod diff;
+pub mod doctest;
+pub mod env;
+pub mod error;
+pub mod event;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod flaky;
+#[cfg(feature = "git")]
+pub mod git;
+pub mod gitea;
+pub mod github;
+pub mod go_test;
+pub mod host;
+pub mod html;
+pub mod http;
+pub mod images;
+pub mod init;
+pub mod input;
+pub mod invocation;
+pub mod junit;
+#[cfg(feature = "kube")]
+pub mod kube;
+pub mod locale;
+#[cfg(feature = "git")]
+pub mod lockfile;
+pub mod logging;
+pub mod matrix;
+pub mod model;
+pub mod output;
+pub mod pdf;
+pub mod processor;
+pub mod pytest;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod raw;
+pub mod redact;
+pub mod rename;
+pub mod rerun;
+pub mod spill;
+#[cfg(feature = "store")]
+pub mod store;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod timeline;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod webhook;
+pub mod xml;
+
+// the addons implement it as `super::Addon`
+use crate::processor::{Addon, Position};
@@ -0,0 +1,327 @@
+/// The language of the fixed labels of a report.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum Lang {
+    /// English
+    #[default]
+    En,
+    /// German
+    De,
+    /// French
+    Fr,
+    /// Japanese
+    Ja,
+}
+
+/// The translated labels of a report.
+#[derive(Debug)]
+pub struct Labels {
+    pub test_result: &'static str,
+    pub total: &'static str,
+    pub passed: &'static str,
+    pub failed: &'static str,
+    pub ignored: &'static str,
+    pub filtered: &'static str,
+    pub duration: &'static str,
+    pub name: &'static str,
+    pub result: &'static str,
+    pub case: &'static str,
+    pub index: &'static str,
+    pub details: &'static str,
+    pub suites: &'static str,
+    pub suite: &'static str,
+    pub kind: &'static str,
+    pub share: &'static str,
+    pub test_time: &'static str,
+    pub wall_clock: &'static str,
+    pub reason: &'static str,
+    pub trend: &'static str,
+    pub pass_rate: &'static str,
+    pub change: &'static str,
+    pub search: &'static str,
+    pub generated_by: &'static str,
+    pub generated_at: &'static str,
+    pub processing_time: &'static str,
+    pub unit_tests: &'static str,
+    pub integration_tests: &'static str,
+    pub doc_tests: &'static str,
+    pub started_at: &'static str,
+    pub execution_order: &'static str,
+    pub events: &'static str,
+    pub run: &'static str,
+    pub platform: &'static str,
+}
+
+/// Labels replacing the fixed ones, like the section `[labels]` of the configuration.
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomLabels {
+    /// The heading of the index.
+    pub index: Option<String>,
+    /// The heading of the details.
+    pub details: Option<String>,
+    pub total: Option<String>,
+    pub passed: Option<String>,
+    pub failed: Option<String>,
+    pub ignored: Option<String>,
+    pub filtered: Option<String>,
+    pub duration: Option<String>,
+}
+
+impl CustomLabels {
+    /// The heading of the index, custom or fixed.
+    pub fn index_title<'a>(&'a self, labels: &'a Labels) -> &'a str {
+        self.index.as_deref().unwrap_or(labels.index)
+    }
+
+    /// The heading of the details, custom or fixed.
+    pub fn details_title<'a>(&'a self, labels: &'a Labels) -> &'a str {
+        self.details.as_deref().unwrap_or(labels.details)
+    }
+
+    /// The columns of the summary: total, passed, failed, ignored, filtered, and duration.
+    pub fn summary<'a>(&'a self, labels: &'a Labels) -> [&'a str; 6] {
+        [
+            self.total.as_deref().unwrap_or(labels.total),
+            self.passed.as_deref().unwrap_or(labels.passed),
+            self.failed.as_deref().unwrap_or(labels.failed),
+            self.ignored.as_deref().unwrap_or(labels.ignored),
+            self.filtered.as_deref().unwrap_or(labels.filtered),
+            self.duration.as_deref().unwrap_or(labels.duration),
+        ]
+    }
+}
+
+const EN: Labels = Labels {
+    test_result: "Test Result",
+    total: "Total",
+    passed: "Passed",
+    failed: "Failed",
+    ignored: "Ignored",
+    filtered: "Filtered",
+    duration: "Duration",
+    name: "Name",
+    result: "Result",
+    case: "Case",
+    index: "Index",
+    details: "Details",
+    suites: "Suites",
+    suite: "Suite",
+    kind: "Kind",
+    share: "Share",
+    test_time: "Total test time",
+    wall_clock: "Wall clock",
+    reason: "Reason",
+    trend: "Trend",
+    pass_rate: "Pass rate",
+    change: "Change",
+    search: "Search",
+    generated_by: "Generated by",
+    generated_at: "Generated at",
+    processing_time: "Processing time",
+    unit_tests: "Unit tests",
+    integration_tests: "Integration tests",
+    doc_tests: "Doc tests",
+    started_at: "Started at",
+    execution_order: "Execution order",
+    events: "Events",
+    run: "Run",
+    platform: "Platform",
+};
+
+const DE: Labels = Labels {
+    test_result: "Testergebnis",
+    total: "Gesamt",
+    passed: "Bestanden",
+    failed: "Fehlgeschlagen",
+    ignored: "Ignoriert",
+    filtered: "Gefiltert",
+    duration: "Dauer",
+    name: "Name",
+    result: "Ergebnis",
+    case: "Fall",
+    index: "Übersicht",
+    details: "Details",
+    suites: "Testsuiten",
+    suite: "Testsuite",
+    kind: "Art",
+    share: "Anteil",
+    test_time: "Gesamte Testzeit",
+    wall_clock: "Laufzeit",
+    reason: "Grund",
+    trend: "Verlauf",
+    pass_rate: "Erfolgsquote",
+    change: "Änderung",
+    search: "Suchen",
+    generated_by: "Erstellt mit",
+    generated_at: "Erstellt am",
+    processing_time: "Verarbeitungszeit",
+    unit_tests: "Unit-Tests",
+    integration_tests: "Integrationstests",
+    doc_tests: "Doc-Tests",
+    started_at: "Gestartet um",
+    execution_order: "Ausführungsreihenfolge",
+    events: "Ereignisse",
+    run: "Lauf",
+    platform: "Plattform",
+};
+
+const FR: Labels = Labels {
+    test_result: "Résultat des tests",
+    total: "Total",
+    passed: "Réussis",
+    failed: "Échoués",
+    ignored: "Ignorés",
+    filtered: "Filtrés",
+    duration: "Durée",
+    name: "Nom",
+    result: "Résultat",
+    case: "Cas",
+    index: "Index",
+    details: "Détails",
+    suites: "Suites",
+    suite: "Suite",
+    kind: "Type",
+    share: "Part",
+    test_time: "Temps de test total",
+    wall_clock: "Temps réel",
+    reason: "Raison",
+    trend: "Tendance",
+    pass_rate: "Taux de réussite",
+    change: "Variation",
+    search: "Rechercher",
+    generated_by: "Généré par",
+    generated_at: "Généré le",
+    processing_time: "Temps de traitement",
+    unit_tests: "Tests unitaires",
+    integration_tests: "Tests d'intégration",
+    doc_tests: "Tests de documentation",
+    started_at: "Démarré à",
+    execution_order: "Ordre d'exécution",
+    events: "Événements",
+    run: "Exécution",
+    platform: "Plateforme",
+};
+
+const JA: Labels = Labels {
+    test_result: "テスト結果",
+    total: "合計",
+    passed: "成功",
+    failed: "失敗",
+    ignored: "無視",
+    filtered: "除外",
+    duration: "所要時間",
+    name: "名前",
+    result: "結果",
+    case: "ケース",
+    index: "一覧",
+    details: "詳細",
+    suites: "スイート",
+    suite: "スイート",
+    kind: "種類",
+    share: "割合",
+    test_time: "合計テスト時間",
+    wall_clock: "実時間",
+    reason: "理由",
+    trend: "推移",
+    pass_rate: "成功率",
+    change: "変化",
+    search: "検索",
+    generated_by: "生成ツール",
+    generated_at: "生成日時",
+    processing_time: "処理時間",
+    unit_tests: "単体テスト",
+    integration_tests: "結合テスト",
+    doc_tests: "ドキュメントテスト",
+    started_at: "開始時刻",
+    execution_order: "実行順序",
+    events: "イベント",
+    run: "実行",
+    platform: "プラットフォーム",
+};
+
+impl Lang {
+    /// The language tag, like `en`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+            Self::Fr => "fr",
+            Self::Ja => "ja",
+        }
+    }
+
+    pub fn labels(&self) -> &'static Labels {
+        match self {
+            Self::En => &EN,
+            Self::De => &DE,
+            Self::Fr => &FR,
+            Self::Ja => &JA,
+        }
+    }
+
+    /// The separators of thousands and decimals.
+    fn separators(&self) -> (char, char) {
+        match self {
+            Self::En | Self::Ja => (',', '.'),
+            Self::De => ('.', ','),
+            // narrow no-break space
+            Self::Fr => ('\u{202f}', ','),
+        }
+    }
+
+    /// Format a count, grouping its thousands.
+    pub fn format_count(&self, count: u64) -> String {
+        group_thousands(&count.to_string(), self.separators().0)
+    }
+
+    /// Localize a formatted number, which uses `.` as decimal separator, like `1234.5ms`.
+    pub fn localize_number(&self, value: &str) -> String {
+        let (thousands, decimal) = self.separators();
+        let digits = value
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(digits);
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+        let mut result = group_thousands(integer, thousands);
+        if !fraction.is_empty() {
+            result.push(decimal);
+            result.push_str(fraction);
+        }
+        result.push_str(unit);
+        result
+    }
+}
+
+fn group_thousands(digits: &str, separator: char) -> String {
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (idx, c) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            result.push(separator);
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(Lang::En.format_count(0), "0");
+        assert_eq!(Lang::En.format_count(999), "999");
+        assert_eq!(Lang::En.format_count(12345), "12,345");
+        assert_eq!(Lang::De.format_count(1234567), "1.234.567");
+        assert_eq!(Lang::Fr.format_count(12345), "12\u{202f}345");
+    }
+
+    #[test]
+    fn test_localize_number() {
+        assert_eq!(Lang::En.localize_number("1234.5s"), "1,234.5s");
+        assert_eq!(Lang::De.localize_number("1234.5s"), "1.234,5s");
+        assert_eq!(Lang::De.localize_number("12ms"), "12ms");
+        assert_eq!(Lang::De.localize_number("1.5µs"), "1,5µs");
+    }
+}
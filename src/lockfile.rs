@@ -0,0 +1,119 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Deserialize)]
+struct LockFile {
+    #[serde(default)]
+    package: Vec<Package>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    /// Missing for the packages of the workspace.
+    source: Option<String>,
+}
+
+/// The options of the `[addon.cargo-lock]` section of the configuration.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub file: PathBuf,
+    pub position: Option<crate::processor::Position>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            file: "Cargo.lock".into(),
+            position: None,
+        }
+    }
+}
+
+/// A fingerprint of the dependencies, from the `Cargo.lock` file.
+#[derive(Debug)]
+pub struct Dependencies {
+    /// The git blob ID of the file, like `git hash-object Cargo.lock`.
+    hash: String,
+    crates: usize,
+    external: usize,
+}
+
+impl Dependencies {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read(path)
+            .with_context(|| format!("Failed to read dependencies from {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse dependencies from {}", path.display()))
+    }
+
+    fn parse(content: &[u8]) -> anyhow::Result<Self> {
+        let lock: LockFile = toml::from_str(std::str::from_utf8(content)?)?;
+        Ok(Self {
+            hash: git2::Oid::hash_object(git2::ObjectType::Blob, content)?.to_string(),
+            crates: lock.package.len(),
+            external: lock
+                .package
+                .iter()
+                .filter(|package| package.source.is_some())
+                .count(),
+        })
+    }
+}
+
+impl super::Addon for Dependencies {
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        writeln!(
+            write,
+            "**Dependencies:** {} crates ({} external) · `Cargo.lock` `{}`",
+            self.crates,
+            self.external,
+            &self.hash[..12]
+        )?;
+        writeln!(write)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+
+    #[test]
+    fn test_render() {
+        let content = br#"version = 4
+
+[[package]]
+name = "anyhow"
+version = "1.0.86"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["anyhow"]
+"#;
+        let dependencies = Dependencies::parse(content).unwrap();
+        assert_eq!(
+            dependencies.hash,
+            git2::Oid::hash_object(git2::ObjectType::Blob, content)
+                .unwrap()
+                .to_string()
+        );
+
+        let mut out = vec![];
+        dependencies.render(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "**Dependencies:** 2 crates (1 external) · `Cargo.lock` `{}`\n\n",
+                &dependencies.hash[..12]
+            )
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::Utc;
+use log::{
+    kv::{Error, Key, Value, VisitSource},
+    LevelFilter, Log, Metadata, Record,
+};
+use serde_json::{Map, Value as Json};
+use std::io::Write;
+
+/// The format of the log output on stderr.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human readable text
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// A logger writing one JSON object per line to stderr, including the key values of a record.
+struct JsonLogger {
+    level: LevelFilter,
+}
+
+struct Fields<'a>(&'a mut Map<String, Json>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        let value = match value.to_u64() {
+            Some(value) => Json::from(value),
+            None => Json::from(value.to_string()),
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+impl JsonLogger {
+    fn format(&self, record: &Record) -> Json {
+        let mut fields = Map::new();
+        fields.insert("time".into(), Utc::now().to_rfc3339().into());
+        fields.insert(
+            "level".into(),
+            record.level().as_str().to_lowercase().into(),
+        );
+        fields.insert("target".into(), record.target().into());
+        fields.insert("message".into(), record.args().to_string().into());
+        // a failing visitor only loses the additional fields
+        let _ = record.key_values().visit(&mut Fields(&mut fields));
+        Json::Object(fields)
+    }
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let _ = writeln!(std::io::stderr().lock(), "{}", self.format(record));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Initialize the JSON logger.
+pub fn init_json(level: LevelFilter) -> anyhow::Result<()> {
+    log::set_boxed_logger(Box::new(JsonLogger { level }))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let logger = JsonLogger {
+            level: LevelFilter::Debug,
+        };
+        let kvs = [("line", 42u64)];
+        let json = logger.format(
+            &Record::builder()
+                .args(format_args!("Ignoring line"))
+                .level(log::Level::Warn)
+                .target("markdown_test_report::processor")
+                .key_values(&kvs)
+                .build(),
+        );
+
+        assert_eq!(json["level"], "warn");
+        assert_eq!(json["message"], "Ignoring line");
+        assert_eq!(json["target"], "markdown_test_report::processor");
+        assert_eq!(json["line"], 42);
+    }
+}
@@ -1,80 +1,490 @@
-// #![deny(missing_docs)]
-mod event;
-mod git;
-mod processor;
-
-use crate::processor::{ProcessOptions, Processor};
-use crate::{git::GitInfo, processor::Addon};
-use clap::Parser;
+use chrono::Utc;
+use clap::{Parser, Subcommand};
 use log::LevelFilter;
+use markdown_test_report::audit::Audit;
+use markdown_test_report::budget::Budget;
+use markdown_test_report::build_info::BuildInfo;
+use markdown_test_report::coverage::Coverage;
+use markdown_test_report::criterion::Benchmarks;
+use markdown_test_report::error::Error;
+use markdown_test_report::host::HostInfo;
+use markdown_test_report::html::Theme;
+use markdown_test_report::http::Secret;
+use markdown_test_report::images::Images;
+use markdown_test_report::input::InputFormat;
+#[cfg(feature = "kube")]
+use markdown_test_report::kube;
+use markdown_test_report::locale::Lang;
+use markdown_test_report::lockfile::Dependencies;
+use markdown_test_report::logging::LogFormat;
+use markdown_test_report::output::Compression;
+use markdown_test_report::processor::{
+    Flavor, Format, Position, ProcessOptions, Processor, Report, RunMode,
+};
+use markdown_test_report::redact::Redactor;
+use markdown_test_report::rename::Rename;
+use markdown_test_report::{
+    bitbucket, config, convert, diff, env, filter, flaky, git, gitea, github, init, input, logging,
+    matrix, model, output, pdf, processor, raw, rerun, store, timeline, webhook,
+};
+use markdown_test_report::{
+    git::GitInfo,
+    processor::{Addon, Notifier},
+};
 use simplelog::{ColorChoice, Config, TermLogger, TerminalMode};
-use std::io::Write;
-use std::ops::Deref;
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, BufWriter},
-    path::Path,
+    ffi::OsString,
+    io::{BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
 };
 
 #[derive(Debug, Parser)]
 #[command(name = "Markdown Test Reporter", version, about, author, long_about = None)]
 struct Cli {
-    /// The filename of the JSON test data. Unnecessary or unparsable lines will be ignored
+    /// The filename of the JSON test data, optionally gzip or zstd compressed. Unnecessary or unparsable lines will be ignored
     #[arg(value_parser, default_value = "test-output.json")]
     input: String,
+    /// The format of the input
+    #[arg(long, value_enum, default_value_t, global = true)]
+    input_format: InputFormat,
     /// The name of the output file
-    #[arg(short, long, value_parser)]
+    #[arg(short, long, value_parser, global = true)]
     output: Option<String>,
+    /// The directory to write the output file to, using the name of --output-name
+    #[arg(long, value_name = "DIR", conflicts_with = "output", global = true)]
+    output_dir: Option<PathBuf>,
+    /// The name of the output file, supporting the placeholders {stem}, {date}, {outcome}, {sha} and {labels} [default: {stem}.md]
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with = "output",
+        global = true
+    )]
+    output_name: Option<String>,
+    /// The configuration file, with sections for the addons and the labels [default: markdown-test-report.toml, if it exists]
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+    /// Check the generated markdown, like that the tables are well-formed and links have an anchor, failing on problems
+    #[arg(long, global = true)]
+    verify_output: bool,
+    /// Fail on lines which are not records, or unknown records, instead of ignoring them
+    #[arg(long, global = true)]
+    strict: bool,
+    /// Don't end the report with the tool, the time, and the duration of creating it
+    #[arg(long, global = true)]
+    no_footer: bool,
     /// Disable report metadata
-    #[arg(short='d', long, action = clap::ArgAction::SetTrue)]
+    #[arg(short='d', long, action = clap::ArgAction::SetTrue, global = true)]
     no_front_matter: bool,
     /// git top-level location [default: .]
-    #[arg(short, long, value_parser)]
+    #[arg(short, long, value_parser, global = true)]
     git: Option<String>,
     /// Show only the summary section
-    #[arg(short, long, action)]
+    #[arg(short, long, action, global = true)]
     summary: bool,
+    /// Also write the summary section to this file, besides the full report, like for the summary of a CI job
+    #[arg(long, value_name = "FILE", conflicts_with = "summary", global = true)]
+    summary_output: Option<String>,
+    /// Append the summary to its output file, like $GITHUB_STEP_SUMMARY, instead of overwriting it
+    #[arg(long, requires = "summary_output", global = true)]
+    summary_append: bool,
     /// Be quiet
-    #[arg(short, long, conflicts_with = "verbose")]
+    #[arg(short, long, conflicts_with = "verbose", global = true)]
     quiet: bool,
     /// Be more verbose. May be repeated multiple times
-    #[arg(short, long, action = clap::ArgAction::Count)]
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
     verbose: u8,
+    /// The format of log messages on stderr
+    #[arg(long, value_enum, default_value_t, global = true)]
+    log_format: LogFormat,
+    /// Overwrite an existing output file
+    #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "backup", global = true)]
+    force: bool,
+    /// Keep an existing output file, by renaming it with a .bak extension
+    #[arg(long, action = clap::ArgAction::SetTrue, global = true)]
+    backup: bool,
     /// Disable extracting git information
-    #[arg(short, long, action = clap::ArgAction::SetTrue, conflicts_with = "git")]
+    #[arg(short, long, action = clap::ArgAction::SetTrue, conflicts_with = "git", global = true)]
     no_git: bool,
     /// Allow Precise Time reporting
-    #[arg(short, long, action = clap::ArgAction::SetTrue)]
+    #[arg(short, long, action = clap::ArgAction::SetTrue, global = true)]
     precise: bool,
+    /// Render when each test started, and the order of execution, if the input has timestamps
+    #[arg(long, action = clap::ArgAction::SetTrue, global = true)]
+    timestamps: bool,
+    /// A JSON file of external events, rendered along the tests running at the time, implies --timestamps
+    #[arg(long, value_name = "FILE", global = true)]
+    timeline: Option<PathBuf>,
+    /// How to handle multiple test runs in the same input
+    #[arg(long, value_enum, default_value_t, global = true)]
+    runs: RunMode,
+    /// Keep captured test output in a temporary file instead of memory, for processing huge inputs
+    #[arg(long, action = clap::ArgAction::SetTrue, global = true)]
+    spill: bool,
+    /// Number of threads parsing the input, 0 uses all available cores
+    #[arg(short, long, default_value_t = 1, global = true)]
+    jobs: usize,
+    /// Map uncompressed input files into memory, instead of reading them, for processing huge inputs
+    #[arg(long, action = clap::ArgAction::SetTrue, global = true)]
+    mmap: bool,
+    /// Compress the output, also when writing to stdout
+    #[arg(long, value_enum, global = true)]
+    compress: Option<Compression>,
+    /// Merge the results into the existing output file, which must have been created with --append
+    #[arg(short, long, action = clap::ArgAction::SetTrue, global = true)]
+    append: bool,
+    /// The format of the report
+    #[arg(short, long, value_enum, default_value_t, global = true)]
+    format: Format,
+    /// The markdown dialect of the platform the report is shown on
+    #[arg(long, value_enum, default_value_t, global = true)]
+    flavor: Flavor,
+    /// Link to the full report, used by the comment format
+    #[arg(long, global = true)]
+    report_url: Option<String>,
+    /// The link to the CI job, with environment variables like 'https://ci.example.com/builds/{BUILD_ID}' [default: the run of GitHub Actions]
+    #[arg(long, value_name = "TEMPLATE", global = true)]
+    job_link_template: Option<String>,
+    /// List the paths with uncommitted changes to tracked files in the git worktree
+    #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "no_git", global = true)]
+    git_changes: bool,
+    /// List the commits since the last tag
+    #[arg(long, action = clap::ArgAction::SetTrue, conflicts_with = "no_git", global = true)]
+    git_changelog: bool,
+    /// An lcov or cobertura file, rendering its coverage totals
+    #[arg(long, value_name = "FILE", global = true)]
+    coverage: Option<PathBuf>,
+    /// Show the coverage of each crate
+    #[arg(long, action = clap::ArgAction::SetTrue, requires = "coverage", global = true)]
+    coverage_per_crate: bool,
+    /// The JSON output of cargo audit or cargo deny, rendering a summary of the vulnerabilities
+    #[arg(long, value_name = "FILE", global = true)]
+    audit: Option<PathBuf>,
+    /// Replace matches of a regular expression in the output of tests. Only the group named 'secret' is replaced, if there is one. May be repeated
+    #[arg(long, value_name = "REGEX", value_parser = regex::Regex::new, global = true)]
+    redact: Vec<regex::Regex>,
+    /// Don't replace common shapes of credentials in the output of tests, like bearer tokens
+    #[arg(long, global = true)]
+    no_builtin_redactions: bool,
+    /// Record environment variables, like 'RUST_LOG,DATABASE_URL:redact'. Values of redacted variables, and of ones named like secrets, are not shown
+    #[arg(long, value_name = "VARS", value_delimiter = ',', value_parser = env::EnvVar::parse, global = true)]
+    env_vars: Vec<env::EnvVar>,
+    /// Record the host, the runner, the number of CPUs, and the number of test threads
+    #[arg(long, global = true)]
+    host_info: bool,
+    /// A file listing the container images used by the tests, one reference per line
+    #[arg(long, value_name = "FILE", global = true)]
+    images: Option<PathBuf>,
+    /// An environment variable containing the reference of a container image used by the tests. May be repeated
+    #[arg(long, value_name = "VAR", global = true)]
+    image_env: Vec<String>,
+    /// Fingerprint the dependencies using the Cargo.lock file, showing its hash and the number of crates
+    #[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true, default_missing_value = "Cargo.lock", global = true)]
+    cargo_lock: Option<PathBuf>,
+    /// The cargo profile the tests were built with, like 'release'
+    #[arg(long, value_name = "NAME", global = true)]
+    build_profile: Option<String>,
+    /// The cargo features the tests were built with, like 'default,json'
+    #[arg(long, value_name = "FEATURES", value_delimiter = ',', global = true)]
+    build_features: Vec<String>,
+    /// The JSON messages of cargo, like of 'cargo test --no-run --message-format json', recording the profile and the features of the tested packages
+    #[arg(long, value_name = "FILE", global = true)]
+    build_messages: Option<PathBuf>,
+    /// Record the Kubernetes cluster of the current context of kubectl, with its nodes
+    #[cfg(feature = "kube")]
+    #[arg(long, global = true)]
+    kube: bool,
+    /// Append the benchmark results of criterion, usually found in target/criterion
+    #[arg(long, value_name = "DIR", global = true)]
+    criterion: Option<PathBuf>,
+    /// Embed the original inputs in a collapsed appendix, optionally compressed
+    #[arg(long, value_enum, value_name = "ENCODING", num_args = 0..=1, require_equals = true, default_missing_value = "plain", global = true)]
+    embed_raw: Option<raw::Encoding>,
+    /// The base URL of the source files, used to link doc-tests to their source. Defaults to the commit when running in GitHub Actions
+    #[arg(long, value_name = "URL", global = true)]
+    source_url: Option<String>,
+    /// Rewrite the displayed names of tests, like 's/^integration:://'. May be repeated
+    #[arg(long, value_name = "RULE", value_parser = Rename::parse, global = true)]
+    rename: Vec<Rename>,
+    /// Truncate test names in tables exceeding this number of characters, keeping the full name as tooltip
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(2..), global = true)]
+    max_name_length: Option<u32>,
+    /// The language of the report, also used for formatting numbers
+    #[arg(
+        long,
+        visible_alias = "locale",
+        value_enum,
+        default_value_t,
+        global = true
+    )]
+    lang: Lang,
+    /// The heading of the index, instead of "Index" in the selected language
+    #[arg(long, value_name = "TITLE", global = true)]
+    index_title: Option<String>,
+    /// The heading of the details, instead of "Details" in the selected language
+    #[arg(long, value_name = "TITLE", global = true)]
+    details_title: Option<String>,
+    /// The color theme of the HTML report, printing always uses the light one
+    #[arg(long, value_enum, default_value_t, global = true)]
+    theme: Theme,
+    /// The command converting the HTML report into a PDF document, like weasyprint, or a browser like chromium, which must be installed
+    #[arg(long, value_name = "COMMAND", default_value = pdf::DEFAULT_CONVERTER, global = true)]
+    pdf_converter: String,
+    /// Limit the duration of the tests, like 'integration=10m' or 'total=20m'. The scope may be total, unit, integration, or doc. May be repeated
+    #[arg(long, value_name = "SCOPE=DURATION", value_parser = Budget::parse, global = true)]
+    budget: Vec<Budget>,
+    /// Fail when the tests exceeded a budget, after writing the report
+    #[arg(long, requires = "budget", global = true)]
+    fail_over_budget: bool,
+    /// Store the results in a SQLite database
+    #[arg(long, value_name = "FILE", global = true)]
+    store: Option<PathBuf>,
+    /// The number of runs in the trend of the store, shown below the summary. 0 disables the trend
+    #[arg(long, value_name = "RUNS", default_value_t = 20, global = true)]
+    trend_runs: usize,
+    /// Show the change of each test's duration in the index, compared to its average over the runs of the trend
+    #[arg(long, requires = "store", global = true)]
+    duration_delta: bool,
+    /// Post the summary as a comment to a pull request of Gitea or Forgejo, like 'https://codeberg.org/owner/repo/pulls/1'
+    #[arg(long, value_name = "URL", requires = "gitea_token", global = true)]
+    gitea_comment: Option<String>,
+    /// The access token for posting to Gitea or Forgejo
+    #[arg(long, env = "GITEA_TOKEN", hide_env_values = true, global = true)]
+    gitea_token: Option<String>,
+    /// Push the results to the Code Insights reports of the commit in Bitbucket Cloud
+    #[arg(long, requires_all = ["bitbucket_repo", "bitbucket_token"], global = true)]
+    bitbucket_report: bool,
+    /// The Bitbucket repository, like 'workspace/repo'
+    #[arg(
+        long,
+        value_name = "REPOSITORY",
+        env = "BITBUCKET_REPO_FULL_NAME",
+        global = true
+    )]
+    bitbucket_repo: Option<String>,
+    /// The commit to report on, instead of the HEAD of the git repository
+    #[arg(long, value_name = "SHA", env = "BITBUCKET_COMMIT", global = true)]
+    bitbucket_commit: Option<String>,
+    /// The access token for pushing to Bitbucket
+    #[arg(long, env = "BITBUCKET_TOKEN", hide_env_values = true, global = true)]
+    bitbucket_token: Option<String>,
+    /// Create a check run of the commit on GitHub, with the failed tests as annotations
+    #[arg(long, requires_all = ["github_repo", "github_token"], global = true)]
+    github_check: bool,
+    /// The GitHub repository, like 'owner/repo'
+    #[arg(
+        long,
+        value_name = "REPOSITORY",
+        env = "GITHUB_REPOSITORY",
+        global = true
+    )]
+    github_repo: Option<String>,
+    /// The commit of the check run, instead of the HEAD of the git repository
+    #[arg(long, value_name = "SHA", global = true)]
+    github_sha: Option<String>,
+    /// The URL of the GitHub API
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "GITHUB_API_URL",
+        default_value = "https://api.github.com",
+        global = true
+    )]
+    github_api_url: String,
+    /// The access token for creating check runs, requires the 'checks: write' permission
+    #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true, global = true)]
+    github_token: Option<String>,
+    /// Post the summary to a Matrix room, using the ID of the room like '!abc:example.org'
+    #[arg(long, value_name = "ROOM_ID", requires_all = ["matrix_homeserver", "matrix_token"], global = true)]
+    matrix_room: Option<String>,
+    /// The URL of the Matrix homeserver, like 'https://matrix.org'
+    #[arg(long, value_name = "URL", env = "MATRIX_HOMESERVER", global = true)]
+    matrix_homeserver: Option<String>,
+    /// The access token for posting to Matrix
+    #[arg(long, env = "MATRIX_TOKEN", hide_env_values = true, global = true)]
+    matrix_token: Option<String>,
+    /// Post the results as JSON document to a URL, like the one of --format json. May be repeated
+    #[arg(long, value_name = "URL", global = true)]
+    webhook: Vec<String>,
+    /// A header of the webhook requests, like 'Authorization: Bearer token'. May be repeated
+    #[arg(long, value_name = "NAME: VALUE", value_parser = webhook::Header::parse, requires = "webhook", global = true)]
+    webhook_header: Vec<webhook::Header>,
+    /// Label the report, like with the environment the tests ran against. May be repeated
+    #[arg(long = "label", value_name = "LABEL")]
+    labels: Vec<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Merge multiple inputs, labeling the results with the shard or job that produced them
+    Merge {
+        /// A labeled input, may be repeated
+        #[arg(short, long = "label", value_name = "LABEL=FILE", value_parser = parse_label, required = true)]
+        labels: Vec<(String, PathBuf)>,
+    },
+    /// Aggregate the results of multiple platforms into a test matrix
+    Aggregate {
+        /// The results of a platform, either a JSON file or a report created with --append. May be repeated
+        #[arg(long = "platform", value_name = "PLATFORM=FILE", value_parser = parse_label, required = true)]
+        platforms: Vec<(String, PathBuf)>,
+    },
+    /// Add a CI job running the tests and creating a report to the repository
+    Init {
+        /// The CI system to integrate with
+        #[arg(value_enum)]
+        target: init::Target,
+        /// The location of the repository
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Convert test results into another format, without creating a report
+    Convert {
+        /// The format of the input
+        #[arg(long, value_enum, default_value_t)]
+        from: InputFormat,
+        /// The format to convert into
+        #[arg(long, value_enum)]
+        to: convert::OutputFormat,
+        /// The file to convert
+        source: PathBuf,
+        /// The file to write to, `-` being stdout
+        #[arg(default_value = "-")]
+        destination: String,
+    },
+    /// Print the JSON Schema of the report created by --format json
+    Schema,
+    /// Reduce test results to the selected tests, keeping the JSON format of libtest
+    Filter {
+        /// Only keep failed tests
+        #[arg(long)]
+        failed_only: bool,
+        /// Only keep tests with a name matching this regular expression
+        #[arg(long, value_name = "REGEX")]
+        name: Option<regex::Regex>,
+        /// The file to filter
+        source: PathBuf,
+        /// The file to write to, `-` being stdout
+        #[arg(default_value = "-")]
+        destination: String,
+    },
+    /// Print the command lines re-running the failed tests of a previous result
+    Rerun {
+        /// The test runner to use
+        #[arg(long, value_enum, default_value_t)]
+        runner: rerun::Runner,
+        /// Execute the commands, instead of printing them
+        #[arg(long)]
+        exec: bool,
+        /// The previous result
+        source: PathBuf,
+    },
+    /// Compare a result against a baseline, reporting changed outcomes and slower tests
+    Diff {
+        /// The minimum increase of a test's duration, in percent, to be considered a regression
+        #[arg(long, default_value_t = 20)]
+        regression_percent: u64,
+        /// The minimum absolute increase of a test's duration to be considered a regression
+        #[arg(long, value_name = "DURATION", default_value = "100ms", value_parser = humantime::parse_duration)]
+        regression_min: std::time::Duration,
+        /// Fail if any test regressed, after writing the report
+        #[arg(long)]
+        fail_on_regression: bool,
+        /// The result to compare against, either a JSON file or a report created with --append
+        baseline: PathBuf,
+        /// The current result
+        current: PathBuf,
+        /// The file to write to, `-` being stdout
+        #[arg(default_value = "-")]
+        destination: String,
+    },
+    /// Export or import the history of the results store, selected by --store
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// Report the flaky tests, from a directory of past results
+    Flaky {
+        /// The number of tests to list
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// The directory containing the past results, either JSON files or reports created with --append
+        dir: PathBuf,
+        /// The file to write to, `-` being stdout
+        #[arg(default_value = "-")]
+        destination: String,
+    },
+}
 
-    // Parse filepaths
-    let input_path = Path::new(&cli.input);
+#[derive(Debug, Subcommand)]
+enum HistoryCommand {
+    /// Export all runs of the store, as JSON
+    Export {
+        /// The file to write to, `-` being stdout
+        #[arg(default_value = "-")]
+        destination: String,
+    },
+    /// Import exported runs, appending them to the runs of the store
+    Import {
+        /// The exported runs, may be repeated
+        #[arg(required = true)]
+        sources: Vec<PathBuf>,
+    },
+}
 
-    let file_stem = input_path
-        .file_stem()
-        .ok_or_else(|| anyhow::anyhow!("unable to parse input filename"))
-        .unwrap()
-        .to_str()
-        .unwrap();
+/// Parse a labeled input, in the form of `label=file`
+fn parse_label(value: &str) -> Result<(String, PathBuf), String> {
+    match value.split_once('=') {
+        Some((label, file)) if !label.is_empty() && !file.is_empty() => {
+            Ok((label.to_string(), file.into()))
+        }
+        _ => Err(format!("expected LABEL=FILE, found: {}", value)),
+    }
+}
 
-    let output_file = match cli.output {
-        Some(o) => o,
-        None => String::from(file_stem) + ".md",
-    };
+/// The names the binary can be invoked with as a cargo subcommand
+const CARGO_SUBCOMMANDS: &[&str] = &["markdown-test-report", "test-report"];
 
-    let mut addons = Vec::<Box<dyn Addon>>::new();
+/// Strip the name of the subcommand, which cargo passes as first argument
+fn strip_cargo_subcommand(mut args: Vec<OsString>, cargo: bool) -> Vec<OsString> {
+    let subcommand = args
+        .get(1)
+        .and_then(|arg| arg.to_str())
+        .is_some_and(|arg| CARGO_SUBCOMMANDS.contains(&arg));
+    if cargo && subcommand {
+        args.remove(1);
+    }
+    args
+}
 
-    if !cli.no_git {
-        let required = cli.git.is_some();
-        addons.push(Box::new(GitInfo::new(
-            Path::new(&cli.git.as_deref().unwrap_or(".")),
-            required,
-        )));
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // like returning the error from main, but telling bad input and I/O errors apart
+            eprintln!("Error: {:?}", err);
+            let code = match (
+                err.downcast_ref::<Error>(),
+                err.downcast_ref::<std::io::Error>(),
+            ) {
+                (Some(err), _) => err.exit_code(),
+                (None, Some(_)) => 74,
+                (None, None) => 1,
+            };
+            ExitCode::from(code)
+        }
     }
+}
+
+fn run() -> anyhow::Result<()> {
+    // cargo sets $CARGO when running a subcommand
+    let cli = Cli::parse_from(strip_cargo_subcommand(
+        std::env::args_os().collect(),
+        std::env::var_os("CARGO").is_some(),
+    ));
 
     let log_level = match (cli.quiet, cli.verbose) {
         (true, _) => LevelFilter::Off,
@@ -84,44 +494,638 @@ fn main() -> anyhow::Result<()> {
         (_, _) => LevelFilter::Trace,
     };
 
-    TermLogger::init(
-        log_level,
-        Config::default(),
-        TerminalMode::Stderr,
-        ColorChoice::Auto,
-    )?;
+    match cli.log_format {
+        LogFormat::Text => TermLogger::init(
+            log_level,
+            Config::default(),
+            TerminalMode::Stderr,
+            ColorChoice::Auto,
+        )?,
+        LogFormat::Json => logging::init_json(log_level)?,
+    }
+
+    // the standalone commands neither need the configuration, nor the addons
+    if let Some(Command::Init { target, path }) = &cli.command {
+        let created = init::init(*target, path, cli.force)?;
+        println!("Created {}", created.display());
+        if *target == init::Target::Gitlab {
+            println!(
+                "Include it from your .gitlab-ci.yml, using: include: {{ local: {} }}",
+                created.strip_prefix(path).unwrap_or(&created).display()
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Schema) = &cli.command {
+        println!("{}", serde_json::to_string_pretty(&model::schema())?);
+        return Ok(());
+    }
+
+    if let Some(Command::Filter {
+        failed_only,
+        name,
+        source,
+        destination,
+    }) = &cli.command
+    {
+        if destination != "-" {
+            output::protect(Path::new(destination), cli.force, cli.backup)?;
+        }
+        let filter = filter::Filter {
+            failed_only: *failed_only,
+            name: name.clone(),
+        };
+        let mut writer = BufWriter::new(output::open(destination, None)?);
+        filter::filter(&filter, source, &mut writer)?;
+        writer.flush()?;
+        return Ok(());
+    }
+
+    if let Some(Command::Rerun {
+        runner,
+        exec,
+        source,
+    }) = &cli.command
+    {
+        let commands = rerun::commands(*runner, &rerun::failed(source)?);
+        if commands.is_empty() {
+            log::info!("No failed tests in {}", source.display());
+        }
+        for command in commands {
+            let line = command
+                .iter()
+                .map(|arg| rerun::quote(arg))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !*exec {
+                println!("{}", line);
+                continue;
+            }
+            log::info!("Running: {}", line);
+            let status = std::process::Command::new(&command[0])
+                .args(&command[1..])
+                .status()?;
+            if !status.success() {
+                anyhow::bail!("Re-running the failed tests failed: {}", status);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Diff {
+        regression_percent,
+        regression_min,
+        fail_on_regression,
+        baseline,
+        current,
+        destination,
+    }) = &cli.command
+    {
+        if destination != "-" {
+            output::protect(Path::new(destination), cli.force, cli.backup)?;
+        }
+        let thresholds = diff::Thresholds {
+            percent: *regression_percent,
+            absolute: *regression_min,
+        };
+        let mut writer = BufWriter::new(output::open(destination, None)?);
+        let regressions = diff::diff(baseline, current, thresholds, &mut writer)?;
+        writer.flush()?;
+        if regressions > 0 && *fail_on_regression {
+            anyhow::bail!("{} tests regressed in performance", regressions);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::History { command }) = &cli.command {
+        let path = cli
+            .store
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("The history requires a store, selected by --store"))?;
+        let store = store::Store::open(path, None)?;
+        match command {
+            HistoryCommand::Export { destination } => {
+                if destination != "-" {
+                    output::protect(Path::new(destination), cli.force, cli.backup)?;
+                }
+                let mut writer = BufWriter::new(output::open(destination, None)?);
+                serde_json::to_writer_pretty(&mut writer, &store.export()?)?;
+                writeln!(writer)?;
+                writer.flush()?;
+            }
+            HistoryCommand::Import { sources } => {
+                for source in sources {
+                    let history = serde_json::from_reader(input::open(source)?)?;
+                    let runs = store.import(&history)?;
+                    log::info!("Imported {} runs from {}", runs, source.display());
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Flaky {
+        top,
+        dir,
+        destination,
+    }) = &cli.command
+    {
+        if destination != "-" {
+            output::protect(Path::new(destination), cli.force, cli.backup)?;
+        }
+        let mut writer = BufWriter::new(output::open(destination, None)?);
+        flaky::flaky(dir, *top, &mut writer)?;
+        writer.flush()?;
+        return Ok(());
+    }
+
+    if let Some(Command::Convert {
+        from,
+        to,
+        source,
+        destination,
+    }) = &cli.command
+    {
+        if destination != "-" {
+            output::protect(Path::new(destination), cli.force, cli.backup)?;
+        }
+        let mut writer = BufWriter::new(output::open(destination, None)?);
+        convert::convert(*from, *to, source, &mut writer)?;
+        writer.flush()?;
+        return Ok(());
+    }
+
+    // Parse filepaths
+    let input_path = Path::new(&cli.input);
+
+    let file_stem = input::file_stem(input_path)
+        .ok_or_else(|| anyhow::anyhow!("unable to parse input filename"))?;
+
+    let template = match (&cli.output_name, cli.compress) {
+        (Some(name), _) => name.clone(),
+        (None, Some(compression)) => format!("{{stem}}.md.{}", compression.extension()),
+        (None, None) if cli.format == Format::Json => "{stem}.json".to_string(),
+        (None, None) if cli.format == Format::Html => "{stem}.html".to_string(),
+        (None, None) if cli.format == Format::Pdf => "{stem}.pdf".to_string(),
+        (None, None) => "{stem}.md".to_string(),
+    };
+
+    let sha = match cli.no_git {
+        true => None,
+        false => git::head_sha(Path::new(cli.git.as_deref().unwrap_or("."))),
+    };
+
+    let output_file = match cli.output {
+        Some(o) => o,
+        None => {
+            let placeholders = output::Placeholders {
+                stem: file_stem.to_string(),
+                date: Utc::now().format("%Y-%m-%d").to_string(),
+                sha: sha.clone().unwrap_or_else(|| "unknown".into()),
+                labels: cli.labels.join("-"),
+            };
+            let name = placeholders.render(&template);
+            match &cli.output_dir {
+                Some(dir) => {
+                    std::fs::create_dir_all(dir)?;
+                    dir.join(name).to_string_lossy().into_owned()
+                }
+                None => name,
+            }
+        }
+    };
+    // the outcome is only known after rendering the report
+    let deferred = output_file.contains("{outcome}");
+
+    // command line arguments take precedence over the configuration
+    let config = config::Config::load(cli.config.as_deref())?;
+    let mut addon = config.addon;
+    let mut custom_labels = config.labels;
+    custom_labels.index = cli.index_title.or(custom_labels.index);
+    custom_labels.details = cli.details_title.or(custom_labels.details);
+    let mut addons = Vec::<(Position, Box<dyn Addon>)>::new();
+    let mut place = |position: Option<Position>, addon: Box<dyn Addon>| {
+        addons.push((position.unwrap_or_else(|| addon.position()), addon));
+    };
+
+    let coverage = match (&cli.coverage, addon.coverage.take()) {
+        (Some(file), options) => Some((
+            file.clone(),
+            cli.coverage_per_crate,
+            options.and_then(|options| options.position),
+        )),
+        (None, Some(options)) => Some((
+            options.file,
+            options.per_crate || cli.coverage_per_crate,
+            options.position,
+        )),
+        (None, None) => None,
+    };
+    if let Some((file, per_crate, position)) = coverage {
+        place(position, Box::new(Coverage::load(&file, per_crate)?));
+    }
+
+    let audit = addon.audit.take();
+    let position = audit.as_ref().and_then(|options| options.position);
+    if let Some(file) = cli.audit.clone().or(audit.map(|options| options.file)) {
+        place(position, Box::new(Audit::load(&file)?));
+    }
+
+    let env = addon.env.take().unwrap_or_default();
+    let env_vars = match cli.env_vars.is_empty() {
+        true => env.vars,
+        false => cli.env_vars.clone(),
+    };
+    if !env_vars.is_empty() {
+        place(env.position, Box::new(env::EnvVars::capture(env_vars)));
+    }
+
+    let host = addon.host.take();
+    if cli.host_info || host.is_some() {
+        let position = host.and_then(|options| options.position);
+        place(position, Box::new(HostInfo::detect()));
+    }
+
+    let images = addon.images.take().unwrap_or_default();
+    let image_file = cli.images.clone().or(images.file);
+    let image_env = match cli.image_env.is_empty() {
+        true => images.env,
+        false => cli.image_env.clone(),
+    };
+    if image_file.is_some() || !image_env.is_empty() {
+        let loaded = Images::load(image_file.as_deref(), &image_env)?;
+        place(images.position, Box::new(loaded));
+    }
+
+    let cargo_lock = addon.cargo_lock.take();
+    let position = cargo_lock.as_ref().and_then(|options| options.position);
+    if let Some(path) = cli
+        .cargo_lock
+        .clone()
+        .or(cargo_lock.map(|options| options.file))
+    {
+        place(position, Box::new(Dependencies::load(&path)?));
+    }
+
+    if let Some(build) = addon.build.take().or_else(|| {
+        (cli.build_profile.is_some()
+            || !cli.build_features.is_empty()
+            || cli.build_messages.is_some())
+        .then(Default::default)
+    }) {
+        let features = match cli.build_features.is_empty() {
+            true => build.features,
+            false => cli.build_features.clone(),
+        };
+        let mut info = BuildInfo::new(cli.build_profile.clone().or(build.profile), features);
+        if let Some(path) = cli.build_messages.clone().or(build.messages) {
+            info.load_messages(&path)?;
+        }
+        place(build.position, Box::new(info));
+    }
+
+    #[cfg(feature = "kube")]
+    if let Some(kube) = addon.kube.take().or(cli.kube.then(Default::default)) {
+        place(kube.position, Box::new(kube::Kube::load()?));
+    }
+
+    let criterion = addon.criterion.take();
+    let position = criterion.as_ref().and_then(|options| options.position);
+    if let Some(dir) = cli
+        .criterion
+        .clone()
+        .or(criterion.map(|options| options.dir))
+    {
+        place(position, Box::new(Benchmarks::load(&dir)?));
+    }
+
+    if !cli.no_git {
+        let required = cli.git.is_some();
+        let options = addon.git.take().unwrap_or_default();
+        place(
+            options.position,
+            Box::new(
+                GitInfo::new(Path::new(&cli.git.as_deref().unwrap_or(".")), required)
+                    .with_changes(cli.git_changes || options.changes)
+                    .with_changelog(cli.git_changelog || options.changelog),
+            ),
+        );
+    }
 
     log::debug!("input_path: {}", input_path.display());
     log::debug!("file_stem: {}", file_stem);
 
-    log::debug!("Reading from: {}", input_path.display());
     log::debug!("Writing to: {}", output_file);
 
-    let input = File::open(input_path)?;
-    let reader = BufReader::new(input);
+    let inputs = match &cli.command {
+        Some(Command::Merge { labels }) | Some(Command::Aggregate { platforms: labels }) => labels
+            .iter()
+            .map(|(label, path)| (Some(label.clone()), path.clone()))
+            .collect(),
+        Some(Command::Init { .. })
+        | Some(Command::Convert { .. })
+        | Some(Command::Schema)
+        | Some(Command::Filter { .. })
+        | Some(Command::Rerun { .. })
+        | Some(Command::Flaky { .. })
+        | Some(Command::Diff { .. })
+        | Some(Command::History { .. })
+        | None => {
+            vec![(None, input_path.to_path_buf())]
+        }
+    };
+
+    let redactor = Redactor::new(cli.redact.clone(), !cli.no_builtin_redactions);
+
+    let raw = addon.raw.take();
+    let position = raw.as_ref().and_then(|options| options.position);
+    if let Some(encoding) = cli.embed_raw.or(raw.map(|options| options.encoding)) {
+        let paths = inputs.iter().map(|(_, path)| path.clone()).collect();
+        let raw = raw::RawInput::new(encoding, paths, redactor.clone());
+        addons.push((position.unwrap_or_else(|| raw.position()), Box::new(raw)));
+    }
+
+    // must be read before the output is truncated
+    let previous = match cli.append {
+        true if output_file == "-" => anyhow::bail!("Unable to append to stdout"),
+        true if cli.format == Format::Json => anyhow::bail!("Unable to append to a JSON report"),
+        true if cli.format == Format::Html => anyhow::bail!("Unable to append to an HTML report"),
+        true if cli.format == Format::Pdf => anyhow::bail!("Unable to append to a PDF report"),
+        true if deferred => anyhow::bail!("Unable to append to a file named after the outcome"),
+        true if Path::new(&output_file).exists() => {
+            let mut report = String::new();
+            input::open(Path::new(&output_file))?.read_to_string(&mut report)?;
+            if processor::embedded_records(&report).is_none() {
+                anyhow::bail!("{} contains no results to append to", output_file);
+            }
+            log::info!("Appending to {}", output_file);
+            Some(report)
+        }
+        _ => None,
+    };
+
+    if output_file != "-" && previous.is_none() && !deferred {
+        output::protect(Path::new(&output_file), cli.force, cli.backup)?;
+    }
+    if let (Some(summary), false) = (&cli.summary_output, cli.summary_append) {
+        if summary != "-" {
+            output::protect(Path::new(summary), cli.force, cli.backup)?;
+        }
+    }
+
+    let store = match &cli.store {
+        Some(path) => Some(store::Store::open(path, sha.clone())?),
+        None => None,
+    };
+
+    let mut notifiers = Vec::<Box<dyn Notifier>>::new();
+    if let (Some(url), Some(token)) = (&cli.gitea_comment, &cli.gitea_token) {
+        notifiers.push(Box::new(gitea::GiteaComment::new(
+            url,
+            Secret(token.clone()),
+        )?));
+    }
+    if let (true, Some(repo), Some(token)) = (
+        cli.bitbucket_report,
+        &cli.bitbucket_repo,
+        &cli.bitbucket_token,
+    ) {
+        let commit = cli
+            .bitbucket_commit
+            .clone()
+            .or(sha.clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Unable to detect the commit, provide it using --bitbucket-commit")
+            })?;
+        notifiers.push(Box::new(bitbucket::BitbucketReport::new(
+            repo,
+            &commit,
+            &cli.labels,
+            Secret(token.clone()),
+        )?));
+    }
+    if let (true, Some(repo), Some(token)) = (cli.github_check, &cli.github_repo, &cli.github_token)
+    {
+        let sha = cli.github_sha.clone().or(sha).ok_or_else(|| {
+            anyhow::anyhow!("Unable to detect the commit, provide it using --github-sha")
+        })?;
+        notifiers.push(Box::new(github::GithubCheck::new(
+            &cli.github_api_url,
+            repo,
+            sha,
+            &cli.labels,
+            Secret(token.clone()),
+        )?));
+    }
+    if let (Some(room), Some(homeserver), Some(token)) =
+        (&cli.matrix_room, &cli.matrix_homeserver, &cli.matrix_token)
+    {
+        notifiers.push(Box::new(matrix::MatrixRoom::new(
+            homeserver,
+            room,
+            Secret(token.clone()),
+        )?));
+    }
+    for url in &cli.webhook {
+        notifiers.push(Box::new(webhook::Webhook::new(
+            url.clone(),
+            cli.webhook_header.clone(),
+        )));
+    }
+
+    let mut temp = None;
+    let writer = match deferred {
+        true => {
+            let dir = Path::new(&output_file)
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            let mut builder = tempfile::Builder::new();
+            builder.prefix(".markdown-test-report-");
+            // the report is persisted under its final name, don't keep it private
+            #[cfg(unix)]
+            builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o644));
+            let (file, path) = builder.tempfile_in(dir)?.into_parts();
+            temp = Some(path);
+            BufWriter::new(output::compress(Box::new(file), cli.compress)?)
+        }
+        false => BufWriter::new(output::open(&output_file, cli.compress)?),
+    };
 
-    let output: Box<dyn Write> = match output_file.deref() {
-        "-" => Box::new(std::io::stdout()),
-        output => Box::new(File::create(output)?),
+    // shared with the signal handler, which persists a partial report too
+    let persist = {
+        let temp = Mutex::new(temp);
+        let output_file = output_file.clone();
+        let (force, backup) = (cli.force, cli.backup);
+        Arc::new(move |report: &Report| -> anyhow::Result<()> {
+            let temp = temp.lock().unwrap_or_else(PoisonError::into_inner).take();
+            if let Some(temp) = temp {
+                let output_file = output_file.replace("{outcome}", report.outcome.name());
+                output::protect(Path::new(&output_file), force, backup)?;
+                log::debug!("Writing to: {}", output_file);
+                temp.persist(&output_file)?;
+            }
+            Ok(())
+        })
     };
-    let writer = BufWriter::new(output);
 
     {
-        let mut processor = Processor::new(
+        let processor = Processor::new(
             writer,
             ProcessOptions {
                 disable_front_matter: cli.no_front_matter,
                 addons,
                 summary: cli.summary,
+                summary_output: cli.summary_output.clone(),
+                summary_append: cli.summary_append,
                 precise: cli.precise,
+                timestamps: cli.timestamps || cli.timeline.is_some(),
+                timeline: match &cli.timeline {
+                    Some(path) => timeline::load(path)?,
+                    None => vec![],
+                },
+                runs: cli.runs,
+                spill: cli.spill,
+                embed_records: cli.append,
+                format: cli.format,
+                flavor: cli.flavor,
+                footer: !cli.no_footer,
+                verify: cli.verify_output,
+                strict: cli.strict,
+                report_url: cli.report_url,
+                job_link_template: cli.job_link_template,
+                labels: cli.labels,
+                aggregate: matches!(cli.command, Some(Command::Aggregate { .. })),
+                source_url: cli.source_url,
+                renames: cli.rename,
+                max_name_length: cli.max_name_length.map(|max| max as usize),
+                lang: cli.lang,
+                custom_labels,
+                theme: cli.theme,
+                pdf_converter: cli.pdf_converter,
+                budgets: cli.budget,
+                store,
+                trend_runs: cli.trend_runs,
+                duration_delta: cli.duration_delta,
+                notifiers,
+                observers: vec![],
+                redactor,
             },
         );
+        let processor = Arc::new(Mutex::new(Some(processor)));
+        handle_signals(processor.clone(), {
+            let persist = persist.clone();
+            move |report: &Report| persist(report)
+        })?;
 
-        for line in reader.lines() {
-            processor.line(&line?)?;
+        if let Some(records) = previous.as_deref().and_then(processor::embedded_records) {
+            with_processor(&processor, |processor| processor.replay(&records, true))?;
         }
+
+        let jobs = match cli.jobs {
+            0 => std::thread::available_parallelism()?.get(),
+            jobs => jobs,
+        };
+        log::debug!("Parsing with {} threads", jobs);
+
+        for (label, path) in inputs {
+            log::debug!("Reading from: {}", path.display());
+            let streamed = input::is_streamed(&path);
+            with_processor(&processor, |processor| {
+                processor.set_label(label);
+                processor.set_streamed(streamed);
+                Ok(())
+            })?;
+
+            if input::is_report(&path) {
+                let mut report = String::new();
+                input::open(&path)?.read_to_string(&mut report)?;
+                let records = processor::embedded_records(&report).ok_or_else(|| {
+                    anyhow::anyhow!("{} contains no embedded results", path.display())
+                })?;
+                with_processor(&processor, |processor| processor.replay(&records, false))?;
+                continue;
+            }
+
+            let reader = input::Lines::open(&path, cli.mmap)?;
+            if let Some(mut converter) = cli.input_format.converter() {
+                let mut lines = vec![];
+                reader.read_lines(|line| {
+                    converter.line(line, &mut lines)?;
+                    with_processor(&processor, |processor| {
+                        lines.drain(..).try_for_each(|line| processor.line(&line))
+                    })
+                })?;
+                converter.finish(&mut lines)?;
+                with_processor(&processor, |processor| {
+                    lines.drain(..).try_for_each(|line| processor.line(&line))
+                })?;
+            } else if jobs > 1 {
+                reader.parse_parallel(jobs, |line, result| {
+                    with_processor(&processor, |processor| processor.parsed(line, result))
+                })?;
+            } else {
+                reader.read_lines(|line| {
+                    with_processor(&processor, |processor| processor.line(line))
+                })?;
+            }
+        }
+
+        let report = match lock(&processor).take() {
+            Some(processor) => processor.finish()?,
+            None => return Ok(()),
+        };
+
+        persist(&report)?;
+
+        if report.over_budget && cli.fail_over_budget {
+            anyhow::bail!("The tests exceeded their time budget");
+        }
+    }
+
+    Ok(())
+}
+
+/// A processor, which can be taken over by the signal handler.
+type Shared<W> = Arc<Mutex<Option<Processor<W>>>>;
+
+fn lock<W: Write>(processor: &Shared<W>) -> MutexGuard<'_, Option<Processor<W>>> {
+    processor.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run a function with the processor, unless it was taken over by the signal handler
+fn with_processor<W, F>(processor: &Shared<W>, f: F) -> anyhow::Result<()>
+where
+    W: Write,
+    F: FnOnce(&mut Processor<W>) -> Result<(), Error>,
+{
+    match lock(processor).as_mut() {
+        Some(processor) => Ok(f(processor)?),
+        None => Ok(()),
     }
+}
 
+/// Write a partial report when receiving SIGINT or SIGTERM, then exit
+fn handle_signals<W, P>(processor: Shared<W>, persist: P) -> anyhow::Result<()>
+where
+    W: Write + Send + 'static,
+    P: Fn(&Report) -> anyhow::Result<()> + Send + 'static,
+{
+    ctrlc::set_handler(move || {
+        if let Some(mut processor) = lock(&processor).take() {
+            log::warn!("Interrupted, writing a partial report");
+            processor.interrupt();
+            let result = processor
+                .finish()
+                .map_err(anyhow::Error::from)
+                .and_then(|report| persist(&report));
+            if let Err(err) = result {
+                log::error!("Unable to write the partial report: {:#}", err);
+            }
+        }
+        std::process::exit(130);
+    })?;
     Ok(())
 }
 
@@ -135,6 +1139,52 @@ mod test {
         Cli::command().debug_assert()
     }
 
+    #[test]
+    fn test_merge_labels() {
+        let cli: Cli = Parser::parse_from(vec![
+            "markdown-test-report",
+            "merge",
+            "--label",
+            "unit=unit.json",
+            "--label",
+            "it=integration.json",
+            "-o",
+            "report.md",
+        ]);
+        assert_eq!(cli.output.as_deref(), Some("report.md"));
+        match cli.command {
+            Some(Command::Merge { labels }) => assert_eq!(
+                labels,
+                vec![
+                    ("unit".to_string(), PathBuf::from("unit.json")),
+                    ("it".to_string(), PathBuf::from("integration.json"))
+                ]
+            ),
+            command => panic!("unexpected command: {:?}", command),
+        }
+
+        assert!(parse_label("unit").is_err());
+        assert!(parse_label("=unit.json").is_err());
+    }
+
+    #[test]
+    fn test_cargo_subcommand() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            strip_cargo_subcommand(args(&["cargo-test-report", "test-report", "in.json"]), true),
+            args(&["cargo-test-report", "in.json"])
+        );
+        assert_eq!(
+            strip_cargo_subcommand(args(&["markdown-test-report", "test-report"]), false),
+            args(&["markdown-test-report", "test-report"])
+        );
+        assert_eq!(
+            strip_cargo_subcommand(args(&["markdown-test-report", "in.json"]), true),
+            args(&["markdown-test-report", "in.json"])
+        );
+    }
+
     #[test]
     fn test_git_not_present() {
         let cli: Cli = Parser::parse_from(vec!["markdown-test-report"]);
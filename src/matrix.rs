@@ -0,0 +1,154 @@
+use crate::http::{self, Secret};
+use crate::model;
+use crate::processor::{Notification, Notifier};
+use askama_escape::{escape, Html};
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The maximum number of failed tests listed in a message.
+const MAX_FAILURES: usize = 10;
+
+/// Posts the summary to a Matrix room.
+#[derive(Debug)]
+pub struct MatrixRoom {
+    homeserver: String,
+    room: String,
+    token: Secret,
+}
+
+impl MatrixRoom {
+    /// Create for the ID of a room, like `!abc:example.org`.
+    pub fn new(homeserver: &str, room: &str, token: Secret) -> anyhow::Result<Self> {
+        if !room.starts_with('!') {
+            anyhow::bail!(
+                "Invalid Matrix room '{}', expected the ID of a room, like '!abc:example.org'",
+                room
+            );
+        }
+        Ok(Self {
+            homeserver: homeserver.trim_end_matches('/').to_string(),
+            room: room.to_string(),
+            token,
+        })
+    }
+}
+
+/// The failed tests, and the title of a message
+fn message(report: &model::Report) -> (String, Vec<&str>) {
+    let mut title = format!(
+        "{} Test results",
+        match report.outcome {
+            "failed" => "❌",
+            "incomplete" => "⚠️",
+            _ => "✅",
+        }
+    );
+    if !report.labels.is_empty() {
+        title.push_str(&format!(" ({})", report.labels.join(", ")));
+    }
+    title.push_str(&format!(
+        ": {} passed, {} failed, {} ignored",
+        report.count("ok"),
+        report.count("failed"),
+        report.count("ignored")
+    ));
+
+    let failed = report
+        .tests()
+        .filter(|test| test.outcome == "failed")
+        .map(|test| test.name.as_str())
+        .collect();
+    (title, failed)
+}
+
+/// The plain text and the HTML body of a message
+fn bodies(report: &model::Report) -> (String, String) {
+    let (title, failed) = message(report);
+    let more = failed.len().saturating_sub(MAX_FAILURES);
+
+    let mut plain = title.clone();
+    let mut html = format!("<p><strong>{}</strong></p>", escape(&title, Html));
+    if !failed.is_empty() {
+        html.push_str("<ul>");
+        for name in failed.iter().take(MAX_FAILURES) {
+            plain.push_str(&format!("\n* {}", name));
+            html.push_str(&format!("<li><code>{}</code></li>", escape(name, Html)));
+        }
+        html.push_str("</ul>");
+    }
+    if more > 0 {
+        plain.push_str(&format!("\n… and {} more", more));
+        html.push_str(&format!("<p>… and {} more</p>", more));
+    }
+    (plain, html)
+}
+
+impl Notifier for MatrixRoom {
+    fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+        let (body, formatted_body) = bodies(notification.report);
+        // the transaction ID only needs to be unique for the access token
+        let txn = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}-{}",
+            self.homeserver,
+            http::encode(&self.room),
+            txn,
+            std::process::id()
+        );
+        http::send(
+            "PUT",
+            &url,
+            &[("Authorization", format!("Bearer {}", self.token.0))],
+            &json!({
+                "msgtype": "m.notice",
+                "body": body,
+                "format": "org.matrix.custom.html",
+                "formatted_body": formatted_body,
+            }),
+        )?;
+        log::info!("Posted the summary to {}", self.room);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bodies() {
+        let test = |name: String, outcome| model::Test {
+            name,
+            outcome,
+            duration: Some(0.5),
+            label: None,
+            message: None,
+            output: None,
+        };
+        let mut tests = vec![test("a".into(), "ok")];
+        tests.extend((0..12).map(|idx| test(format!("tests::<{}>", idx), "failed")));
+        let report = model::Report {
+            version: model::VERSION,
+            outcome: "failed",
+            labels: vec!["linux".into()],
+            partial: false,
+            runs: vec![model::Run {
+                name: None,
+                summary: None,
+                suites: vec![],
+                tests,
+            }],
+        };
+
+        let (plain, html) = bodies(&report);
+        assert!(plain.starts_with(
+            "❌ Test results (linux): 1 passed, 12 failed, 0 ignored\n* tests::<0>\n"
+        ));
+        assert!(plain.ends_with("\n* tests::<9>\n… and 2 more"));
+        assert!(html.contains("<li><code>tests::&lt;0&gt;</code></li>"));
+
+        assert!(
+            MatrixRoom::new("https://matrix.org", "#room:matrix.org", Secret("t".into())).is_err()
+        );
+    }
+}
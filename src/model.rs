@@ -0,0 +1,315 @@
+use crate::doctest::DocTest;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The version of the report model, increased on incompatible changes.
+pub const VERSION: u32 = 1;
+
+/// The report, as rendered by `--format json`.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub version: u32,
+    /// The overall outcome of all runs
+    pub outcome: &'static str,
+    pub labels: Vec<String>,
+    /// If processing the input was interrupted.
+    pub partial: bool,
+    pub runs: Vec<Run>,
+}
+
+/// A test run, or the result of merging runs.
+#[derive(Debug, Serialize)]
+pub struct Run {
+    /// The name of the run, when there are several.
+    pub name: Option<String>,
+    pub summary: Option<Summary>,
+    pub suites: Vec<Suite>,
+    pub tests: Vec<Test>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub outcome: &'static str,
+    /// The number of announced tests, if known.
+    pub total: Option<u64>,
+    pub passed: u64,
+    pub failed: u64,
+    pub ignored: u64,
+    pub filtered_out: u64,
+    /// The duration in seconds.
+    pub duration: f64,
+    /// The summary was derived from the test results, as not all suites finished.
+    pub incomplete: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Suite {
+    /// The target reported by cargo, like `unittests src/lib.rs`.
+    pub target: Option<String>,
+    pub kind: &'static str,
+    pub label: Option<String>,
+    pub summary: Summary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Test {
+    pub name: String,
+    pub outcome: &'static str,
+    /// The duration in seconds, unknown for ignored tests.
+    pub duration: Option<f64>,
+    pub label: Option<String>,
+    /// The reason of the failure, or why the test was ignored.
+    pub message: Option<String>,
+    /// The captured output of a failed test.
+    pub output: Option<String>,
+}
+
+impl Report {
+    /// The tests of all runs
+    pub fn tests(&self) -> impl Iterator<Item = &Test> {
+        self.runs.iter().flat_map(|run| &run.tests)
+    }
+
+    /// The number of tests with an outcome, over all runs
+    pub fn count(&self, outcome: &str) -> usize {
+        self.tests().filter(|test| test.outcome == outcome).count()
+    }
+}
+
+/// A position in the sources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: String,
+    pub line: u32,
+}
+
+impl Test {
+    /// Where the test is located, or failed, as far as it is known.
+    ///
+    /// Doc-tests know their location from their name, other tests from the location of a panic in
+    /// their output.
+    pub fn location(&self) -> Option<Location> {
+        if let Some(doc_test) = DocTest::parse(&self.name) {
+            return Some(Location {
+                path: doc_test.file.to_string(),
+                line: doc_test.line,
+            });
+        }
+
+        self.output.as_deref()?.lines().find_map(panic_location)
+    }
+}
+
+/// Parse the location of a panic, like `thread 'a' panicked at src/lib.rs:10:5:`, or the older
+/// `thread 'a' panicked at 'message', src/lib.rs:10:5`.
+fn panic_location(line: &str) -> Option<Location> {
+    let (_, rest) = line.split_once("panicked at ")?;
+    let rest = match rest.strip_prefix('\'') {
+        Some(message) => message.rsplit_once("', ")?.1,
+        None => rest,
+    };
+    let location = rest.split_whitespace().next()?.trim_end_matches(':');
+    let mut parts = location.rsplitn(3, ':');
+    let (Some(_column), Some(line), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+        return None;
+    };
+    Some(Location {
+        path: path.to_string(),
+        line: line.parse().ok()?,
+    })
+}
+
+/// The JSON Schema of the [`Report`].
+pub fn schema() -> Value {
+    let nullable = |kind: &str| json!({ "type": [kind, "null"] });
+    let count = json!({ "type": "integer", "minimum": 0 });
+    let outcome = json!({ "enum": ["ok", "incomplete", "failed"] });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": "https://github.com/ctron/markdown-test-report/report.schema.json",
+        "title": "markdown-test-report",
+        "type": "object",
+        "required": ["version", "outcome", "labels", "partial", "runs"],
+        "properties": {
+            "version": { "const": VERSION },
+            "outcome": outcome,
+            "labels": { "type": "array", "items": { "type": "string" } },
+            "partial": { "type": "boolean" },
+            "runs": { "type": "array", "items": { "$ref": "#/$defs/run" } },
+        },
+        "$defs": {
+            "run": {
+                "type": "object",
+                "required": ["name", "summary", "suites", "tests"],
+                "properties": {
+                    "name": nullable("string"),
+                    "summary": {
+                        "oneOf": [{ "$ref": "#/$defs/summary" }, { "type": "null" }]
+                    },
+                    "suites": { "type": "array", "items": { "$ref": "#/$defs/suite" } },
+                    "tests": { "type": "array", "items": { "$ref": "#/$defs/test" } },
+                },
+            },
+            "summary": {
+                "type": "object",
+                "required": [
+                    "outcome", "total", "passed", "failed", "ignored", "filtered_out", "duration",
+                    "incomplete"
+                ],
+                "properties": {
+                    "outcome": outcome,
+                    "total": { "type": ["integer", "null"], "minimum": 0 },
+                    "passed": count,
+                    "failed": count,
+                    "ignored": count,
+                    "filtered_out": count,
+                    "duration": { "type": "number", "minimum": 0 },
+                    "incomplete": { "type": "boolean" },
+                },
+            },
+            "suite": {
+                "type": "object",
+                "required": ["target", "kind", "label", "summary"],
+                "properties": {
+                    "target": nullable("string"),
+                    "kind": { "enum": ["unit", "integration", "doc"] },
+                    "label": nullable("string"),
+                    "summary": { "$ref": "#/$defs/summary" },
+                },
+            },
+            "test": {
+                "type": "object",
+                "required": ["name", "outcome", "duration", "label", "message", "output"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "outcome": { "enum": ["ok", "failed", "ignored"] },
+                    "duration": { "type": ["number", "null"], "minimum": 0 },
+                    "label": nullable("string"),
+                    "message": nullable("string"),
+                    "output": nullable("string"),
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Check that all fields of the value are described by the schema, and all required ones exist
+    fn check(value: &Value, schema: &Value, defs: &Value) {
+        let schema = match schema["$ref"].as_str() {
+            Some(reference) => &defs[reference.trim_start_matches("#/$defs/")],
+            None => schema,
+        };
+        if let Some(object) = value.as_object() {
+            for (key, value) in object {
+                let property = &schema["properties"][key];
+                assert!(!property.is_null(), "{} is missing in the schema", key);
+                match property["oneOf"].as_array() {
+                    Some(variants) if !value.is_null() => check(value, &variants[0], defs),
+                    _ => check(value, property, defs),
+                }
+            }
+            for key in schema["required"].as_array().into_iter().flatten() {
+                assert!(
+                    object.contains_key(key.as_str().unwrap()),
+                    "{} is missing",
+                    key
+                );
+            }
+        }
+        if let Some(items) = value.as_array() {
+            for item in items {
+                check(item, &schema["items"], defs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let summary = Summary {
+            outcome: "failed",
+            total: Some(1),
+            passed: 0,
+            failed: 1,
+            ignored: 0,
+            filtered_out: 0,
+            duration: 1.0,
+            incomplete: false,
+        };
+        let report = Report {
+            version: VERSION,
+            outcome: "failed",
+            labels: vec!["linux".into()],
+            partial: false,
+            runs: vec![Run {
+                name: None,
+                summary: Some(Summary { ..summary }),
+                suites: vec![Suite {
+                    target: Some("unittests src/lib.rs".into()),
+                    kind: "unit",
+                    label: None,
+                    summary,
+                }],
+                tests: vec![Test {
+                    name: "tests::a".into(),
+                    outcome: "failed",
+                    duration: Some(1.0),
+                    label: None,
+                    message: None,
+                    output: Some("boom".into()),
+                }],
+            }],
+        };
+
+        let schema = schema();
+        check(
+            &serde_json::to_value(&report).unwrap(),
+            &schema,
+            &schema["$defs"],
+        );
+    }
+
+    #[test]
+    fn test_location() {
+        let test = |name: &str, output: Option<&str>| Test {
+            name: name.into(),
+            outcome: "failed",
+            duration: None,
+            label: None,
+            message: None,
+            output: output.map(String::from),
+        };
+        let location = |path: &str, line| {
+            Some(Location {
+                path: path.into(),
+                line,
+            })
+        };
+
+        assert_eq!(
+            test("src/lib.rs - foo (line 42)", None).location(),
+            location("src/lib.rs", 42)
+        );
+        assert_eq!(
+            test(
+                "a",
+                Some("\nthread 'a' panicked at src/lib.rs:10:5:\nassertion failed")
+            )
+            .location(),
+            location("src/lib.rs", 10)
+        );
+        assert_eq!(
+            test(
+                "a",
+                Some("thread 'a' panicked at 'left: 1, right: 2', tests/it.rs:3:9")
+            )
+            .location(),
+            location("tests/it.rs", 3)
+        );
+        assert_eq!(test("a", Some("boom")).location(), None);
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::bail;
+use flate2::write::GzEncoder;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// Compression of the output.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ValueEnum)]
+pub enum Compression {
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// The file extension of the compression format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            #[cfg(feature = "zstd")]
+            Self::Zstd => "zst",
+        }
+    }
+}
+
+/// Protect an existing output file from being overwritten, unless forced or backed up.
+pub fn protect(output: &Path, force: bool, backup: bool) -> anyhow::Result<()> {
+    // devices, like /dev/null, are fine to write to
+    if !output.is_file() || force {
+        return Ok(());
+    }
+
+    if backup {
+        let mut name = output.as_os_str().to_owned();
+        name.push(".bak");
+        log::info!(
+            "Keeping {} as {}",
+            output.display(),
+            Path::new(&name).display()
+        );
+        fs::rename(output, &name)?;
+        return Ok(());
+    }
+
+    bail!(
+        "{} already exists, use --force to overwrite it or --backup to keep it",
+        output.display()
+    )
+}
+
+/// The values of the placeholders in the name of the output file.
+#[derive(Debug, Default)]
+pub struct Placeholders {
+    pub stem: String,
+    pub date: String,
+    pub sha: String,
+    pub labels: String,
+}
+
+impl Placeholders {
+    /// Replace the placeholders of a file name template, except `{outcome}`, which is only known
+    /// after the report was rendered.
+    pub fn render(&self, template: &str) -> String {
+        template
+            .replace("{stem}", &self.stem)
+            .replace("{date}", &self.date)
+            .replace("{sha}", &self.sha)
+            .replace("{labels}", &self.labels)
+    }
+}
+
+/// Open the output, `-` being stdout, optionally compressing it.
+///
+/// The compressed stream gets finished when the writer is dropped.
+pub fn open(
+    output: &str,
+    compression: Option<Compression>,
+) -> anyhow::Result<Box<dyn Write + Send>> {
+    let write: Box<dyn Write + Send> = match output {
+        "-" => Box::new(std::io::stdout()),
+        output => Box::new(File::create(output)?),
+    };
+
+    compress(write, compression)
+}
+
+/// Open the output for appending, `-` being stdout, creating the file if required.
+pub fn append(output: &str) -> anyhow::Result<Box<dyn Write + Send>> {
+    Ok(match output {
+        "-" => Box::new(std::io::stdout()),
+        output => Box::new(OpenOptions::new().append(true).create(true).open(output)?),
+    })
+}
+
+/// Optionally compress a writer.
+pub fn compress(
+    write: Box<dyn Write + Send>,
+    compression: Option<Compression>,
+) -> anyhow::Result<Box<dyn Write + Send>> {
+    Ok(match compression {
+        None => write,
+        Some(Compression::Gzip) => Box::new(GzEncoder::new(write, Default::default())),
+        #[cfg(feature = "zstd")]
+        Some(Compression::Zstd) => Box::new(zstd::Encoder::new(write, 0)?.auto_finish()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        let placeholders = Placeholders {
+            stem: "test-output".into(),
+            date: "2024-01-31".into(),
+            sha: "abcdef0".into(),
+            labels: "linux".into(),
+        };
+        assert_eq!(
+            placeholders.render("{date}-{labels}-{sha}-{outcome}.md"),
+            "2024-01-31-linux-abcdef0-{outcome}.md"
+        );
+        assert_eq!(placeholders.render("{stem}.md"), "test-output.md");
+    }
+
+    #[test]
+    fn test_protect() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("report.md");
+        protect(&output, false, false).unwrap();
+
+        fs::write(&output, "edited").unwrap();
+        assert!(protect(&output, false, false).is_err());
+        protect(&output, true, false).unwrap();
+
+        protect(&output, false, true).unwrap();
+        assert!(!output.exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("report.md.bak")).unwrap(),
+            "edited"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_compressed() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let path = dir
+                .path()
+                .join(format!("report.md.{}", compression.extension()));
+            {
+                let mut write = open(path.to_str().unwrap(), Some(compression)).unwrap();
+                write.write_all(b"# Report").unwrap();
+            }
+
+            let mut content = String::new();
+            crate::input::open(&path)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            assert_eq!(content, "# Report");
+        }
+    }
+}
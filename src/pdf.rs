@@ -0,0 +1,104 @@
+use std::{ffi::OsString, io::Write, path::Path, process::Command};
+
+/// The default command converting the HTML report into a PDF document.
+pub const DEFAULT_CONVERTER: &str = "weasyprint";
+
+/// Browsers, which convert using their headless mode.
+const BROWSERS: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "chrome",
+    "google-chrome",
+    "google-chrome-stable",
+];
+
+/// The arguments of a converter, converting the input into the output
+fn arguments(converter: &str, input: &Path, output: &Path) -> Vec<OsString> {
+    let name = Path::new(converter)
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match BROWSERS.contains(&name.as_str()) {
+        true => {
+            let mut print = OsString::from("--print-to-pdf=");
+            print.push(output);
+            vec![
+                "--headless".into(),
+                "--no-pdf-header-footer".into(),
+                print,
+                input.into(),
+            ]
+        }
+        false => vec![input.into(), output.into()],
+    }
+}
+
+/// Convert an HTML document into a PDF document, using an external converter.
+///
+/// Browsers, like `chromium`, print the document in their headless mode. Any other converter is
+/// called with the paths of the HTML input and of the PDF output, like `weasyprint` expects them.
+pub fn convert(converter: &str, html: &[u8], write: &mut dyn Write) -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("report.html");
+    let output = dir.path().join("report.pdf");
+    std::fs::write(&input, html)?;
+
+    let result = Command::new(converter)
+        .args(arguments(converter, &input, &output))
+        .output()
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Unable to run {}, which is required for --format pdf: {}",
+                converter,
+                err
+            )
+        })?;
+    if !result.status.success() {
+        anyhow::bail!(
+            "Failed to convert the report into a PDF document: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    write.write_all(&std::fs::read(&output)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_arguments() {
+        let (input, output) = (Path::new("in.html"), Path::new("out.pdf"));
+        assert_eq!(
+            arguments("weasyprint", input, output),
+            ["in.html", "out.pdf"]
+        );
+        assert_eq!(
+            arguments("/usr/bin/chromium", input, output),
+            [
+                "--headless",
+                "--no-pdf-header-footer",
+                "--print-to-pdf=out.pdf",
+                "in.html"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        // copying the input is good enough to check how the converter is called
+        let mut out = vec![];
+        convert("cp", b"<html></html>", &mut out).unwrap();
+        assert_eq!(out, b"<html></html>");
+
+        let err = convert("false", b"", &mut vec![]).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to convert"));
+
+        let err = convert("no-such-converter", b"", &mut vec![]).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Unable to run no-such-converter"));
+    }
+}
@@ -1,45 +1,395 @@
-use crate::event::{suite, test, Record};
+use crate::budget::{Budget, Scope};
+use crate::doctest::DocTest;
+use crate::error::Error;
+use crate::event::{self, suite, test, Kind, ParseError, Parser, Record};
+use crate::html::{self, Theme};
+use crate::invocation::Invocation;
+use crate::locale::{CustomLabels, Labels, Lang};
+use crate::model;
+use crate::output;
+use crate::pdf;
+use crate::raw;
+use crate::redact::Redactor;
+use crate::rename::Rename;
+use crate::spill::{Spill, Spilled};
+#[cfg(feature = "store")]
+use crate::store::Store;
+use crate::timeline;
+use crate::verify;
 use askama_escape::{escape, Html};
-use chrono::Utc;
+use chrono::{DateTime, FixedOffset, Utc};
+use regex::Regex;
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
 use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{Debug, Display, Formatter},
     io::Write,
+    sync::Arc,
     time::Duration,
 };
+// std's panics in the browser
+#[cfg(target_family = "wasm")]
+use web_time::Instant;
 
-pub trait Addon: Debug {
+/// Where an addon is rendered in the report.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Position {
+    /// Before the summary table
+    Header,
+    /// After the summary table, before the link to the job
+    AfterSummary,
+    /// After the summary, before the index of the tests
+    BeforeIndex,
+    /// After the details of the tests
+    Footer,
+}
+
+pub trait Addon: Debug + Send {
     fn render(&self, write: &mut dyn Write) -> anyhow::Result<()>;
+
+    /// Where the addon is rendered, unless configured otherwise.
+    fn position(&self) -> Position {
+        Position::AfterSummary
+    }
+}
+
+/// The result of a test, as handed to an [`Observer`].
+#[derive(Debug, Clone, Copy)]
+pub struct TestResult<'a> {
+    /// The event of the test, which isn't `started`.
+    pub event: &'a test::Event,
+    /// The label of the input the test was recorded from.
+    pub label: Option<&'a str>,
+}
+
+/// The result of a finished suite, as handed to an [`Observer`].
+#[derive(Debug, Clone, Copy)]
+pub struct SuiteResult<'a> {
+    pub outcome: Outcome,
+    pub passed: u64,
+    pub failed: u64,
+    pub ignored: u64,
+    pub filtered_out: u64,
+    pub exec_time: Duration,
+    /// The label of the input the suite was recorded from.
+    pub label: Option<&'a str>,
+}
+
+/// Gets called while the records are processed, like for collecting metrics in the same pass.
+pub trait Observer: Debug + Send {
+    /// A test finished, or was ignored.
+    fn on_test(&mut self, _test: &TestResult) {}
+
+    /// A suite finished.
+    fn on_suite(&mut self, _suite: &SuiteResult) {}
+}
+
+/// The results, as handed to a [`Notifier`].
+pub struct Notification<'a> {
+    /// The results, rendered as a pull request comment.
+    pub comment: &'a str,
+    pub report: &'a model::Report,
+}
+
+/// Sends the results to another system, after the report was rendered.
+pub trait Notifier: Debug + Send {
+    fn notify(&self, notification: &Notification) -> anyhow::Result<()>;
+}
+
+/// The output of the processor, which can temporarily be captured instead, like for rendering a
+/// comment besides the report.
+struct Capture<W> {
+    write: W,
+    captured: Option<Vec<u8>>,
+}
+
+impl<W: Write> Write for Capture<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.captured {
+            Some(captured) => captured.write(buf),
+            None => self.write.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.captured {
+            Some(_) => Ok(()),
+            None => self.write.flush(),
+        }
+    }
+}
+
+/// How to deal with multiple test runs in the same input.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum RunMode {
+    /// Merge all runs, the last result of a test wins
+    Merge,
+    /// Render each run as its own section
+    #[default]
+    Separate,
+    /// Only render the latest run
+    Latest,
+}
+
+/// The markdown dialect of the platform the report is shown on.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum Flavor {
+    /// GitHub, and other platforms following it
+    #[default]
+    Github,
+    /// Azure DevOps wikis and pull requests, which don't render `<details>` and have their own anchors
+    AzureDevops,
+}
+
+/// The format of the report.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    /// The full markdown report
+    #[default]
+    Markdown,
+    /// A short report, sized for pull request comments
+    Comment,
+    /// A JSON document of the results, described by the `schema` subcommand
+    Json,
+    /// A single HTML file, which can be searched and filtered offline
+    Html,
+    /// A PDF document, converted from the HTML report by an external command
+    Pdf,
 }
 
+/// The blocks of a sparkline, from the lowest to the highest value.
+#[cfg(feature = "store")]
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The link to the CI job, without a template: the run of GitHub Actions.
+const GITHUB_JOB_LINK: &str = "https://github.com/{GITHUB_REPOSITORY}/actions/runs/{GITHUB_RUN_ID}";
+
+/// Maximum number of failed tests shown in a comment.
+const MAX_COMMENT_FAILURES: usize = 20;
+/// Maximum number of output lines per failed test shown in a comment.
+const MAX_COMMENT_OUTPUT_LINES: usize = 50;
+
 #[derive(Debug)]
 pub struct ProcessOptions {
     pub disable_front_matter: bool,
-    pub addons: Vec<Box<dyn Addon>>,
+    /// The addons, with where they are rendered.
+    pub addons: Vec<(Position, Box<dyn Addon>)>,
     pub summary: bool,
+    /// Also write the summary to this output, `-` being stdout, like for the summary of a CI job.
+    pub summary_output: Option<String>,
+    /// Append the summary to its output, instead of overwriting it.
+    pub summary_append: bool,
     pub precise: bool,
+    /// Render when each test started, and the tests in the order they ran, if the input has them.
+    pub timestamps: bool,
+    /// External events, ordered by their time, rendered along the tests running at the time.
+    pub timeline: Vec<timeline::Event>,
+    pub runs: RunMode,
+    pub spill: bool,
+    /// Embed the processed records into the report.
+    pub embed_records: bool,
+    pub format: Format,
+    pub flavor: Flavor,
+    /// Link to the full report, used by the comment format.
+    pub report_url: Option<String>,
+    /// The link to the CI job, with environment variables like `{BUILD_ID}`, instead of the link
+    /// to the run of GitHub Actions.
+    pub job_link_template: Option<String>,
+    /// Labels of the report, like the environment the tests ran against.
+    pub labels: Vec<String>,
+    /// Aggregate the labeled inputs as platforms of a test matrix.
+    pub aggregate: bool,
+    /// The base URL of the source files, used to link doc-tests.
+    pub source_url: Option<String>,
+    /// Rules rewriting the displayed names of tests.
+    pub renames: Vec<Rename>,
+    /// Truncate names in tables exceeding this number of characters.
+    pub max_name_length: Option<usize>,
+    /// The language of the fixed labels.
+    pub lang: Lang,
+    /// Labels replacing the fixed ones, like the heading of the index.
+    pub custom_labels: CustomLabels,
+    /// The color theme of the HTML report.
+    pub theme: Theme,
+    /// The command converting the HTML report into a PDF document.
+    pub pdf_converter: String,
+    /// Limits of the duration of the tests.
+    pub budgets: Vec<Budget>,
+    /// The database to store the results in.
+    #[cfg(feature = "store")]
+    pub store: Option<Store>,
+    /// The number of runs shown by the trend of the store, including the current one.
+    pub trend_runs: usize,
+    /// Show the change of each test's duration, compared to its average in the store.
+    pub duration_delta: bool,
+    /// Receivers of the results, besides the report.
+    pub notifiers: Vec<Box<dyn Notifier>>,
+    /// Get called for each result, while the records are processed.
+    pub observers: Vec<Box<dyn Observer>>,
+    /// Replaces credentials in the output of tests.
+    pub redactor: Redactor,
+    /// End the report with the tool, the time, and the duration of creating it.
+    pub footer: bool,
+    /// Check the generated markdown, failing on problems like broken links.
+    pub verify: bool,
+    /// Fail on lines which are ignored otherwise, like unknown records.
+    pub strict: bool,
+}
+
+/// The defaults of the command line tool.
+impl Default for ProcessOptions {
+    fn default() -> Self {
+        Self {
+            disable_front_matter: false,
+            addons: vec![],
+            summary: false,
+            summary_output: None,
+            summary_append: false,
+            precise: false,
+            timestamps: false,
+            timeline: vec![],
+            runs: RunMode::default(),
+            spill: false,
+            embed_records: false,
+            format: Format::default(),
+            flavor: Flavor::default(),
+            report_url: None,
+            job_link_template: None,
+            labels: vec![],
+            aggregate: false,
+            source_url: None,
+            renames: vec![],
+            max_name_length: None,
+            lang: Lang::default(),
+            custom_labels: CustomLabels::default(),
+            theme: Theme::default(),
+            pdf_converter: pdf::DEFAULT_CONVERTER.to_string(),
+            budgets: vec![],
+            #[cfg(feature = "store")]
+            store: None,
+            trend_runs: 20,
+            duration_delta: false,
+            notifiers: vec![],
+            observers: vec![],
+            redactor: Redactor::default(),
+            footer: true,
+            verify: false,
+            strict: false,
+        }
+    }
 }
 
 pub struct Processor<W>
 where
     W: Write,
 {
-    write: W,
+    write: Capture<W>,
     options: ProcessOptions,
-    tests: Vec<test::Event>,
-    test_count: Option<u64>,
-    summary: Option<Summary>,
+    /// The recorded suites, which may consist of several runs.
+    run: Run,
+    spill: Option<Spill>,
+    parser: Parser,
+    /// The processed lines, in case they need to be embedded.
+    records: Vec<String>,
+    /// The label of the input currently being processed.
+    label: Option<Arc<str>>,
+    /// The invocation announced for the next suite.
+    invocation: Option<Invocation>,
+    /// The number of the line currently being processed, of the current input.
+    line: u64,
+    ignored: Ignored,
+    /// If processing was interrupted, and the report is partial.
+    interrupted: bool,
+    /// If the report was already rendered.
+    finished: bool,
+    /// If the input is streamed, while the tests are running.
+    streamed: bool,
+    /// When the first and the last record of a streamed input were received.
+    received: Option<(Instant, Instant)>,
+    /// The average duration of the tests in previous runs, if the change is shown.
+    averages: Option<HashMap<String, f64>>,
+    /// When processing started.
+    started: Instant,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Outcome {
+/// Statistics about the lines which were ignored.
+#[derive(Debug, Default)]
+struct Ignored {
+    /// All lines, which were processed.
+    lines: u64,
+    /// Lines which aren't JSON, like the output of cargo itself.
+    invalid: u64,
+    /// Lines which are JSON, but not a record.
+    unknown: u64,
+    /// Records of an unknown type or event, like of a newer test harness, by their kind.
+    kinds: BTreeMap<Kind, u64>,
+}
+
+impl Ignored {
+    fn warn(&self) {
+        let records = self.kinds.values().sum::<u64>();
+        if self.invalid + self.unknown + records == 0 {
+            return;
+        }
+        log::warn!(
+            "Ignored {} of {} lines: {} not JSON, {} not a record, {} unknown records{} (use -vv for details)",
+            self.invalid + self.unknown + records,
+            self.lines,
+            self.invalid,
+            self.unknown,
+            records,
+            match records {
+                0 => String::new(),
+                _ => format!(" ({})", self.format_kinds(|kind| kind.to_string())),
+            }
+        );
+    }
+
+    /// The counts of the unknown kinds of records, like `bench: 2, test/timeout: 1`
+    fn format_kinds(&self, format: impl Fn(&Kind) -> String) -> String {
+        self.kinds
+            .iter()
+            .map(|(kind, count)| format!("{}: {}", format(kind), count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The outcome of a test run, ordered by severity.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Outcome {
     Ok,
+    Incomplete,
     Failed,
 }
 
+/// The result of rendering a report.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Report {
+    pub outcome: Outcome,
+    /// If any of the budgets was exceeded.
+    pub over_budget: bool,
+}
+
+impl Outcome {
+    /// The name of the outcome, usable in file names
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Incomplete => "incomplete",
+            Self::Failed => "failed",
+        }
+    }
+}
+
 impl Display for Outcome {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Ok => f.write_str("✅"),
+            Self::Incomplete => f.write_str("⚠️"),
             Self::Failed => f.write_str("❌"),
         }
     }
@@ -53,323 +403,2868 @@ struct Summary {
     ignored: u64,
     filtered_out: u64,
     exec_time: Duration,
+    /// The summary was derived from the test events, as the suite didn't finish.
+    incomplete: bool,
 }
 
-impl<W> Processor<W>
-where
-    W: Write,
-{
-    pub fn new(write: W, options: ProcessOptions) -> Self {
-        Self {
-            write,
-            options,
-            tests: Vec::new(),
-            test_count: None,
-            summary: None,
-        }
+impl Summary {
+    /// Add the values of another summary to this one
+    fn add(&mut self, other: &Summary) {
+        self.outcome = self.outcome.max(other.outcome);
+        self.incomplete |= other.incomplete;
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.ignored += other.ignored;
+        self.filtered_out += other.filtered_out;
+        self.exec_time += other.exec_time;
     }
 
-    fn write_header(&mut self, summary: &Summary) -> anyhow::Result<()> {
-        let run_id = std::env::var("GITHUB_RUN_ID").ok();
-        let repo = std::env::var("GITHUB_REPOSITORY").ok();
+    fn to_model(self, total: Option<u64>) -> model::Summary {
+        model::Summary {
+            outcome: self.outcome.name(),
+            total,
+            passed: self.passed,
+            failed: self.failed,
+            ignored: self.ignored,
+            filtered_out: self.filtered_out,
+            duration: self.exec_time.as_secs_f64(),
+            incomplete: self.incomplete,
+        }
+    }
+}
 
-        let link = match (&repo, &run_id) {
-            (Some(repo), Some(id)) => Some(format!(
-                "https://github.com/{repo}/actions/runs/{id}",
-                repo = repo,
-                id = id
-            )),
-            _ => None,
-        };
+/// The kind of tests a suite consists of.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub enum SuiteKind {
+    #[default]
+    Unit,
+    Integration,
+    Doc,
+}
 
-        let date = Utc::now();
+impl SuiteKind {
+    /// Classify a target, as reported by cargo
+    fn from_target(target: &str) -> Option<Self> {
+        if target.starts_with("unittests ") {
+            Some(Self::Unit)
+        } else if target.starts_with("doc-tests ") {
+            Some(Self::Doc)
+        } else if target.starts_with("tests/") || target.starts_with("tests\\") {
+            Some(Self::Integration)
+        } else {
+            None
+        }
+    }
 
-        if !self.options.disable_front_matter {
-            let title = format!(
-                "{} Test Result {}",
-                summary.outcome,
-                date.format("%Y-%m-%d %H:%M UTC")
-            );
+    /// The name of the kind, as used by the JSON report
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Unit => "unit",
+            Self::Integration => "integration",
+            Self::Doc => "doc",
+        }
+    }
 
-            writeln!(self.write, "---")?;
-            writeln!(self.write, "title: \"{}\"", title)?;
-            writeln!(self.write, "date: {}", date.to_rfc3339())?;
-            writeln!(self.write, "categories: test-report")?;
-            writeln!(self.write, "excerpt_separator: <!--more-->")?;
-            writeln!(self.write, "---")?;
-            writeln!(self.write)?;
+    fn title(&self, labels: &Labels) -> &'static str {
+        match self {
+            Self::Unit => labels.unit_tests,
+            Self::Integration => labels.integration_tests,
+            Self::Doc => labels.doc_tests,
         }
+    }
+}
 
-        let total = self
-            .test_count
-            .map(|total| total.to_string())
-            .unwrap_or_else(|| "*unknown*".into());
+/// A single suite, started by a suite "started" event.
+#[derive(Debug, Default)]
+struct Suite {
+    label: Option<Arc<str>>,
+    /// How the suite was run, if known.
+    invocation: Option<Invocation>,
+    test_count: Option<u64>,
+    tests: Vec<Recorded>,
+    summary: Option<Summary>,
+    kind: SuiteKind,
+}
 
-        writeln!(
-            self.write,
-            r#"
-| | Total | Passed | Failed | Ignored | Filtered | Duration |
-| --- | ----- | -------| ------ | ------- | -------- | -------- |
-| {} | {} | {} | {} | {} | {} | {} |
-"#,
-            summary.outcome,
-            total,
-            summary.passed,
-            summary.failed,
-            summary.ignored,
-            summary.filtered_out,
-            self.format_duration(&summary.exec_time)
-        )?;
-        writeln!(self.write)?;
+/// A recorded test event.
+#[derive(Debug)]
+struct Recorded {
+    event: test::Event,
+    /// The label of the input the test was recorded from.
+    label: Option<Arc<str>>,
+    /// The output of the test, if it was spilled to disk.
+    spilled: Option<Spilled>,
+    /// The kind of the suite the test belongs to.
+    kind: SuiteKind,
+}
 
-        for addon in &self.options.addons {
-            addon.render(&mut self.write)?;
-            writeln!(self.write)?;
+impl Suite {
+    /// Infer the kind of the suite, from the target reported by cargo, or the names of its tests.
+    ///
+    /// Without any hints, the first suite of an input is considered the unit tests, as cargo runs
+    /// them first, and all further suites the integration tests.
+    fn infer_kind(&self, first: bool) -> SuiteKind {
+        if let Some(kind) = self
+            .invocation
+            .as_ref()
+            .and_then(|invocation| invocation.target.as_deref())
+            .and_then(SuiteKind::from_target)
+        {
+            return kind;
         }
 
-        if let Some(link) = link {
-            writeln!(self.write, "**Job:** [{link}]({link})", link = link)?;
-            writeln!(self.write)?;
+        let mut names = self
+            .tests
+            .iter()
+            .filter_map(|test| finished_name(&test.event))
+            .peekable();
+        if names.peek().is_some() && names.all(|name| DocTest::parse(name).is_some()) {
+            SuiteKind::Doc
+        } else if first {
+            SuiteKind::Unit
+        } else {
+            SuiteKind::Integration
         }
-
-        Ok(())
     }
 
-    pub fn line(&mut self, line: &str) -> anyhow::Result<()> {
-        match serde_json::from_str(line) {
-            Ok(record) => self.record(record)?,
-            Err(err) => log::debug!("Ignoring line: {:?} -> {}", err, line),
+    /// The summary of the suite, derived from the test events if the suite didn't finish
+    fn summary(&self) -> Summary {
+        if let Some(summary) = self.summary {
+            return summary;
         }
 
-        Ok(())
-    }
-
-    fn record(&mut self, record: Record) -> anyhow::Result<()> {
-        log::debug!("Record: {:?}", record);
-
-        match record {
-            Record::Test(test) => {
-                self.tests.push(test);
-            }
+        let mut summary = Summary {
+            outcome: Outcome::Incomplete,
+            passed: 0,
+            failed: 0,
+            ignored: 0,
+            filtered_out: 0,
+            exec_time: Duration::ZERO,
+            incomplete: true,
+        };
 
-            Record::Suite(suite::Event::Started { test_count }) => {
-                self.record_suite_started(test_count);
-            }
-            Record::Suite(suite::Event::Ok {
-                passed,
-                failed,
-                ignored,
-                filtered_out,
-                exec_time,
-                ..
-            }) => {
-                self.record_suite_ok(passed, failed, ignored, filtered_out, exec_time);
-            }
-            Record::Suite(suite::Event::Failed {
-                passed,
-                failed,
-                ignored,
-                filtered_out,
-                exec_time,
-                ..
-            }) => {
-                self.record_suite_failed(passed, failed, ignored, filtered_out, exec_time);
+        for test in &self.tests {
+            match &test.event {
+                test::Event::Started { .. } => {}
+                test::Event::Ignored { .. } => summary.ignored += 1,
+                test::Event::Ok { exec_time, .. } => {
+                    summary.passed += 1;
+                    summary.exec_time += *exec_time;
+                }
+                test::Event::Failed { exec_time, .. } => {
+                    summary.outcome = Outcome::Failed;
+                    summary.failed += 1;
+                    summary.exec_time += *exec_time;
+                }
             }
         }
 
-        Ok(())
+        summary
     }
 
-    fn record_suite_started(&mut self, test_count: u64) {
-        self.test_count = match self.test_count {
-            Some(count) => Some(count + test_count),
-            None => Some(test_count),
-        };
+    /// What identifies the suite when it gets run again: the label, the binary, and the number of
+    /// tests, including the filtered ones.
+    fn signature(&self) -> (Option<&str>, Option<&str>, Option<&str>, Option<u64>) {
+        let invocation = self.invocation.as_ref();
+        (
+            self.label.as_deref(),
+            invocation.and_then(|invocation| invocation.target.as_deref()),
+            invocation.and_then(|invocation| invocation.binary.as_deref()),
+            self.test_count
+                .map(|count| count + self.summary().filtered_out),
+        )
     }
 
-    fn record_suite_ok(&mut self, passed: u64, failed: u64, ignored: u64, filtered_out: u64, exec_time: Duration) {
-        match &mut self.summary {
-            Some(summary) => {
-                summary.outcome = if summary.outcome == Outcome::Failed { Outcome::Failed } else { Outcome::Ok };
-                summary.passed += passed;
-                summary.failed += failed;
-                summary.ignored += ignored;
-                summary.filtered_out += filtered_out;
-                summary.exec_time += exec_time;
-            }
-            None => {
-                self.summary = Some(Summary {
-                    outcome: Outcome::Ok,
-                    passed,
-                    failed,
-                    ignored,
-                    filtered_out,
-                    exec_time,
-                });
-            }
-        }
+    /// Names of all tests which finished in this suite
+    fn finished_names(&self) -> HashSet<&str> {
+        self.tests
+            .iter()
+            .filter_map(|test| finished_name(&test.event))
+            .collect()
     }
 
-    fn record_suite_failed(&mut self, passed: u64, failed: u64, ignored: u64, filtered_out: u64, exec_time: Duration) {
-        match &mut self.summary {
-            Some(summary) => {
-                summary.outcome = Outcome::Failed;
-                summary.passed += passed;
-                summary.failed += failed;
-                summary.ignored += ignored;
-                summary.filtered_out += filtered_out;
-                summary.exec_time += exec_time;
-            }
-            None => {
-                self.summary = Some(Summary {
-                    outcome: Outcome::Failed,
-                    passed,
-                    failed,
-                    ignored,
-                    filtered_out,
-                    exec_time,
-                });
-            }
-        }
+    /// Check if the suite may be running an earlier one again, or some of its tests.
+    fn may_repeat(&self, earlier: &Suite) -> bool {
+        self.signature() == earlier.signature()
+            && self.finished_names().is_subset(&earlier.finished_names())
     }
+}
+
+/// A complete test run, consisting of one or more suites.
+#[derive(Debug, Default)]
+struct Run {
+    suites: Vec<Suite>,
+}
 
-    /// Create a name (for the index) linking to the actual test
-    fn make_linked_name(&self, name: &str) -> String {
-        format!("[{}](#{})", name, make_anchor(name))
+impl Run {
+    fn test_count(&self) -> Option<u64> {
+        self.suites
+            .iter()
+            .filter_map(|suite| suite.test_count)
+            .reduce(|a, b| a + b)
     }
 
-    /// Create the heading statement of a test
-    fn make_heading(&self, name: &str, outcome: &str) -> String {
-        format!(
-            r#"## {}<a id="{}"></a>"#,
-            self.make_heading_title(name, outcome),
-            make_anchor(name)
-        )
+    fn summary(&self) -> Option<Summary> {
+        self.suites.iter().map(Suite::summary).reduce(|mut a, b| {
+            a.add(&b);
+            a
+        })
     }
 
-    /// Create the title of a heading
-    fn make_heading_title(&self, name: &str, outcome: &str) -> String {
-        format!("{} {}", outcome, name)
+    fn invocations(&self) -> impl Iterator<Item = &Invocation> {
+        self.suites
+            .iter()
+            .filter_map(|suite| suite.invocation.as_ref())
     }
 
-    fn render_index(&mut self) -> anyhow::Result<()> {
-        writeln!(self.write, "<!--more-->")?;
+    fn tests(&self) -> impl Iterator<Item = &Recorded> {
+        self.suites.iter().flat_map(|suite| suite.tests.iter())
+    }
 
-        writeln!(self.write)?;
-        writeln!(self.write, "# Index")?;
-        writeln!(self.write)?;
-        writeln!(self.write, "| Name | Result | Duration |")?;
-        writeln!(self.write, "| ---- | ------ | -------- |")?;
+    /// Classify the suites, and the tests they contain
+    fn classify(&mut self) {
+        let mut seen = HashSet::new();
+        for suite in &mut self.suites {
+            let kind = suite.infer_kind(!seen.contains(&suite.label));
+            if kind != SuiteKind::Doc {
+                seen.insert(suite.label.clone());
+            }
+            suite.kind = kind;
+            for test in &mut suite.tests {
+                test.kind = kind;
+            }
+        }
+    }
 
-        for test in &self.tests {
-            match test {
-                test::Event::Started { .. } => {}
-                test::Event::Ok { name, exec_time } => {
-                    writeln!(
-                        self.write,
-                        "| {} | ✅ | {} | ",
-                        self.make_linked_name(name),
-                        self.format_duration(exec_time)
-                    )?;
-                }
+    /// Split the suites into the runs they consist of, like appended by a retry loop.
+    ///
+    /// Runs are only detected when the whole sequence of suites repeats. Without the binaries,
+    /// the suites of different crates may look the same, so all but the last run must also have
+    /// failed, which is why they were repeated.
+    fn split(self) -> Vec<Run> {
+        let len = self.suites.len();
+        let binaries = self
+            .invocations()
+            .any(|invocation| invocation.binary.is_some());
+        let failed = |suites: &[Suite]| {
+            suites
+                .iter()
+                .any(|suite| suite.summary().outcome == Outcome::Failed)
+        };
 
-                test::Event::Failed {
-                    name, exec_time, ..
-                } => {
-                    writeln!(
-                        self.write,
-                        "| {} | ❌ | {} | ",
-                        self.make_linked_name(name),
-                        self.format_duration(exec_time)
-                    )?;
-                }
+        let period = (1..=len / 2)
+            .filter(|period| len.is_multiple_of(*period))
+            .find(|&period| {
+                let first = &self.suites[..period];
+                self.suites[period..].chunks(period).all(|chunk| {
+                    chunk
+                        .iter()
+                        .zip(first)
+                        .all(|(suite, earlier)| suite.may_repeat(earlier))
+                }) && (binaries || self.suites[..len - period].chunks(period).all(failed))
+            });
+
+        match period {
+            _ if len == 0 => vec![],
+            Some(period) => {
+                log::info!("Detected {} test runs", len / period);
+                let mut suites = self.suites.into_iter();
+                (0..len / period)
+                    .map(|_| Run {
+                        suites: suites.by_ref().take(period).collect(),
+                    })
+                    .collect()
             }
+            None => vec![self],
         }
-
-        Ok(())
     }
+}
 
-    fn render_details(&mut self) -> anyhow::Result<()> {
-        writeln!(self.write)?;
-        writeln!(self.write)?;
-        writeln!(self.write, "# Details")?;
+/// A failure of a test, on one or more platforms.
+struct Failure {
+    name: String,
+    anchor: String,
+    platforms: Vec<String>,
+    exec_times: Vec<Duration>,
+    output: String,
+}
 
-        for test in &self.tests {
-            match test {
-                test::Event::Started { .. } => {}
-                test::Event::Ok { name, exec_time } => {
-                    writeln!(self.write)?;
-                    writeln!(self.write, "{}", self.make_heading(name, "✅"))?;
-                    writeln!(self.write)?;
-                    writeln!(
-                        self.write,
-                        "**Duration**: {}",
-                        self.format_duration(exec_time)
-                    )?;
-                }
+/// What gets rendered: a single run, or the result of merging runs.
+struct View<'a> {
+    /// The name of the view, when rendering multiple views.
+    name: String,
+    /// The prefix of anchors, required to keep them unique when rendering multiple views.
+    anchor_prefix: String,
+    /// The suffix of section titles.
+    title_suffix: String,
+    test_count: Option<u64>,
+    summary: Option<Summary>,
+    tests: Vec<&'a Recorded>,
+    invocations: Vec<&'a Invocation>,
+    suites: Vec<&'a Suite>,
+    /// The start of the anchors of the tests, by label, including the anchor prefix.
+    label_anchors: HashMap<Option<&'a str>, String>,
+}
 
-                test::Event::Failed {
-                    name,
-                    exec_time,
-                    stdout,
-                } => {
-                    writeln!(self.write)?;
-                    writeln!(self.write, "{}", self.make_heading(name, "❌"))?;
-                    writeln!(self.write)?;
-                    writeln!(
-                        self.write,
-                        "**Duration**: {}",
-                        self.format_duration(exec_time)
-                    )?;
-                    if !stdout.is_empty() {
-                        writeln!(self.write)?;
-                        writeln!(self.write, "<details>")?;
-                        writeln!(self.write)?;
+impl<'a> View<'a> {
+    /// The number of reported test results, including ignored tests
+    fn reported(&self) -> Option<u64> {
+        self.summary.map(|summary| {
+            self.tests
+                .iter()
+                .filter(|test| finished_name(&test.event).is_some())
+                .count() as u64
+                + summary.ignored
+        })
+    }
 
-                        writeln!(self.write, "<summary>Test output</summary>")?;
-                        writeln!(self.write)?;
+    /// Create the start of the anchors for each label, shared by all of its tests
+    fn cache_anchors(&mut self) {
+        for test in &self.tests {
+            let label = test.label.as_deref();
+            if !self.label_anchors.contains_key(&label) {
+                let start = label_anchor(&self.anchor_prefix, label);
+                self.label_anchors.insert(label, start);
+            }
+        }
+    }
+
+    fn from_run(run: &'a Run) -> Self {
+        Self {
+            name: String::new(),
+            anchor_prefix: String::new(),
+            title_suffix: String::new(),
+            test_count: run.test_count(),
+            summary: run.summary(),
+            tests: run.tests().collect(),
+            invocations: run.invocations().collect(),
+            suites: run.suites.iter().collect(),
+            label_anchors: HashMap::new(),
+        }
+    }
 
-                        writeln!(self.write, "<pre>")?;
-                        writeln!(self.write, "{}", escape(stdout, Html))?;
-                        writeln!(self.write, "</pre>")?;
+    /// Merge all runs, keeping the order of first appearance but the last result of each test.
+    fn merged(runs: &'a [Run]) -> Self {
+        let mut tests = Vec::<&Recorded>::new();
+        let mut index = HashMap::<(Option<&str>, &str), usize>::new();
 
-                        writeln!(self.write)?;
-                        writeln!(self.write, "</details>")?;
+        for test in runs.iter().flat_map(Run::tests) {
+            let name = finished_name(&test.event)
+                .or_else(|| ignored_test(&test.event).map(|(name, _)| name));
+            if let Some(name) = name {
+                let name = (test.label.as_deref(), name);
+                match index.get(&name) {
+                    Some(idx) => tests[*idx] = test,
+                    None => {
+                        index.insert(name, tests.len());
+                        tests.push(test);
                     }
                 }
             }
         }
 
-        Ok(())
-    }
+        let summary = runs.iter().rev().find_map(Run::summary).map(|last| {
+            let failed = tests
+                .iter()
+                .filter(|test| matches!(test.event, test::Event::Failed { .. }))
+                .count() as u64;
+            let passed = tests
+                .iter()
+                .filter(|test| matches!(test.event, test::Event::Ok { .. }))
+                .count() as u64;
+            let incomplete = runs
+                .iter()
+                .filter_map(Run::summary)
+                .any(|summary| summary.incomplete);
+            Summary {
+                outcome: if failed > 0 {
+                    Outcome::Failed
+                } else if incomplete {
+                    Outcome::Incomplete
+                } else {
+                    Outcome::Ok
+                },
+                passed,
+                failed,
+                ignored: last.ignored,
+                filtered_out: last.filtered_out,
+                exec_time: runs
+                    .iter()
+                    .filter_map(|run| run.summary())
+                    .map(|summary| summary.exec_time)
+                    .sum(),
+                incomplete,
+            }
+        });
 
-    /// Make a readable duration from the provided one
-    fn format_duration(&self, duration: &Duration) -> String {
-        if self.options.precise {
-            return format!("{:?}", duration);
+        Self {
+            name: String::new(),
+            anchor_prefix: String::new(),
+            title_suffix: String::new(),
+            test_count: summary.map(|summary| summary.passed + summary.failed + summary.ignored),
+            summary,
+            tests,
+            invocations: runs.iter().flat_map(Run::invocations).collect(),
+            suites: runs
+                .last()
+                .map(|run| run.suites.iter().collect())
+                .unwrap_or_default(),
+            label_anchors: HashMap::new(),
         }
-        let duration = duration.as_secs();
-        humantime::format_duration(Duration::from_secs(duration)).to_string()
     }
 }
 
-impl<W> Drop for Processor<W>
+impl<W> Processor<W>
 where
     W: Write,
 {
-    fn drop(&mut self) {
-        if let Some(summary) = self.summary {
-            self.write_header(&summary).expect("Render header");
+    pub fn new(write: W, options: ProcessOptions) -> Self {
+        Self {
+            write: Capture {
+                write,
+                captured: None,
+            },
+            options,
+            run: Run::default(),
+            spill: None,
+            parser: Parser::default(),
+            records: Vec::new(),
+            label: None,
+            invocation: None,
+            line: 0,
+            ignored: Ignored::default(),
+            interrupted: false,
+            finished: false,
+            streamed: false,
+            received: None,
+            averages: None,
+            started: Instant::now(),
         }
-        if !self.options.summary {
-            self.render_index().expect("Render index");
-            self.render_details().expect("Render details");
+    }
+
+    /// The fixed labels, in the selected language
+    fn labels(&self) -> &'static Labels {
+        self.options.lang.labels()
+    }
+
+    /// The heading of the column, which tells the views apart
+    fn view_column(&self) -> &'static str {
+        match self.options.aggregate {
+            true => self.labels().platform,
+            false => self.labels().run,
         }
     }
-}
 
-fn make_anchor(link: &str) -> String {
-    let mut s = String::with_capacity(link.len());
+    fn write_front_matter(&mut self, outcome: Outcome) -> anyhow::Result<()> {
+        let date = Utc::now();
+
+        let title = format!(
+            "{} {} {}{}",
+            outcome,
+            self.labels().test_result,
+            date.format("%Y-%m-%d %H:%M UTC"),
+            self.labels_suffix()
+        );
+
+        writeln!(self.write, "---")?;
+        // JSON strings are valid YAML strings
+        writeln!(self.write, "title: {}", serde_json::to_string(&title)?)?;
+        writeln!(self.write, "date: {}", date.to_rfc3339())?;
+        writeln!(self.write, "categories: test-report")?;
+        if !self.options.labels.is_empty() {
+            writeln!(
+                self.write,
+                "tags: {}",
+                serde_json::to_string(&self.options.labels)?
+            )?;
+        }
+        writeln!(self.write, "excerpt_separator: <!--more-->")?;
+        writeln!(self.write, "---")?;
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    /// The labels of the report, to be appended to a title
+    fn labels_suffix(&self) -> String {
+        match self.options.labels.is_empty() {
+            true => String::new(),
+            false => format!(" ({})", self.options.labels.join(", ")),
+        }
+    }
+
+    fn write_labels(&mut self) -> anyhow::Result<()> {
+        if !self.options.labels.is_empty() {
+            let labels = self
+                .options
+                .labels
+                .iter()
+                .map(|label| format!("`{}`", label))
+                .collect::<Vec<_>>();
+            writeln!(self.write, "**Labels:** {}", labels.join(" "))?;
+            writeln!(self.write)?;
+        }
+        Ok(())
+    }
+
+    fn write_header(&mut self, views: &[View]) -> anyhow::Result<()> {
+        if !self.options.disable_front_matter {
+            self.write_front_matter(overall_outcome(views))?;
+        }
+
+        self.write_addons(Position::Header)?;
+        self.write_summary(views)?;
+        self.write_suites(views)?;
+        self.write_labels()?;
+        self.write_invocations(views)?;
+        self.write_addons(Position::AfterSummary)?;
+
+        if let Some(link) = self.job_link() {
+            writeln!(self.write, "**Job:** [{link}]({link})", link = link)?;
+            writeln!(self.write)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_addons(&mut self, position: Position) -> anyhow::Result<()> {
+        for (_, addon) in self.options.addons.iter().filter(|(p, _)| *p == position) {
+            addon.render(&mut self.write)?;
+            // the footer ends the report, and its addons separate themselves
+            if position != Position::Footer {
+                writeln!(self.write)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the summary table, and warnings about the summary
+    fn write_summary(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let multiple = views.len() > 1;
+        let column = self.view_column();
+
+        let l = self.labels();
+        let [total, passed, failed, ignored, filtered, duration] =
+            self.options.custom_labels.summary(l);
+        writeln!(self.write)?;
+        if multiple {
+            writeln!(
+                self.write,
+                "| {} | | {} | {} | {} | {} | {} | {} |",
+                column, total, passed, failed, ignored, filtered, duration
+            )?;
+            writeln!(
+                self.write,
+                "| --- | --- | ----- | -------| ------ | ------- | -------- | -------- |"
+            )?;
+        } else {
+            writeln!(
+                self.write,
+                "| | {} | {} | {} | {} | {} | {} |",
+                total, passed, failed, ignored, filtered, duration
+            )?;
+            writeln!(
+                self.write,
+                "| --- | ----- | -------| ------ | ------- | -------- | -------- |"
+            )?;
+        }
+
+        for view in views {
+            let summary = match view.summary {
+                Some(summary) => summary,
+                None => continue,
+            };
+
+            let total = view
+                .test_count
+                .map(|total| self.options.lang.format_count(total))
+                .unwrap_or_else(|| "*unknown*".into());
+
+            if multiple {
+                write!(self.write, "| {} ", view.name)?;
+            }
+
+            writeln!(
+                self.write,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                summary.outcome,
+                total,
+                self.options.lang.format_count(summary.passed),
+                self.options.lang.format_count(summary.failed),
+                self.options.lang.format_count(summary.ignored),
+                self.options.lang.format_count(summary.filtered_out),
+                self.format_duration(&summary.exec_time)
+            )?;
+        }
+        writeln!(self.write)?;
+
+        #[cfg(feature = "store")]
+        if let [.., current] = views {
+            self.write_trend(current)?;
+        }
+
+        if let Some((first, last)) = self.received {
+            let total = views
+                .iter()
+                .filter_map(|view| view.summary)
+                .map(|summary| summary.exec_time)
+                .sum::<Duration>();
+            writeln!(
+                self.write,
+                "**{}:** {} · **{}:** {}",
+                l.test_time,
+                self.format_duration(&total),
+                l.wall_clock,
+                self.format_duration(&(last - first))
+            )?;
+            writeln!(self.write)?;
+        }
+
+        if views
+            .iter()
+            .filter_map(|view| view.summary)
+            .any(|summary| summary.incomplete)
+        {
+            writeln!(
+                self.write,
+                "> ⚠️ **Incomplete:** The test output ended before all test suites finished. \
+                The summary was derived from the recorded test results."
+            )?;
+            writeln!(self.write)?;
+        }
+
+        if !self.ignored.kinds.is_empty() {
+            writeln!(
+                self.write,
+                "> ⚠️ **Unknown records:** The test output contained records which are not supported, \
+                and were ignored: {}.",
+                self.ignored.format_kinds(|kind| format!("`{}`", kind))
+            )?;
+            writeln!(self.write)?;
+        }
+
+        if self.interrupted {
+            writeln!(
+                self.write,
+                "> ⚠️ **Partial:** Processing the test output was interrupted. \
+                The report only contains the results received until then."
+            )?;
+            writeln!(self.write)?;
+        }
+
+        for view in views {
+            if let (Some(expected), Some(reported)) = (view.test_count, view.reported()) {
+                if expected != reported {
+                    writeln!(
+                        self.write,
+                        "> ⚠️ **Count mismatch{}:** {} tests were announced, but {} results were reported.",
+                        view.title_suffix,
+                        self.options.lang.format_count(expected),
+                        self.options.lang.format_count(reported)
+                    )?;
+                    writeln!(self.write)?;
+                }
+            }
+        }
+
+        for view in views {
+            for (budget, took) in self.exceeded_budgets(view) {
+                writeln!(
+                    self.write,
+                    "> ⚠️ **Over budget{}:** {} took {}, exceeding the budget of {}.",
+                    view.title_suffix,
+                    match budget.scope {
+                        Scope::Total => l.total,
+                        Scope::Kind(kind) => kind.title(l),
+                    },
+                    self.format_duration(&took),
+                    self.format_duration(&budget.limit)
+                )?;
+                writeln!(self.write)?;
+            }
+        }
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    /// Write sparklines of the pass rate and the duration of the previous runs in the store, and the
+    /// current one
+    #[cfg(feature = "store")]
+    fn write_trend(&mut self, view: &View) -> anyhow::Result<()> {
+        let (Some(store), Some(summary)) = (&self.options.store, view.summary) else {
+            return Ok(());
+        };
+        if self.options.trend_runs < 2 {
+            return Ok(());
+        }
+
+        let mut runs = store.trend(self.options.trend_runs - 1)?;
+        if runs.is_empty() {
+            return Ok(());
+        }
+        runs.push((
+            summary.passed,
+            summary.failed,
+            summary.exec_time.as_secs_f64(),
+        ));
+
+        let rates = runs
+            .iter()
+            .filter(|(passed, failed, _)| passed + failed > 0)
+            .map(|(passed, failed, _)| *passed as f64 / (passed + failed) as f64)
+            .collect::<Vec<_>>();
+        let durations = runs
+            .iter()
+            .map(|(_, _, duration)| *duration)
+            .collect::<Vec<_>>();
+
+        let l = self.labels();
+        write!(self.write, "**{}:** ", l.trend)?;
+        if let Some(rate) = rates.last() {
+            write!(
+                self.write,
+                "{} {} {:.0}% · ",
+                l.pass_rate,
+                sparkline(&rates),
+                rate * 100.0
+            )?;
+        }
+        writeln!(
+            self.write,
+            "{} {} {}",
+            l.duration,
+            sparkline(&durations),
+            self.format_duration(&summary.exec_time)
+        )?;
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    /// Write the results and duration of each suite, if there is more than one suite
+    fn write_suites(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let suites = views
+            .iter()
+            .flat_map(|view| view.suites.iter().map(move |suite| (view, suite)))
+            .collect::<Vec<_>>();
+        if suites.len() < 2 {
+            return Ok(());
+        }
+
+        let multiple = views.len() > 1;
+        let total = suites
+            .iter()
+            .map(|(_, suite)| suite.summary().exec_time)
+            .sum::<Duration>();
+
+        let l = self.labels();
+        writeln!(self.write, "**{}:**", l.suites)?;
+        writeln!(self.write)?;
+        if multiple {
+            write!(self.write, "| {} ", self.view_column())?;
+        }
+        writeln!(
+            self.write,
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            l.suite, l.kind, l.total, l.passed, l.failed, l.duration, l.share
+        )?;
+        if multiple {
+            write!(self.write, "| --- ")?;
+        }
+        writeln!(
+            self.write,
+            "| ----- | ---- | ----- | ------ | ------ | -------- | ----- |"
+        )?;
+
+        for (idx, (view, suite)) in suites.iter().enumerate() {
+            let summary = suite.summary();
+            let over_budget = self
+                .exceeded_budgets(view)
+                .iter()
+                .any(|(budget, _)| budget.scope == Scope::Kind(suite.kind));
+            let name = suite
+                .invocation
+                .as_ref()
+                .and_then(|invocation| {
+                    invocation
+                        .target
+                        .as_deref()
+                        .or_else(|| invocation.binary_name())
+                })
+                .map(|target| format!("`{}`", target))
+                .unwrap_or_else(|| format!("#{}", idx + 1));
+            let name = match &suite.label {
+                Some(label) => format!("{} ({})", name, label),
+                None => name,
+            };
+            let share = match total.is_zero() {
+                true => 0.0,
+                false => summary.exec_time.as_secs_f64() / total.as_secs_f64() * 100.0,
+            };
+
+            if multiple {
+                write!(self.write, "| {} ", view.name)?;
+            }
+            writeln!(
+                self.write,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                name,
+                suite.kind.title(l),
+                self.options.lang.format_count(
+                    suite
+                        .test_count
+                        .unwrap_or(summary.passed + summary.failed + summary.ignored)
+                ),
+                self.options.lang.format_count(summary.passed),
+                self.options.lang.format_count(summary.failed),
+                match over_budget {
+                    true => format!("⚠️ {}", self.format_duration(&summary.exec_time)),
+                    false => self.format_duration(&summary.exec_time),
+                },
+                self.options.lang.localize_number(&format!("{:.0}%", share)),
+            )?;
+        }
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    /// Write how the test binaries were invoked, if known
+    fn write_invocations(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let mut invocations = Vec::<&Invocation>::new();
+        for invocation in views.iter().flat_map(|view| view.invocations.iter()) {
+            if !invocations.contains(invocation) {
+                invocations.push(invocation);
+            }
+        }
+
+        if invocations.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(self.write, "**Invocations:**")?;
+        writeln!(self.write)?;
+        writeln!(
+            self.write,
+            "| Target | Binary | Filters | Test threads | Arguments |"
+        )?;
+        writeln!(
+            self.write,
+            "| ------ | ------ | ------- | ------------ | --------- |"
+        )?;
+
+        for invocation in invocations {
+            let code = |value: &str| format!("`{}`", value);
+            writeln!(
+                self.write,
+                "| {} | {} | {} | {} | {} |",
+                invocation.target.as_deref().unwrap_or_default(),
+                invocation.binary_name().map(code).unwrap_or_default(),
+                invocation
+                    .filters()
+                    .into_iter()
+                    .map(code)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                invocation.test_threads().unwrap_or_default(),
+                match invocation.args.is_empty() {
+                    true => String::new(),
+                    false => code(&invocation.args.join(" ")),
+                }
+            )?;
+        }
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    /// Render a short report, meant to be used as pull request comment
+    fn render_comment(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let failed = views
+            .iter()
+            .flat_map(|view| view.tests.iter().map(move |test| (view, test)))
+            .filter(|(_, test)| matches!(test.event, test::Event::Failed { .. }))
+            .collect::<Vec<_>>();
+        let outcome = overall_outcome(views);
+
+        let verdict = match outcome {
+            Outcome::Ok => "All tests passed".to_string(),
+            Outcome::Incomplete => "Test run incomplete".to_string(),
+            Outcome::Failed if failed.len() == 1 => "1 test failed".to_string(),
+            Outcome::Failed => format!("{} tests failed", failed.len()),
+        };
+        writeln!(
+            self.write,
+            "### {} {}{}",
+            outcome,
+            verdict,
+            self.labels_suffix()
+        )?;
+
+        self.write_summary(views)?;
+
+        if !failed.is_empty() {
+            writeln!(self.write, "#### Failed tests")?;
+            writeln!(self.write)?;
+        }
+
+        for (view, test) in failed.iter().take(MAX_COMMENT_FAILURES) {
+            let name = finished_name(&test.event).unwrap_or_default();
+            let label = match &test.label {
+                Some(label) => format!(" ({})", label),
+                None => String::new(),
+            };
+
+            let stdout = self.output(test)?;
+            let (output, _) = sanitize_output(&stdout);
+            let lines = output.lines().collect::<Vec<_>>();
+            let skip = lines.len().saturating_sub(MAX_COMMENT_OUTPUT_LINES);
+
+            if self.options.flavor == Flavor::AzureDevops {
+                writeln!(
+                    self.write,
+                    "**❌ `{}`{}{}**",
+                    self.display_name(name),
+                    label,
+                    view.title_suffix
+                )?;
+                writeln!(self.write)?;
+                if skip > 0 {
+                    writeln!(
+                        self.write,
+                        "*Showing the last {} lines.*",
+                        MAX_COMMENT_OUTPUT_LINES
+                    )?;
+                    writeln!(self.write)?;
+                }
+                self.render_code_block(&lines[skip..].join("\n"))?;
+                writeln!(self.write)?;
+                continue;
+            }
+
+            writeln!(self.write, "<details>")?;
+            writeln!(
+                self.write,
+                "<summary>❌ <code>{}</code>{}{}</summary>",
+                escape(&self.display_name(name), Html),
+                escape(&label, Html),
+                escape(&view.title_suffix, Html)
+            )?;
+            writeln!(self.write)?;
+
+            if skip > 0 {
+                writeln!(
+                    self.write,
+                    "*Showing the last {} lines.*",
+                    MAX_COMMENT_OUTPUT_LINES
+                )?;
+                writeln!(self.write)?;
+            }
+
+            writeln!(self.write, "<pre>")?;
+            for line in &lines[skip..] {
+                writeln!(self.write, "{}", escape(line, Html))?;
+            }
+            writeln!(self.write, "</pre>")?;
+            writeln!(self.write)?;
+            writeln!(self.write, "</details>")?;
+            writeln!(self.write)?;
+        }
+
+        if failed.len() > MAX_COMMENT_FAILURES {
+            writeln!(
+                self.write,
+                "*… and {} more failed tests.*",
+                failed.len() - MAX_COMMENT_FAILURES
+            )?;
+            writeln!(self.write)?;
+        }
+
+        if let Some(url) = &self.options.report_url {
+            writeln!(self.write, "[Full report]({})", url)?;
+            writeln!(self.write)?;
+        }
+
+        if let Some(link) = self.job_link() {
+            writeln!(self.write, "**Job:** [{link}]({link})", link = link)?;
+            writeln!(self.write)?;
+        }
+
+        Ok(())
+    }
+
+    /// The link to the CI job, if all variables of its template are set
+    fn job_link(&self) -> Option<String> {
+        let template = self
+            .options
+            .job_link_template
+            .as_deref()
+            .unwrap_or(GITHUB_JOB_LINK);
+        expand_template(template, |name| std::env::var(name).ok())
+    }
+
+    /// Mark the report as partial, as processing the input was interrupted
+    pub fn interrupt(&mut self) {
+        self.interrupted = true;
+    }
+
+    /// Set if the input which gets processed next is streamed while the tests are running, which
+    /// allows measuring the wall-clock time of the run
+    pub fn set_streamed(&mut self, streamed: bool) {
+        self.streamed = streamed;
+    }
+
+    /// Set the label of the input which gets processed next
+    pub fn set_label(&mut self, label: Option<String>) {
+        if self.options.embed_records {
+            self.records.push(match &label {
+                Some(label) => format!("{} {}", LABEL_PREFIX, label),
+                None => LABEL_PREFIX.to_string(),
+            });
+        }
+        self.label = label.map(Arc::from);
+        self.line = 0;
+    }
+
+    /// Replay records, which were previously embedded into a report, optionally keeping their labels
+    pub fn replay(&mut self, records: &[&str], labels: bool) -> Result<(), Error> {
+        for record in records {
+            match record.strip_prefix(LABEL_PREFIX) {
+                Some(_) if !labels => {}
+                Some(label) => {
+                    self.set_label(Some(label.trim().to_string()).filter(|l| !l.is_empty()))
+                }
+                None => self.line(record)?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn line(&mut self, line: &str) -> Result<(), Error> {
+        let result = self.parser.parse(line);
+        self.parsed(line, result)
+    }
+
+    /// Where the current line is, like `line 12` or `unit.json, line 12`, for diagnostics.
+    fn location(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{}, line {}", label, self.line),
+            None => format!("line {}", self.line),
+        }
+    }
+
+    /// Fail with an error about the current line, when running strict.
+    fn strict(&self, message: impl FnOnce() -> String) -> Result<(), Error> {
+        match self.options.strict {
+            true => Err(Error::Parse {
+                line: self.line,
+                message: message(),
+            }),
+            false => Ok(()),
+        }
+    }
+
+    /// Process a line, which was already parsed
+    ///
+    /// Lines which are not records are ignored, unless running strict, but broken records fail, as
+    /// they would lose results.
+    pub fn parsed(&mut self, line: &str, result: Result<Record, ParseError>) -> Result<(), Error> {
+        self.line += 1;
+        self.ignored.lines += 1;
+        match result {
+            Ok(mut record) => {
+                let redacted = self.redact(&mut record);
+                if self.options.embed_records {
+                    self.records.push(match redacted {
+                        // don't leak it through the embedded records either
+                        true => record.to_line(),
+                        false => line.to_string(),
+                    });
+                }
+                self.record(record)?
+            }
+            Err(err) => match Invocation::parse(line) {
+                Some(invocation) => {
+                    log::debug!(line = self.line; "Invocation: {:?}", invocation);
+                    if self.options.embed_records {
+                        self.records.push(line.to_string());
+                    }
+                    self.invocation = Some(invocation);
+                }
+                None if line.trim().is_empty() => {}
+                None if event::is_broken(line) => {
+                    return Err(Error::Parse {
+                        line: self.line,
+                        message: err.to_string(),
+                    })
+                }
+                None => {
+                    log::debug!(line = self.line; "{}: ignoring line: {:?} -> {}", self.location(), err, line);
+                    match serde_json::from_str::<serde::de::IgnoredAny>(line) {
+                        Ok(_) => {
+                            self.strict(|| "not a record".into())?;
+                            self.ignored.unknown += 1;
+                        }
+                        Err(_) => {
+                            self.strict(|| "not JSON".into())?;
+                            self.ignored.invalid += 1;
+                        }
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Redact the output of a failed test, returning if anything was replaced
+    fn redact(&self, record: &mut Record) -> bool {
+        let Record::Test(test::Event::Failed {
+            stdout, message, ..
+        }) = record
+        else {
+            return false;
+        };
+
+        let mut redacted = false;
+        for text in std::iter::once(stdout).chain(message.as_mut()) {
+            if let Cow::Owned(replaced) = self.options.redactor.redact(text) {
+                *text = replaced;
+                redacted = true;
+            }
+        }
+        redacted
+    }
+
+    fn record(&mut self, record: Record) -> anyhow::Result<()> {
+        log::debug!(line = self.line; "Record: {:?}", record);
+
+        if self.streamed {
+            let now = Instant::now();
+            let first = self.received.map_or(now, |(first, _)| first);
+            self.received = Some((first, now));
+        }
+
+        match record {
+            Record::Test(test) => {
+                if !matches!(test, test::Event::Started { .. }) {
+                    let result = TestResult {
+                        event: &test,
+                        label: self.label.as_deref(),
+                    };
+                    for observer in &mut self.options.observers {
+                        observer.on_test(&result);
+                    }
+                }
+                let test = self.store(test)?;
+                self.current_suite().tests.push(test);
+            }
+            Record::Unknown(unknown) => {
+                self.strict(|| format!("unknown record `{}`", unknown))?;
+                // report each kind once, the summary counts all of them
+                if !self.ignored.kinds.contains_key(&unknown) {
+                    log::warn!(line = self.line; "{}: ignoring unknown record `{}`", self.location(), unknown);
+                }
+                *self.ignored.kinds.entry(unknown).or_default() += 1;
+            }
+
+            Record::Suite(suite::Event::Started { test_count }) => {
+                self.record_suite_started(test_count);
+            }
+            Record::Suite(suite::Event::Ok {
+                passed,
+                failed,
+                ignored,
+                filtered_out,
+                exec_time,
+                ..
+            }) => {
+                self.record_suite_finished(Summary {
+                    outcome: Outcome::Ok,
+                    passed,
+                    failed,
+                    ignored,
+                    filtered_out,
+                    exec_time,
+                    incomplete: false,
+                });
+            }
+            Record::Suite(suite::Event::Failed {
+                passed,
+                failed,
+                ignored,
+                filtered_out,
+                exec_time,
+                ..
+            }) => {
+                self.record_suite_finished(Summary {
+                    outcome: Outcome::Failed,
+                    passed,
+                    failed,
+                    ignored,
+                    filtered_out,
+                    exec_time,
+                    incomplete: false,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store a test event, spilling its output to disk if requested
+    fn store(&mut self, mut event: test::Event) -> anyhow::Result<Recorded> {
+        let mut spilled = None;
+
+        if self.options.spill {
+            if let test::Event::Failed { stdout, .. } = &mut event {
+                if !stdout.is_empty() {
+                    let spill = match &mut self.spill {
+                        Some(spill) => spill,
+                        None => self.spill.insert(Spill::new()?),
+                    };
+                    spilled = Some(spill.store(&std::mem::take(stdout))?);
+                }
+            }
+        }
+
+        Ok(Recorded {
+            event,
+            label: self.label.clone(),
+            spilled,
+            kind: SuiteKind::default(),
+        })
+    }
+
+    /// Get the output of a test, loading it from disk if it was spilled
+    fn output<'t>(&self, test: &'t Recorded) -> anyhow::Result<Cow<'t, str>> {
+        match (&test.event, test.spilled, &self.spill) {
+            (_, Some(spilled), Some(spill)) => Ok(Cow::Owned(spill.load(spilled)?)),
+            (test::Event::Failed { stdout, .. }, _, _) => Ok(Cow::Borrowed(stdout)),
+            _ => Ok(Cow::Borrowed("")),
+        }
+    }
+
+    /// Get the current suite, creating one if there is none yet
+    fn current_suite(&mut self) -> &mut Suite {
+        let label = self.label.clone();
+        let run = &mut self.run;
+        if run.suites.last().is_none_or(|suite| suite.label != label) {
+            run.suites.push(Suite {
+                label,
+                ..Default::default()
+            });
+        }
+        run.suites.last_mut().unwrap()
+    }
+
+    fn record_suite_started(&mut self, test_count: u64) {
+        let label = self.label.clone();
+        let invocation = self.invocation.take();
+        self.run.suites.push(Suite {
+            label,
+            invocation,
+            test_count: Some(test_count),
+            ..Default::default()
+        });
+    }
+
+    fn record_suite_finished(&mut self, summary: Summary) {
+        let result = SuiteResult {
+            outcome: summary.outcome,
+            passed: summary.passed,
+            failed: summary.failed,
+            ignored: summary.ignored,
+            filtered_out: summary.filtered_out,
+            exec_time: summary.exec_time,
+            label: self.label.as_deref(),
+        };
+        for observer in &mut self.options.observers {
+            observer.on_suite(&result);
+        }
+
+        let mut suite = self.run.suites.pop().unwrap_or_default();
+        suite.summary = Some(summary);
+        self.run.suites.push(suite);
+    }
+
+    /// Create the anchor of a test, which must be unique in the report
+    ///
+    /// Azure DevOps only links to headings, the anchor then follows the name in the heading.
+    fn make_test_anchor(&self, view: &View, test: &Recorded, name: &str) -> String {
+        if self.options.flavor == Flavor::AzureDevops {
+            return make_azure_anchor(&self.display_name(name));
+        }
+
+        let doc_test = DocTest::parse(name).map(|doc_test| doc_test.anchor_name());
+        let name = doc_test.as_deref().unwrap_or(name);
+        let label = test.label.as_deref();
+        let start = match view.label_anchors.get(&label) {
+            Some(start) => Cow::Borrowed(start.as_str()),
+            None => Cow::Owned(label_anchor(&view.anchor_prefix, label)),
+        };
+
+        // this is called twice per test, building the anchor in a single allocation
+        let mut anchor = String::with_capacity(start.len() + name.len());
+        anchor.push_str(&start);
+        push_anchor(&mut anchor, name);
+        anchor
+    }
+
+    /// Write a name (for the index) linking to the actual test
+    fn write_linked_name(
+        &mut self,
+        view: &View,
+        test: &Recorded,
+        name: &str,
+        title: &str,
+    ) -> anyhow::Result<()> {
+        let anchor = self.make_test_anchor(view, test, name);
+        match self.truncate_name(title) {
+            Some(truncated) => write!(
+                self.write,
+                r#"[{}](#{} "{}")"#,
+                truncated,
+                anchor,
+                title.replace('"', "\\\"")
+            )?,
+            None => write!(self.write, "[{}](#{})", title, anchor)?,
+        }
+        Ok(())
+    }
+
+    /// Truncate a name exceeding the maximum length, returning `None` if it fits
+    fn truncate_name(&self, name: &str) -> Option<String> {
+        let max = self.options.max_name_length?;
+        if name.chars().count() <= max {
+            return None;
+        }
+        let mut truncated = name.chars().take(max.saturating_sub(1)).collect::<String>();
+        truncated.push('…');
+        Some(truncated)
+    }
+
+    /// The base URL of the source files, used to link doc-tests to their source
+    fn source_url(&self) -> Option<String> {
+        if let Some(url) = &self.options.source_url {
+            return Some(url.trim_end_matches('/').to_string());
+        }
+
+        let server = std::env::var("GITHUB_SERVER_URL").ok()?;
+        let repo = std::env::var("GITHUB_REPOSITORY").ok()?;
+        let sha = std::env::var("GITHUB_SHA").ok()?;
+        Some(format!("{}/{}/blob/{}", server, repo, sha))
+    }
+
+    /// Write the heading statement of a test
+    fn write_heading(
+        &mut self,
+        view: &View,
+        test: &Recorded,
+        name: &str,
+        outcome: &str,
+    ) -> anyhow::Result<()> {
+        let title = self.display_name(name);
+        match self.options.flavor {
+            Flavor::Github => writeln!(
+                self.write,
+                r#"## {} {}<a id="{}"></a>"#,
+                outcome,
+                title,
+                self.make_test_anchor(view, test, name)
+            )?,
+            Flavor::AzureDevops => writeln!(self.write, "## {} {}", outcome, title)?,
+        }
+        Ok(())
+    }
+
+    /// The tag declaring an anchor at a heading, Azure DevOps uses the heading itself
+    fn make_anchor_tag(&self, anchor: &str) -> String {
+        match self.options.flavor {
+            Flavor::Github => format!(r#"<a id="{}"></a>"#, anchor),
+            Flavor::AzureDevops => String::new(),
+        }
+    }
+
+    /// The name of a test, as it gets displayed, after applying the rename rules
+    fn display_name<'n>(&self, name: &'n str) -> Cow<'n, str> {
+        let mut result = Cow::Borrowed(name);
+        for rename in &self.options.renames {
+            if let Cow::Owned(renamed) = rename.apply(&result) {
+                result = Cow::Owned(renamed);
+            }
+        }
+        result
+    }
+
+    fn render_index(&mut self, view: &View) -> anyhow::Result<()> {
+        let l = self.labels();
+        writeln!(
+            self.write,
+            "# {}{}",
+            self.options.custom_labels.index_title(l),
+            view.title_suffix
+        )?;
+        writeln!(self.write)?;
+
+        let labeled = view.tests.iter().any(|test| test.label.is_some());
+        let (change, change_separator) = match self.averages.is_some() {
+            true => (format!(" {} |", l.change), " ------ |"),
+            false => (String::new(), ""),
+        };
+        if labeled {
+            writeln!(
+                self.write,
+                "| {} | Job | {} | {} |{}",
+                l.name, l.result, l.duration, change
+            )?;
+            writeln!(
+                self.write,
+                "| ---- | --- | ------ | -------- |{}",
+                change_separator
+            )?;
+        } else {
+            writeln!(
+                self.write,
+                "| {} | {} | {} |{}",
+                l.name, l.result, l.duration, change
+            )?;
+            writeln!(
+                self.write,
+                "| ---- | ------ | -------- |{}",
+                change_separator
+            )?;
+        }
+
+        let columns = match (labeled, self.averages.is_some()) {
+            (true, true) => " | | | |",
+            (true, false) | (false, true) => " | | |",
+            (false, false) => " | |",
+        };
+
+        let kinds = group_kinds(&view.tests);
+        let grouped = kinds.len() > 1;
+        for (kind, tests) in kinds {
+            if grouped {
+                writeln!(self.write, "| ***{}*** |{}", kind.title(l), columns)?;
+            }
+
+            let (tests, files) = group_doc_tests(&tests);
+            for item in group_cases(tests) {
+                match item {
+                    Item::Test(test) => self.render_index_row(view, test, labeled)?,
+                    Item::Cases { parent, tests } => {
+                        self.render_index_cases_row(view, parent, &tests, labeled)?
+                    }
+                }
+            }
+
+            for (file, tests) in files {
+                writeln!(self.write, "| **{}** |{}", file, columns)?;
+                for test in tests {
+                    self.render_index_row(view, test, labeled)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the tests in the order they started, if enabled and the input has timestamps
+    fn render_execution_order(&mut self, view: &View) -> anyhow::Result<()> {
+        if !self.options.timestamps {
+            return Ok(());
+        }
+        let mut tests = view
+            .tests
+            .iter()
+            .filter_map(|test| Some((start_time(&test.event)?, *test)))
+            .collect::<Vec<_>>();
+        if tests.is_empty() {
+            return Ok(());
+        }
+        tests.sort_by_key(|(start_time, _)| *start_time);
+
+        let l = self.labels();
+        writeln!(self.write)?;
+        writeln!(self.write)?;
+        writeln!(self.write, "# {}{}", l.execution_order, view.title_suffix)?;
+        writeln!(self.write)?;
+        writeln!(
+            self.write,
+            "| {} | {} | {} | {} |",
+            l.started_at, l.name, l.result, l.duration
+        )?;
+        writeln!(self.write, "| ---------- | ---- | ------ | -------- |")?;
+        let mut next = 0;
+        for (start_time, test) in tests {
+            while self
+                .options
+                .timeline
+                .get(next)
+                .is_some_and(|event| event.time <= start_time)
+            {
+                self.render_timeline_row(next)?;
+                next += 1;
+            }
+            let (name, outcome, exec_time) = match &test.event {
+                test::Event::Started { .. } | test::Event::Ignored { .. } => continue,
+                test::Event::Ok {
+                    name, exec_time, ..
+                } => (name, "✅", exec_time),
+                test::Event::Failed {
+                    name, exec_time, ..
+                } => (name, "❌", exec_time),
+            };
+            // only failed cases of parameterized tests have a section of their own
+            let target = match (&test.event, split_case(name)) {
+                (test::Event::Ok { .. }, Some((parent, _))) => parent,
+                _ => name,
+            };
+            write!(self.write, "| {} | ", format_timestamp(&start_time))?;
+            self.write_linked_name(view, test, target, &self.display_name(name))?;
+            writeln!(
+                self.write,
+                " | {} | {} |",
+                outcome,
+                self.format_duration(exec_time)
+            )?;
+        }
+        for idx in next..self.options.timeline.len() {
+            self.render_timeline_row(idx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Render the event of the timeline at the index, as a row of the execution order
+    fn render_timeline_row(&mut self, idx: usize) -> anyhow::Result<()> {
+        let event = &self.options.timeline[idx];
+        writeln!(
+            self.write,
+            "| {} | 📌 {} | | |",
+            format_timestamp(&event.time),
+            escape(&event.message, Html).to_string().replace('|', "\\|")
+        )?;
+        Ok(())
+    }
+
+    /// Render the ignored tests, with the reason if known
+    fn render_ignored(&mut self, view: &View) -> anyhow::Result<()> {
+        let ignored = view
+            .tests
+            .iter()
+            .filter_map(|test| ignored_test(&test.event))
+            .collect::<Vec<_>>();
+        if ignored.is_empty() {
+            return Ok(());
+        }
+
+        let l = self.labels();
+        writeln!(self.write)?;
+        writeln!(self.write)?;
+        writeln!(self.write, "# {}{}", l.ignored, view.title_suffix)?;
+        writeln!(self.write)?;
+        writeln!(self.write, "| {} | {} |", l.name, l.reason)?;
+        writeln!(self.write, "| ---- | ------ |")?;
+        for (name, reason) in ignored {
+            writeln!(
+                self.write,
+                "| {} | {} |",
+                self.display_name(name),
+                reason
+                    .map(|reason| escape(reason, Html).to_string().replace('|', "\\|"))
+                    .unwrap_or_default()
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn render_index_row(
+        &mut self,
+        view: &View,
+        test: &Recorded,
+        labeled: bool,
+    ) -> anyhow::Result<()> {
+        let (name, outcome, exec_time) = match &test.event {
+            test::Event::Started { .. } | test::Event::Ignored { .. } => return Ok(()),
+            test::Event::Ok {
+                name, exec_time, ..
+            } => (name, "✅", exec_time),
+            test::Event::Failed {
+                name, exec_time, ..
+            } => (name, "❌", exec_time),
+        };
+
+        let doc_test = DocTest::parse(name);
+        let (kind, title) = match &doc_test {
+            Some(doc_test) => (None, Cow::Owned(doc_test.title())),
+            None => match split_kind(name) {
+                Some((kind, name)) => (Some(kind), Cow::Borrowed(name)),
+                None => (None, Cow::Borrowed(name.as_str())),
+            },
+        };
+        let title = self.display_name(&title);
+        write!(self.write, "| ")?;
+        self.write_linked_name(view, test, name, &title)?;
+        write!(self.write, " ")?;
+        for mode in doc_test.iter().flat_map(DocTest::modes) {
+            write!(self.write, "`{}` ", mode.attribute())?;
+        }
+        if let Some(kind) = kind {
+            write!(self.write, "`{}` ", kind)?;
+        }
+        if labeled {
+            write!(
+                self.write,
+                "| {} ",
+                test.label.as_deref().unwrap_or_default()
+            )?;
+        }
+        write!(
+            self.write,
+            "| {} | {} | ",
+            outcome,
+            self.format_duration(exec_time)
+        )?;
+        if let Some(averages) = &self.averages {
+            let change = averages
+                .get(name)
+                .filter(|_| !exec_time.is_zero())
+                .map(|average| {
+                    format!("{:+.0}%", (exec_time.as_secs_f64() / average - 1.0) * 100.0)
+                })
+                .unwrap_or_default();
+            write!(self.write, "{} | ", change)?;
+        }
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    /// Render a single row for all cases of a parameterized test
+    fn render_index_cases_row(
+        &mut self,
+        view: &View,
+        parent: &str,
+        tests: &[&Recorded],
+        labeled: bool,
+    ) -> anyhow::Result<()> {
+        let first = tests[0];
+        write!(self.write, "| ")?;
+        self.write_linked_name(view, first, parent, &self.display_name(parent))?;
+        write!(self.write, " ({} cases) ", tests.len())?;
+        if labeled {
+            write!(
+                self.write,
+                "| {} ",
+                first.label.as_deref().unwrap_or_default()
+            )?;
+        }
+        write!(
+            self.write,
+            "| {} | {} | ",
+            cases_outcome(tests),
+            self.format_duration(&cases_duration(tests))
+        )?;
+        if self.averages.is_some() {
+            write!(self.write, " | ")?;
+        }
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    fn render_details(&mut self, view: &View) -> anyhow::Result<()> {
+        writeln!(self.write)?;
+        writeln!(self.write)?;
+        writeln!(
+            self.write,
+            "# {}{}",
+            self.options.custom_labels.details_title(self.labels()),
+            view.title_suffix
+        )?;
+
+        for (_, tests) in group_kinds(&view.tests) {
+            let (tests, files) = group_doc_tests(&tests);
+            for item in group_cases(tests) {
+                match item {
+                    Item::Test(test) => self.render_test_details(view, test)?,
+                    Item::Cases { parent, tests } => {
+                        self.render_cases_details(view, parent, &tests)?
+                    }
+                }
+            }
+            for test in files.into_iter().flat_map(|(_, tests)| tests) {
+                self.render_test_details(view, test)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render all cases of a parameterized test in one section, with the output of failed cases
+    fn render_cases_details(
+        &mut self,
+        view: &View,
+        parent: &str,
+        tests: &[&Recorded],
+    ) -> anyhow::Result<()> {
+        let first = tests[0];
+        writeln!(self.write)?;
+        self.write_heading(view, first, parent, cases_outcome(tests))?;
+        writeln!(self.write)?;
+        self.render_label(first)?;
+        writeln!(
+            self.write,
+            "**{}**: {}",
+            self.labels().duration,
+            self.format_duration(&cases_duration(tests))
+        )?;
+        writeln!(self.write)?;
+
+        let l = self.labels();
+        writeln!(self.write, "| {} | {} | {} |", l.case, l.result, l.duration)?;
+        writeln!(self.write, "| ---- | ------ | -------- |")?;
+        for test in tests {
+            let (name, outcome, exec_time) = match &test.event {
+                test::Event::Started { .. } | test::Event::Ignored { .. } => continue,
+                test::Event::Ok {
+                    name, exec_time, ..
+                } => (name, "✅", exec_time),
+                test::Event::Failed {
+                    name, exec_time, ..
+                } => (name, "❌", exec_time),
+            };
+            let case = split_case(name).map(|(_, case)| case).unwrap_or(name);
+            write!(self.write, "| ")?;
+            match &test.event {
+                test::Event::Failed { .. } => self.write_linked_name(view, test, name, case)?,
+                _ => write!(self.write, "{}", case)?,
+            }
+            writeln!(
+                self.write,
+                " | {} | {} |",
+                outcome,
+                self.format_duration(exec_time)
+            )?;
+        }
+
+        for test in tests {
+            if let test::Event::Failed {
+                name, exec_time, ..
+            } = &test.event
+            {
+                let case = match self.options.flavor {
+                    Flavor::Github => split_case(name).map(|(_, case)| case).unwrap_or(name),
+                    // the heading must contain the full name, to be linked to
+                    Flavor::AzureDevops => &self.display_name(name),
+                };
+                writeln!(self.write)?;
+                writeln!(
+                    self.write,
+                    "### ❌ {}{}",
+                    case,
+                    self.make_anchor_tag(&self.make_test_anchor(view, test, name))
+                )?;
+                writeln!(self.write)?;
+                writeln!(
+                    self.write,
+                    "**{}**: {}",
+                    self.labels().duration,
+                    self.format_duration(exec_time)
+                )?;
+                self.render_start_time(test)?;
+                self.render_message(test)?;
+                let stdout = self.output(test)?;
+                self.render_output(&stdout)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn render_test_details(&mut self, view: &View, test: &Recorded) -> anyhow::Result<()> {
+        match &test.event {
+            test::Event::Started { .. } | test::Event::Ignored { .. } => {}
+            test::Event::Ok {
+                name, exec_time, ..
+            } => {
+                writeln!(self.write)?;
+                self.write_heading(view, test, name, "✅")?;
+                writeln!(self.write)?;
+                self.render_label(test)?;
+                self.render_source(name)?;
+                writeln!(
+                    self.write,
+                    "**{}**: {}",
+                    self.labels().duration,
+                    self.format_duration(exec_time)
+                )?;
+                self.render_start_time(test)?;
+            }
+
+            test::Event::Failed {
+                name, exec_time, ..
+            } => {
+                writeln!(self.write)?;
+                self.write_heading(view, test, name, "❌")?;
+                writeln!(self.write)?;
+                self.render_label(test)?;
+                self.render_source(name)?;
+                writeln!(
+                    self.write,
+                    "**{}**: {}",
+                    self.labels().duration,
+                    self.format_duration(exec_time)
+                )?;
+                self.render_start_time(test)?;
+                self.render_message(test)?;
+                let stdout = self.output(test)?;
+                self.render_output(&stdout)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render when the test started, and the events of the timeline while it ran, if enabled and
+    /// reported
+    fn render_start_time(&mut self, test: &Recorded) -> anyhow::Result<()> {
+        let (true, Some(start_time)) = (self.options.timestamps, start_time(&test.event)) else {
+            return Ok(());
+        };
+        let l = self.labels();
+        writeln!(self.write)?;
+        writeln!(
+            self.write,
+            "**{}**: {}",
+            l.started_at,
+            format_timestamp(&start_time)
+        )?;
+
+        let end_time = match &test.event {
+            test::Event::Ok { exec_time, .. } | test::Event::Failed { exec_time, .. } => {
+                start_time
+                    + chrono::Duration::from_std(*exec_time)
+                        .unwrap_or_else(|_| chrono::Duration::zero())
+            }
+            test::Event::Started { .. } | test::Event::Ignored { .. } => start_time,
+        };
+        let mut events = self
+            .options
+            .timeline
+            .iter()
+            .filter(|event| event.time >= start_time && event.time <= end_time)
+            .peekable();
+        if events.peek().is_some() {
+            writeln!(self.write)?;
+            writeln!(self.write, "**{}**:", l.events)?;
+            writeln!(self.write)?;
+            for event in events {
+                writeln!(
+                    self.write,
+                    "* {}: {}",
+                    format_timestamp(&event.time),
+                    escape(&event.message, Html)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Render the reason of a failure, if the test harness reported one
+    fn render_message(&mut self, test: &Recorded) -> anyhow::Result<()> {
+        if let test::Event::Failed {
+            message: Some(message),
+            ..
+        } = &test.event
+        {
+            writeln!(self.write)?;
+            writeln!(self.write, "**Message**: {}", escape(message, Html))?;
+        }
+        Ok(())
+    }
+
+    /// Render the captured output of a test, collapsed
+    fn render_output(&mut self, stdout: &str) -> anyhow::Result<()> {
+        if stdout.is_empty() {
+            return Ok(());
+        }
+
+        let (output, note) = sanitize_output(stdout);
+        if self.options.flavor == Flavor::AzureDevops {
+            writeln!(self.write)?;
+            writeln!(self.write, "**Test output**:")?;
+            writeln!(self.write)?;
+            if let Some(note) = note {
+                writeln!(self.write, "*{}*", note)?;
+                writeln!(self.write)?;
+            }
+            return self.render_code_block(&output);
+        }
+
+        writeln!(self.write)?;
+        writeln!(self.write, "<details>")?;
+        writeln!(self.write)?;
+
+        writeln!(self.write, "<summary>Test output</summary>")?;
+        writeln!(self.write)?;
+
+        if let Some(note) = note {
+            writeln!(self.write, "*{}*", note)?;
+            writeln!(self.write)?;
+        }
+
+        writeln!(self.write, "<pre>")?;
+        writeln!(self.write, "{}", escape(&output, Html))?;
+        writeln!(self.write, "</pre>")?;
+
+        writeln!(self.write)?;
+        writeln!(self.write, "</details>")?;
+
+        Ok(())
+    }
+
+    /// Render a fenced code block, for platforms not rendering `<pre>` inside of markdown
+    fn render_code_block(&mut self, content: &str) -> anyhow::Result<()> {
+        let fence = raw::fence(content);
+        writeln!(self.write, "{}text", fence)?;
+        writeln!(self.write, "{}", content.trim_end_matches('\n'))?;
+        writeln!(self.write, "{}", fence)?;
+        Ok(())
+    }
+
+    fn render_label(&mut self, test: &Recorded) -> anyhow::Result<()> {
+        if let Some(label) = &test.label {
+            let kind = match self.options.aggregate {
+                true => "Platform",
+                false => "Job",
+            };
+            writeln!(self.write, "**{}**: {}", kind, label)?;
+            writeln!(self.write)?;
+        }
+        Ok(())
+    }
+
+    /// Render the location of a doc-test, linked to the source when possible
+    fn render_source(&mut self, name: &str) -> anyhow::Result<()> {
+        if let Some(doc_test) = DocTest::parse(name) {
+            let location = format!("{}:{}", doc_test.file, doc_test.line);
+            match self.source_url() {
+                Some(url) => writeln!(
+                    self.write,
+                    "**Source**: [`{}`]({}/{}#L{})",
+                    location, url, doc_test.file, doc_test.line
+                )?,
+                None => writeln!(self.write, "**Source**: `{}`", location)?,
+            }
+            writeln!(self.write)?;
+            for mode in doc_test.modes() {
+                writeln!(
+                    self.write,
+                    "**Note**: `{}` – {}",
+                    mode.attribute(),
+                    mode.description()
+                )?;
+                writeln!(self.write)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the views to render, based on the selected run mode
+    fn make_views<'a>(&self, runs: &'a [Run]) -> Vec<View<'a>> {
+        match (self.options.runs, runs) {
+            (_, []) => vec![],
+            (_, [run]) => vec![View::from_run(run)],
+            (RunMode::Merge, runs) => vec![View::merged(runs)],
+            (RunMode::Latest, [.., run]) => vec![View::from_run(run)],
+            (RunMode::Separate, runs) => runs
+                .iter()
+                .enumerate()
+                .map(|(idx, run)| View {
+                    name: (idx + 1).to_string(),
+                    anchor_prefix: format!("run-{}-", idx + 1),
+                    title_suffix: format!(" (run {})", idx + 1),
+                    ..View::from_run(run)
+                })
+                .collect(),
+        }
+    }
+
+    /// Render the report, returning the overall outcome.
+    ///
+    /// Otherwise, the report gets rendered when the processor is dropped.
+    pub fn finish(mut self) -> Result<Report, Error> {
+        self.finished = true;
+        Ok(self.render()?)
+    }
+
+    /// Render the report, returning the overall outcome and the output, like a buffer.
+    pub fn finish_output(mut self) -> Result<(Report, W), Error>
+    where
+        W: Default,
+    {
+        self.finished = true;
+        let report = self.render()?;
+        Ok((report, std::mem::take(&mut self.write.write)))
+    }
+
+    fn render(&mut self) -> anyhow::Result<Report> {
+        self.ignored.warn();
+
+        #[cfg(feature = "store")]
+        if let (true, Some(store)) = (self.options.duration_delta, &self.options.store) {
+            self.averages = Some(store.average_durations(self.options.trend_runs.max(1))?);
+        }
+
+        let mut runs = std::mem::take(&mut self.run).split();
+        runs.iter_mut().for_each(Run::classify);
+        let platforms;
+        let mut views: Vec<View> = match self.options.aggregate {
+            true => {
+                platforms = split_by_label(runs);
+                platforms
+                    .iter()
+                    .map(|(label, runs)| View {
+                        name: label.to_string(),
+                        title_suffix: format!(" ({})", label),
+                        ..View::merged(runs)
+                    })
+                    .collect()
+            }
+            false => self.make_views(&runs),
+        };
+        views.iter_mut().for_each(View::cache_anchors);
+
+        match self.options.format {
+            Format::Markdown if self.options.verify => {
+                let markdown = self.capture(|processor| processor.render_markdown(&views))?;
+                self.write.write_all(markdown.as_bytes())?;
+                self.verify(&markdown)?;
+            }
+            Format::Markdown => self.render_markdown(&views)?,
+            Format::Comment => self.render_comment(&views)?,
+            Format::Json => self.render_json(&views)?,
+            Format::Html => {
+                let report = self.make_model(&views)?;
+                html::render(
+                    &report,
+                    self.options.lang,
+                    &self.options.custom_labels,
+                    self.options.theme,
+                    &mut self.write,
+                )?;
+            }
+            Format::Pdf => {
+                let report = self.make_model(&views)?;
+                let mut document = vec![];
+                html::render(
+                    &report,
+                    self.options.lang,
+                    &self.options.custom_labels,
+                    Theme::Light,
+                    &mut document,
+                )?;
+                pdf::convert(&self.options.pdf_converter, &document, &mut self.write)?;
+            }
+        }
+
+        if self.options.embed_records
+            && matches!(self.options.format, Format::Markdown | Format::Comment)
+        {
+            self.render_records()?;
+        }
+
+        if let Some(output) = self.options.summary_output.clone() {
+            let summary = self.capture(|processor| processor.render_summary(&views))?;
+            match self.options.summary_append {
+                true => output::append(&output)?,
+                false => output::open(&output, None)?,
+            }
+            .write_all(summary.as_bytes())?;
+        }
+
+        #[cfg(feature = "store")]
+        if let Some(store) = &self.options.store {
+            match self.interrupted {
+                true => log::warn!("Not storing the partial results"),
+                false => store.insert(&self.make_model(&views)?)?,
+            }
+        }
+
+        if !self.options.notifiers.is_empty() {
+            match self.interrupted {
+                true => log::warn!("Not sending the partial results"),
+                false => self.notify(&views)?,
+            }
+        }
+
+        Ok(Report {
+            outcome: overall_outcome(&views),
+            over_budget: views
+                .iter()
+                .any(|view| !self.exceeded_budgets(view).is_empty()),
+        })
+    }
+
+    /// The budgets exceeded by a view, with the actual duration
+    fn exceeded_budgets(&self, view: &View) -> Vec<(Budget, Duration)> {
+        self.options
+            .budgets
+            .iter()
+            .filter_map(|budget| {
+                let took = match budget.scope {
+                    Scope::Total => view.summary?.exec_time,
+                    Scope::Kind(kind) => view
+                        .suites
+                        .iter()
+                        .filter(|suite| suite.kind == kind)
+                        .map(|suite| suite.summary().exec_time)
+                        .sum(),
+                };
+                (took > budget.limit).then_some((*budget, took))
+            })
+            .collect()
+    }
+
+    fn render_markdown(&mut self, views: &[View]) -> anyhow::Result<()> {
+        if views.iter().any(|view| view.summary.is_some()) {
+            self.write_header(views)?;
+        }
+
+        if !self.options.summary {
+            writeln!(self.write, "<!--more-->")?;
+            writeln!(self.write)?;
+
+            if self.options.flavor == Flavor::AzureDevops {
+                writeln!(self.write, "[[_TOC_]]")?;
+                writeln!(self.write)?;
+            }
+
+            self.write_addons(Position::BeforeIndex)?;
+
+            if self.options.aggregate {
+                self.render_aggregate(views)?;
+            } else {
+                for (idx, view) in views.iter().enumerate() {
+                    if idx > 0 {
+                        writeln!(self.write)?;
+                        writeln!(self.write)?;
+                    }
+                    self.render_index(view)?;
+                    self.render_ignored(view)?;
+                    self.render_execution_order(view)?;
+                    self.render_details(view)?;
+                }
+            }
+
+            self.write_addons(Position::Footer)?;
+        }
+
+        if self.options.footer {
+            self.write_footer()?;
+        }
+
+        Ok(())
+    }
+
+    /// Render only the summary, without the front matter, for the summary output
+    fn render_summary(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let summary = std::mem::replace(&mut self.options.summary, true);
+        let disable_front_matter = std::mem::replace(&mut self.options.disable_front_matter, true);
+        let result = self.render_markdown(views);
+        self.options.summary = summary;
+        self.options.disable_front_matter = disable_front_matter;
+        result
+    }
+
+    /// Log the problems of the generated report, failing if there are any.
+    fn verify(&self, markdown: &str) -> anyhow::Result<()> {
+        let problems = verify::verify(markdown, self.options.flavor);
+        if problems.is_empty() {
+            return Ok(());
+        }
+        for problem in &problems {
+            log::warn!("{}", problem);
+        }
+        anyhow::bail!(
+            "The report failed the verification, with {} problems",
+            problems.len()
+        )
+    }
+
+    fn write_footer(&mut self) -> anyhow::Result<()> {
+        let l = self.labels();
+        let elapsed = Duration::from_millis(self.started.elapsed().as_millis() as u64);
+        writeln!(self.write)?;
+        writeln!(self.write, "---")?;
+        writeln!(self.write)?;
+        writeln!(
+            self.write,
+            "**{}:** [{}]({}) {} · **{}:** {} · **{}:** {}",
+            l.generated_by,
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_REPOSITORY"),
+            env!("CARGO_PKG_VERSION"),
+            l.generated_at,
+            Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+            l.processing_time,
+            humantime::format_duration(elapsed),
+        )?;
+        Ok(())
+    }
+
+    /// Render a table of all tests, with the result of each platform, followed by the failures.
+    ///
+    /// Failures with identical output on multiple platforms are only rendered once.
+    fn render_aggregate(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let mut names = Vec::<&str>::new();
+        let mut seen = HashSet::<&str>::new();
+        let mut results = HashMap::<(&str, usize), &Recorded>::new();
+        for (idx, view) in views.iter().enumerate() {
+            for test in &view.tests {
+                if let Some(name) = finished_name(&test.event) {
+                    if seen.insert(name) {
+                        names.push(name);
+                    }
+                    results.insert((name, idx), test);
+                }
+            }
+        }
+
+        // group failures of the same test by their output
+        let mut failures = Vec::<Failure>::new();
+        let mut anchors = HashMap::<(&str, usize), String>::new();
+        for name in &names {
+            let mut groups = Vec::<Failure>::new();
+            for (idx, view) in views.iter().enumerate() {
+                let test = match results.get(&(*name, idx)) {
+                    Some(test) if matches!(test.event, test::Event::Failed { .. }) => test,
+                    _ => continue,
+                };
+                let output = self.output(test)?.into_owned();
+                let group = match groups.iter_mut().find(|group| group.output == output) {
+                    Some(group) => group,
+                    None => {
+                        groups.push(Failure {
+                            name: self.display_name(name).into_owned(),
+                            anchor: self.make_test_anchor(view, test, name),
+                            platforms: vec![],
+                            exec_times: vec![],
+                            output,
+                        });
+                        groups.last_mut().unwrap()
+                    }
+                };
+                if let test::Event::Failed { exec_time, .. } = test.event {
+                    group.exec_times.push(exec_time);
+                }
+                group.platforms.push(view.name.clone());
+                anchors.insert((*name, idx), group.anchor.clone());
+            }
+            failures.extend(groups);
+        }
+
+        writeln!(self.write, "# Matrix")?;
+        writeln!(self.write)?;
+        write!(self.write, "| {} |", self.labels().name)?;
+        for view in views {
+            write!(self.write, " {} |", view.name)?;
+        }
+        writeln!(self.write)?;
+        write!(self.write, "| ---- |")?;
+        for _ in views {
+            write!(self.write, " --- |")?;
+        }
+        writeln!(self.write)?;
+
+        for name in &names {
+            let display_name = self.display_name(name);
+            match self.truncate_name(&display_name) {
+                Some(truncated) => write!(
+                    self.write,
+                    r#"| <span title="{}">{}</span> |"#,
+                    escape(&display_name, Html),
+                    truncated
+                )?,
+                None => write!(self.write, "| {} |", display_name)?,
+            }
+            for idx in 0..views.len() {
+                match (results.get(&(*name, idx)), anchors.get(&(*name, idx))) {
+                    (Some(_), Some(anchor)) => write!(self.write, " [❌](#{}) |", anchor)?,
+                    (Some(_), None) => write!(self.write, " ✅ |")?,
+                    (None, _) => write!(self.write, " |")?,
+                }
+            }
+            writeln!(self.write)?;
+        }
+
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(self.write)?;
+        writeln!(self.write)?;
+        writeln!(self.write, "# Failures")?;
+
+        for failure in &failures {
+            writeln!(self.write)?;
+            writeln!(
+                self.write,
+                "## ❌ {}{}",
+                failure.name,
+                self.make_anchor_tag(&failure.anchor)
+            )?;
+            writeln!(self.write)?;
+            writeln!(
+                self.write,
+                "**Platforms**: {}",
+                failure.platforms.join(", ")
+            )?;
+            writeln!(self.write)?;
+            let durations = failure
+                .exec_times
+                .iter()
+                .map(|exec_time| self.format_duration(exec_time))
+                .collect::<Vec<_>>();
+            writeln!(
+                self.write,
+                "**{}**: {}",
+                self.labels().duration,
+                durations.join(", ")
+            )?;
+            self.render_output(&failure.output)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hand the results to all notifiers
+    fn notify(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let comment = self.capture(|processor| processor.render_comment(views))?;
+        let report = self.make_model(views)?;
+        let notification = Notification {
+            comment: &comment,
+            report: &report,
+        };
+        for notifier in &self.options.notifiers {
+            notifier.notify(&notification)?;
+        }
+        Ok(())
+    }
+
+    /// Capture what gets written, instead of writing it to the output
+    fn capture<F>(&mut self, f: F) -> anyhow::Result<String>
+    where
+        F: FnOnce(&mut Self) -> anyhow::Result<()>,
+    {
+        self.write.captured = Some(vec![]);
+        let result = f(self);
+        let captured = self.write.captured.take().unwrap_or_default();
+        result?;
+        Ok(String::from_utf8_lossy(&captured).into_owned())
+    }
+
+    /// Render the views as a JSON document, following [`model::schema`]
+    fn render_json(&mut self, views: &[View]) -> anyhow::Result<()> {
+        let report = self.make_model(views)?;
+        serde_json::to_writer_pretty(&mut self.write, &report)?;
+        writeln!(self.write)?;
+
+        Ok(())
+    }
+
+    /// Create the model of the results, as used by the JSON report and the store
+    fn make_model(&self, views: &[View]) -> anyhow::Result<model::Report> {
+        let mut runs = vec![];
+        for view in views {
+            let mut tests = vec![];
+            for test in &view.tests {
+                let (name, outcome, duration, message) = match &test.event {
+                    test::Event::Started { .. } => continue,
+                    test::Event::Ok {
+                        name, exec_time, ..
+                    } => (name, "ok", Some(exec_time), None),
+                    test::Event::Failed {
+                        name,
+                        exec_time,
+                        message,
+                        ..
+                    } => (name, "failed", Some(exec_time), message.clone()),
+                    test::Event::Ignored { name, message } => {
+                        (name, "ignored", None, message.clone())
+                    }
+                };
+                let output = self.output(test)?;
+                tests.push(model::Test {
+                    name: name.clone(),
+                    outcome,
+                    duration: duration.map(Duration::as_secs_f64),
+                    label: test.label.as_deref().map(String::from),
+                    message,
+                    output: (!output.is_empty()).then(|| output.into_owned()),
+                });
+            }
+
+            runs.push(model::Run {
+                name: (!view.name.is_empty()).then(|| view.name.clone()),
+                summary: view
+                    .summary
+                    .map(|summary| summary.to_model(view.test_count)),
+                suites: view
+                    .suites
+                    .iter()
+                    .map(|suite| model::Suite {
+                        target: suite
+                            .invocation
+                            .as_ref()
+                            .and_then(|invocation| invocation.target.clone()),
+                        kind: suite.kind.name(),
+                        label: suite.label.as_deref().map(String::from),
+                        summary: suite.summary().to_model(suite.test_count),
+                    })
+                    .collect(),
+                tests,
+            });
+        }
+
+        Ok(model::Report {
+            version: model::VERSION,
+            outcome: overall_outcome(views).name(),
+            labels: self.options.labels.clone(),
+            partial: self.interrupted,
+            runs,
+        })
+    }
+
+    /// Render the processed records into a comment, so that they can be read back later
+    fn render_records(&mut self) -> anyhow::Result<()> {
+        writeln!(self.write)?;
+        writeln!(self.write, "{}", RECORDS_START)?;
+        for record in &self.records {
+            // "-->" can only occur inside JSON strings, so we can escape it
+            writeln!(self.write, "{}", record.replace("-->", "--\\u003e"))?;
+        }
+        writeln!(self.write, "{}", RECORDS_END)?;
+
+        Ok(())
+    }
+
+    /// Make a readable duration from the provided one
+    fn format_duration(&self, duration: &Duration) -> String {
+        if self.options.precise {
+            return self
+                .options
+                .lang
+                .localize_number(&format!("{:?}", duration));
+        }
+        let duration = duration.as_secs();
+        humantime::format_duration(Duration::from_secs(duration)).to_string()
+    }
+}
+
+impl<W> Drop for Processor<W>
+where
+    W: Write,
+{
+    fn drop(&mut self) {
+        if !self.finished {
+            self.render().expect("Render report");
+        }
+    }
+}
+
+const RECORDS_START: &str = "<!-- markdown-test-report:records";
+const RECORDS_END: &str = "-->";
+/// Marks a change of the label in the embedded records.
+const LABEL_PREFIX: &str = "@label";
+
+/// Render the report of an input, which is in memory as a whole, like the one passed to a binding.
+pub fn render_str(input: &str, options: ProcessOptions) -> Result<String, Error> {
+    let mut processor = Processor::new(Vec::new(), options);
+    input.lines().try_for_each(|line| processor.line(line))?;
+    let (_, output) = processor.finish_output()?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Extract the records which were embedded into a report
+pub fn embedded_records(report: &str) -> Option<Vec<&str>> {
+    let (_, records) = report.split_once(RECORDS_START)?;
+    let (records, _) = records.split_once(RECORDS_END)?;
+    Some(
+        records
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect(),
+    )
+}
+
+/// Split the runs by the label of their suites, in the order of first appearance
+fn split_by_label(runs: Vec<Run>) -> Vec<(Arc<str>, Vec<Run>)> {
+    let mut result = Vec::<(Arc<str>, Vec<Run>)>::new();
+
+    for run in runs {
+        let mut split = Vec::<(Arc<str>, Run)>::new();
+        for suite in run.suites {
+            let label = suite.label.clone().unwrap_or_else(|| Arc::from(""));
+            match split.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, run)) => run.suites.push(suite),
+                None => split.push((
+                    label,
+                    Run {
+                        suites: vec![suite],
+                    },
+                )),
+            }
+        }
+
+        for (label, run) in split {
+            match result.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, runs)) => runs.push(run),
+                None => result.push((label, vec![run])),
+            }
+        }
+    }
+
+    result
+}
+
+/// The outcome of all views combined
+fn overall_outcome(views: &[View]) -> Outcome {
+    views
+        .iter()
+        .filter_map(|view| view.summary)
+        .map(|summary| summary.outcome)
+        .max()
+        .unwrap_or(Outcome::Ok)
+}
+
+/// Expand the placeholders of variables in a template, like `{BUILD_ID}`, `None` if one is unset
+fn expand_template(template: &str, var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let placeholder = Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid pattern");
+    let mut unset = false;
+    let expanded = placeholder.replace_all(template, |captures: &regex::Captures| {
+        var(&captures[1]).unwrap_or_else(|| {
+            unset = true;
+            String::new()
+        })
+    });
+    (!unset).then(|| expanded.into_owned())
+}
+
+/// When a finished test started, if reported
+fn start_time(test: &test::Event) -> Option<DateTime<FixedOffset>> {
+    match test {
+        test::Event::Started { .. } | test::Event::Ignored { .. } => None,
+        test::Event::Ok { start_time, .. } | test::Event::Failed { start_time, .. } => *start_time,
+    }
+}
+
+/// Format a timestamp in UTC, with milliseconds, to be compared with other logs
+fn format_timestamp(timestamp: &DateTime<FixedOffset>) -> String {
+    timestamp
+        .with_timezone(&Utc)
+        .format("%Y-%m-%d %H:%M:%S%.3f UTC")
+        .to_string()
+}
+
+/// Get the name of a test, if it finished
+fn finished_name(test: &test::Event) -> Option<&str> {
+    match test {
+        test::Event::Started { .. } | test::Event::Ignored { .. } => None,
+        test::Event::Ok { name, .. } | test::Event::Failed { name, .. } => Some(name),
+    }
+}
+
+/// Split the kind of a test of a custom harness, like `[tidy] check_toph` of libtest-mimic
+fn split_kind(name: &str) -> Option<(&str, &str)> {
+    let (kind, name) = name.strip_prefix('[')?.split_once("] ")?;
+    (!kind.is_empty() && !name.is_empty()).then_some((kind, name))
+}
+
+/// Get the name and reason of a test, if it was ignored
+fn ignored_test(test: &test::Event) -> Option<(&str, Option<&str>)> {
+    match test {
+        test::Event::Ignored { name, message } => Some((name, message.as_deref())),
+        _ => None,
+    }
+}
+
+/// A test, or all cases of a parameterized test
+enum Item<'t> {
+    Test(&'t Recorded),
+    Cases {
+        parent: &'t str,
+        tests: Vec<&'t Recorded>,
+    },
+}
+
+/// Split the name of a parameterized test case, generated by rstest or test-case, into the
+/// name of the parent test and the case
+fn split_case(name: &str) -> Option<(&str, &str)> {
+    let (parent, case) = name.rsplit_once("::")?;
+    // rstest: case_1, case_2_description
+    let rstest = case
+        .strip_prefix("case_")
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()));
+    // test-case: _description_expects_result
+    let test_case = case.len() > 1 && case.starts_with('_');
+    (rstest || test_case).then_some((parent, case))
+}
+
+/// Group the cases of parameterized tests, at the position of their first case
+fn group_cases<'t>(tests: Vec<&'t Recorded>) -> Vec<Item<'t>> {
+    let mut items = Vec::<Item>::new();
+    let mut parents = HashMap::<(Option<&str>, &str), usize>::new();
+
+    for test in tests {
+        let parent = finished_name(&test.event)
+            .and_then(split_case)
+            .map(|(parent, _)| parent);
+        let Some(parent) = parent else {
+            items.push(Item::Test(test));
+            continue;
+        };
+
+        match parents.get(&(test.label.as_deref(), parent)) {
+            Some(idx) => {
+                if let Item::Cases { tests, .. } = &mut items[*idx] {
+                    tests.push(test);
+                }
+            }
+            None => {
+                parents.insert((test.label.as_deref(), parent), items.len());
+                items.push(Item::Cases {
+                    parent,
+                    tests: vec![test],
+                });
+            }
+        }
+    }
+
+    // a single case isn't worth a group
+    items
+        .into_iter()
+        .map(|item| match item {
+            Item::Cases { tests, .. } if tests.len() == 1 => Item::Test(tests[0]),
+            item => item,
+        })
+        .collect()
+}
+
+fn cases_outcome(tests: &[&Recorded]) -> &'static str {
+    match tests
+        .iter()
+        .any(|test| matches!(test.event, test::Event::Failed { .. }))
+    {
+        true => "❌",
+        false => "✅",
+    }
+}
+
+fn cases_duration(tests: &[&Recorded]) -> Duration {
+    tests
+        .iter()
+        .filter_map(|test| match &test.event {
+            test::Event::Ok { exec_time, .. } | test::Event::Failed { exec_time, .. } => {
+                Some(*exec_time)
+            }
+            test::Event::Started { .. } | test::Event::Ignored { .. } => None,
+        })
+        .sum()
+}
+
+/// Group the tests by the kind of their suite, unit tests first
+fn group_kinds<'t>(tests: &[&'t Recorded]) -> Vec<(SuiteKind, Vec<&'t Recorded>)> {
+    let mut kinds = Vec::<(SuiteKind, Vec<&Recorded>)>::new();
+    for test in tests {
+        match kinds.iter_mut().find(|(kind, _)| *kind == test.kind) {
+            Some((_, tests)) => tests.push(test),
+            None => kinds.push((test.kind, vec![test])),
+        }
+    }
+    kinds.sort_by_key(|(kind, _)| *kind);
+    kinds
+}
+
+/// Split the doc-tests from all other tests, grouping them by their file
+fn group_doc_tests<'t>(
+    tests: &[&'t Recorded],
+) -> (Vec<&'t Recorded>, Vec<(&'t str, Vec<&'t Recorded>)>) {
+    let mut other = vec![];
+    let mut files = Vec::<(&str, Vec<&Recorded>)>::new();
+
+    for test in tests {
+        let file = finished_name(&test.event)
+            .and_then(DocTest::parse)
+            .map(|doc_test| doc_test.file);
+        match file {
+            Some(file) => match files.iter_mut().find(|(f, _)| *f == file) {
+                Some((_, tests)) => tests.push(test),
+                None => files.push((file, vec![test])),
+            },
+            None => other.push(*test),
+        }
+    }
+
+    (other, files)
+}
+
+/// Check if a character is binary content, which may not be rendered as-is
+fn is_binary(c: char) -> bool {
+    (c.is_control() && !matches!(c, '\n' | '\r' | '\t')) || c == char::REPLACEMENT_CHARACTER
+}
+
+/// Remove the escape sequences of terminals, like the colors of panic messages.
+fn strip_ansi(output: &str) -> Cow<'_, str> {
+    if !output.contains('\x1b') {
+        return Cow::Borrowed(output);
+    }
+
+    let mut result = String::with_capacity(output.len());
+    let mut chars = output.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek() {
+            // CSI, like colors: parameters, ended by a character of `@` to `~`
+            Some('[') => {
+                chars.next();
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC, like links: ended by BEL or ST
+            Some(']') => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            Some(c) if ('@'..='_').contains(c) => {
+                chars.next();
+            }
+            // not a sequence, which is binary content
+            _ => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// Make captured output safe to render.
+///
+/// Escape sequences of terminals are removed. Mostly textual output gets non-printable characters
+/// replaced, mostly binary output is converted into a hex dump. If the output was changed, beyond
+/// removing escape sequences, a note is returned as well.
+fn sanitize_output(output: &str) -> (Cow<'_, str>, Option<&'static str>) {
+    let output = strip_ansi(output);
+    let total = output.chars().count();
+    let binary = output.chars().filter(|c| is_binary(*c)).count();
+
+    if binary == 0 {
+        (output, None)
+    } else if binary * 10 > total {
+        // the output was read as text, invalid UTF-8 is already replaced
+        let note = match output.contains(char::REPLACEMENT_CHARACTER) {
+            true => "Output contained binary data, it is shown as hex dump. Invalid UTF-8 was replaced before, shown as `ef bf bd`.",
+            false => "Output contained binary data, it is shown as hex dump.",
+        };
+        (Cow::Owned(hex_dump(output.as_bytes())), Some(note))
+    } else {
+        (
+            Cow::Owned(
+                output
+                    .chars()
+                    .map(|c| {
+                        if is_binary(c) {
+                            char::REPLACEMENT_CHARACTER
+                        } else {
+                            c
+                        }
+                    })
+                    .collect(),
+            ),
+            Some("Output contained binary data, non-printable characters were replaced."),
+        )
+    }
+}
+
+/// Create a hex dump, 16 bytes per line
+fn hex_dump(data: &[u8]) -> String {
+    let mut s = String::new();
+    for (idx, chunk) in data.chunks(16).enumerate() {
+        let hex = chunk
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = chunk
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    *b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        s.push_str(&format!("{:08x}  {:<47}  |{}|\n", idx * 16, hex, ascii));
+    }
+    s
+}
+
+/// Render values as a sparkline, scaled between the lowest and highest value
+#[cfg(feature = "store")]
+fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| match max - min {
+            range if range > 0.0 => {
+                SPARKS[((value - min) / range * (SPARKS.len() - 1) as f64).round() as usize]
+            }
+            _ => SPARKS[SPARKS.len() / 2],
+        })
+        .collect()
+}
+
+/// Create an anchor the way Azure DevOps does for headings, dropping everything which isn't part
+/// of a word, like emojis.
+pub fn make_azure_anchor(heading: &str) -> String {
+    let mut s = String::with_capacity(heading.len());
+    for c in heading.chars() {
+        if c == ' ' {
+            s.push('-');
+        } else if c.is_alphanumeric() || c == '-' || c == '_' {
+            s.extend(c.to_lowercase());
+        }
+    }
+    s.trim_matches('-').to_string()
+}
+
+/// The start of the anchors of the tests of a label: the prefix and the label
+fn label_anchor(prefix: &str, label: Option<&str>) -> String {
+    let mut anchor = prefix.to_string();
+    if let Some(label) = label {
+        push_anchor(&mut anchor, label);
+        anchor.push('-');
+    }
+    anchor
+}
+
+/// Append the anchor of a link to a string
+fn push_anchor(s: &mut String, link: &str) {
     let mut was_dash = false;
     for c in link.chars() {
         if c == '_' {
@@ -382,20 +3277,1108 @@ fn make_anchor(link: &str) -> String {
                 was_dash = true;
                 s.push('-');
             }
-        } else if c.is_alphanumeric() {
-            s.push(c);
-            was_dash = false;
+        } else if c.is_alphanumeric() {
+            s.push(c);
+            was_dash = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN: &str = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "tests::a" }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.1 }
+{ "type": "test", "event": "started", "name": "tests::b" }
+{ "type": "test", "name": "tests::b", "event": "failed", "exec_time": 0.2, "stdout": "boom" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.3 }
+"#;
+
+    const RETRY: &str = r#"{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "tests::b", "event": "ok", "exec_time": 0.2 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 1, "exec_time": 0.2 }
+"#;
+
+    fn options() -> ProcessOptions {
+        ProcessOptions {
+            disable_front_matter: true,
+            addons: vec![],
+            summary: false,
+            summary_output: None,
+            summary_append: false,
+            precise: false,
+            timestamps: false,
+            timeline: vec![],
+            runs: RunMode::default(),
+            spill: false,
+            embed_records: false,
+            format: Format::default(),
+            flavor: Flavor::default(),
+            footer: false,
+            verify: false,
+            strict: false,
+            report_url: None,
+            job_link_template: None,
+            labels: vec![],
+            aggregate: false,
+            source_url: None,
+            renames: vec![],
+            max_name_length: None,
+            lang: Lang::default(),
+            custom_labels: CustomLabels::default(),
+            theme: Theme::default(),
+            pdf_converter: pdf::DEFAULT_CONVERTER.to_string(),
+            budgets: vec![],
+            #[cfg(feature = "store")]
+            store: None,
+            trend_runs: 0,
+            duration_delta: false,
+            notifiers: vec![],
+            observers: vec![],
+            redactor: Redactor::default(),
+        }
+    }
+
+    fn render(input: &str, options: ProcessOptions) -> String {
+        let mut output = Vec::new();
+        {
+            let mut processor = Processor::new(&mut output, options);
+            for line in input.lines() {
+                processor.line(line).unwrap();
+            }
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_sanitize_output() {
+        assert_eq!(sanitize_output("foo\nbar\t"), ("foo\nbar\t".into(), None));
+
+        let (output, note) = sanitize_output("some text with a \0 byte");
+        assert_eq!(output, "some text with a \u{fffd} byte");
+        assert!(note.is_some());
+
+        let (output, note) = sanitize_output("\0\x01\x02abc");
+        assert_eq!(
+            output,
+            "00000000  00 01 02 61 62 63                                |...abc|\n"
+        );
+        assert_eq!(
+            note,
+            Some("Output contained binary data, it is shown as hex dump.")
+        );
+        let (_, note) = sanitize_output("\0\u{fffd}\x02abc");
+        assert!(note.unwrap().contains("`ef bf bd`"));
+
+        // colors of terminals aren't binary
+        let colored = "thread 'main' panicked at \x1b[1m\x1b[31msrc/lib.rs\x1b[0m:\n\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07";
+        assert_eq!(
+            sanitize_output(colored),
+            ("thread 'main' panicked at src/lib.rs:\nlink".into(), None)
+        );
+        let (output, note) = sanitize_output("a lone \x1b escape");
+        assert_eq!(output, "a lone \u{fffd} escape");
+        assert!(note.is_some());
+    }
+
+    #[test]
+    fn test_single_run() {
+        let output = render(RUN, options());
+        assert!(output.contains("| ❌ | 2 | 1 | 1 | 0 | 0 | 0s |"));
+        assert!(output.contains("# Index\n"));
+        assert!(output.contains("[tests::b](#testsb)"));
+        assert!(!output.contains("Count mismatch"));
+    }
+
+    #[test]
+    fn test_incomplete() {
+        let truncated = RUN.lines().take(4).collect::<Vec<_>>().join("\n");
+        let output = render(&truncated, options());
+        assert!(output.contains("| ⚠️ | 2 | 1 | 0 | 0 | 0 | 0s |"));
+        assert!(output.contains("**Incomplete:**"));
+        assert!(output
+            .contains("**Count mismatch:** 2 tests were announced, but 1 results were reported."));
+    }
+
+    #[test]
+    fn test_spill() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                spill: true,
+                ..options()
+            },
+        );
+        assert_eq!(output, render(RUN, options()));
+        assert!(output.contains("<pre>\nboom\n</pre>"));
+    }
+
+    #[test]
+    fn test_embed_records() {
+        let input = r#"{ "type": "test", "name": "a", "event": "failed", "exec_time": 0.1, "stdout": "<!-- -->" }"#;
+        let output = render(
+            input,
+            ProcessOptions {
+                embed_records: true,
+                ..options()
+            },
+        );
+        assert_eq!(
+            embedded_records(&output),
+            Some(vec![input.replace("-->", "--\\u003e").as_str()])
+        );
+
+        // the escaped records still parse to the same output
+        assert_eq!(
+            render(embedded_records(&output).unwrap()[0], options()),
+            render(input, options())
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut output = Vec::new();
+        {
+            let mut processor = Processor::new(&mut output, options());
+            for label in ["linux", "macos"] {
+                processor.set_label(Some(label.to_string()));
+                for line in RUN.lines() {
+                    processor.line(line).unwrap();
+                }
+            }
         }
+        let output = String::from_utf8(output).unwrap();
+
+        // labeled inputs are not detected as runs
+        assert!(output.contains("| ❌ | 4 | 2 | 2 | 0 | 0 | 0s |"));
+        assert!(output.contains("| Name | Job | Result | Duration |"));
+        assert!(output.contains("| [tests::b](#macos-testsb) | macos | ❌ | 0s |"));
+        assert!(output.contains(r#"## ❌ tests::b<a id="linux-testsb"></a>"#));
     }
-    s
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_comment() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                format: Format::Comment,
+                report_url: Some("https://example.com/report".into()),
+                ..options()
+            },
+        );
+        assert!(output.starts_with("### ❌ 1 test failed\n"));
+        assert!(output.contains("| ❌ | 2 | 1 | 1 | 0 | 0 | 0s |"));
+        assert!(output.contains("<summary>❌ <code>tests::b</code></summary>"));
+        assert!(output.contains("<pre>\nboom\n</pre>"));
+        assert!(output.contains("[Full report](https://example.com/report)"));
+        assert!(!output.contains("tests::a"));
+    }
+
+    #[derive(Debug)]
+    struct Marker(&'static str);
+
+    impl Addon for Marker {
+        fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+            writeln!(write, "{}", self.0)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_positions() {
+        let addons = [
+            (Position::Footer, "footer"),
+            (Position::BeforeIndex, "before-index"),
+            (Position::AfterSummary, "after-summary"),
+            (Position::Header, "header"),
+        ];
+        let output = render(
+            RUN,
+            ProcessOptions {
+                addons: addons
+                    .into_iter()
+                    .map(|(position, name)| (position, Box::new(Marker(name)) as Box<dyn Addon>))
+                    .collect(),
+                ..options()
+            },
+        );
+        let position = |text: &str| output.find(text).unwrap();
+        assert!(position("header\n") < position("| ❌ | 2 |"));
+        assert!(position("| ❌ | 2 |") < position("after-summary\n"));
+        assert!(position("after-summary\n") < position("<!--more-->"));
+        assert!(position("<!--more-->") < position("before-index\n"));
+        assert!(position("before-index\n") < position("# Index"));
+        assert!(output.ends_with("footer\n"));
+    }
+
+    #[test]
+    fn test_verify() {
+        let verify = |input: &str, flavor: Flavor| {
+            let mut output = Vec::new();
+            let mut processor = Processor::new(
+                &mut output,
+                ProcessOptions {
+                    verify: true,
+                    footer: true,
+                    flavor,
+                    ..options()
+                },
+            );
+            input.lines().for_each(|line| processor.line(line).unwrap());
+            processor.finish().map(|_| ())
+        };
+        let twice = format!("{}\n{}", RUN, RUN);
+        verify(RUN, Flavor::Github).unwrap();
+        verify(&twice, Flavor::Github).unwrap();
+        verify(RUN, Flavor::AzureDevops).unwrap();
+        // in Azure DevOps, the headings of the runs can't be told apart
+        assert!(verify(&twice, Flavor::AzureDevops).is_err());
+    }
+
+    #[test]
+    fn test_footer() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                footer: true,
+                lang: Lang::De,
+                ..options()
+            },
+        );
+        let footer = output.rsplit("\n---\n\n").next().unwrap();
+        assert!(footer.starts_with(&format!(
+            "**Erstellt mit:** [markdown-test-report](https://github.com/ctron/markdown-test-report) {} · **Erstellt am:** ",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(footer.contains(" UTC · **Verarbeitungszeit:** "));
+        assert!(!render(RUN, options()).contains("\n---\n"));
+    }
+
+    #[derive(Debug, Default)]
+    struct Recorder(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl Notifier for Recorder {
+        fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(notification.comment.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_notify() {
+        let recorder = Recorder::default();
+        let comments = recorder.0.clone();
+        let output = render(
+            RUN,
+            ProcessOptions {
+                notifiers: vec![Box::new(recorder)],
+                ..options()
+            },
+        );
+        let comments = comments.lock().unwrap();
+        assert_eq!(comments.len(), 1);
+        assert!(comments[0].starts_with("### ❌ 1 test failed\n"));
+        // the comment is not part of the report
+        assert!(!output.contains("1 test failed"));
+    }
+
+    #[derive(Debug, Default)]
+    struct Counter(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl Observer for Counter {
+        fn on_test(&mut self, test: &TestResult) {
+            let name = match test.event {
+                test::Event::Ok { name, .. } | test::Event::Failed { name, .. } => name,
+                _ => "other",
+            };
+            self.0.lock().unwrap().push(name.to_string());
+        }
+
+        fn on_suite(&mut self, suite: &SuiteResult) {
+            self.0.lock().unwrap().push(format!(
+                "{} {}/{} {}",
+                suite.outcome,
+                suite.passed,
+                suite.failed,
+                suite.label.unwrap_or_default()
+            ));
+        }
+    }
+
+    #[test]
+    fn test_observe() {
+        let counter = Counter::default();
+        let events = counter.0.clone();
+        let mut processor = Processor::new(
+            Vec::new(),
+            ProcessOptions {
+                observers: vec![Box::new(counter)],
+                ..options()
+            },
+        );
+        processor.set_label(Some("linux".into()));
+        for line in RUN.lines() {
+            processor.line(line).unwrap();
+        }
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["tests::a", "tests::b", "❌ 1/1 linux"]
+        );
+    }
+
+    #[test]
+    fn test_redact() {
+        let input = RUN.replace(r#""stdout": "boom""#, r#""stdout": "Bearer s3cr3t""#);
+        let output = render(
+            &input,
+            ProcessOptions {
+                embed_records: true,
+                redactor: Redactor::new(vec![], true),
+                ..options()
+            },
+        );
+        assert!(output.contains("Bearer [REDACTED]"));
+        assert!(!output.contains("s3cr3t"));
+    }
+
+    #[test]
+    fn test_azure_devops() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                flavor: Flavor::AzureDevops,
+                ..options()
+            },
+        );
+        assert!(output.contains("<!--more-->\n\n[[_TOC_]]\n"));
+        assert!(output.contains("[tests::b](#testsb)"));
+        assert!(output.contains("\n## ❌ tests::b\n"));
+        assert!(output.contains("**Test output**:\n\n```text\nboom\n```\n"));
+        assert!(!output.contains("<details>") && !output.contains("<a id="));
+
+        assert_eq!(make_azure_anchor("✅ tests::a_b"), "testsa_b");
+        assert_eq!(make_azure_anchor("Ünit Tests"), "ünit-tests");
+    }
+
+    #[test]
+    fn test_json() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                format: Format::Json,
+                embed_records: true,
+                ..options()
+            },
+        );
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["outcome"], "failed");
+        let run = &report["runs"][0];
+        assert_eq!(run["summary"]["total"], 2);
+        assert_eq!(run["summary"]["failed"], 1);
+        assert_eq!(run["suites"][0]["kind"], "unit");
+        assert_eq!(run["tests"].as_array().unwrap().len(), 2);
+        assert_eq!(run["tests"][0]["output"], serde_json::Value::Null);
+        assert_eq!(
+            run["tests"][1],
+            serde_json::json!({
+                "name": "tests::b",
+                "outcome": "failed",
+                "duration": 0.2,
+                "label": null,
+                "message": null,
+                "output": "boom",
+            })
+        );
+    }
+
+    #[test]
+    fn test_invocation() {
+        let input = format!(
+            "     Running `target/debug/deps/foo-1234 --format json --test-threads 1 tests`\n{}",
+            RUN
+        );
+        let output = render(&input, options());
+        assert!(output
+            .contains("|  | `foo-1234` | `tests` | 1 | `--format json --test-threads 1 tests` |"));
+    }
+
+    #[test]
+    fn test_doc_tests() {
+        let input = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "name": "src/lib.rs - foo::bar (line 42)", "event": "ok", "exec_time": 0.1 }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.1 }
+{ "type": "test", "name": "src/lib.rs - (line 1)", "event": "ok", "exec_time": 0.1 }
+{ "type": "suite", "event": "ok", "passed": 3, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.3 }
+"#;
+        let output = render(
+            input,
+            ProcessOptions {
+                source_url: Some("https://github.com/foo/bar/blob/main/".into()),
+                ..options()
+            },
+        );
+        assert!(output.contains(
+            "| [tests::a](#testsa) | ✅ | 0s | \n| **src/lib.rs** | | |\n| [foo::bar (line 42)](#doctest-src-lib-rs-foo-bar-42) | ✅ | 0s | \n| [line 1](#doctest-src-lib-rs-1) |"
+        ));
+        assert!(output.contains(r#"<a id="doctest-src-lib-rs-foo-bar-42"></a>"#));
+        assert!(output.contains(
+            "**Source**: [`src/lib.rs:42`](https://github.com/foo/bar/blob/main/src/lib.rs#L42)"
+        ));
+
+        let output = render(
+            r#"{ "type": "test", "name": "src/lib.rs - foo (line 7) - compile fail", "event": "ok", "exec_time": 0.1 }"#,
+            options(),
+        );
+        assert!(output.contains(
+            "| [foo (line 7)](#doctest-src-lib-rs-foo-7-compile-fail) `compile_fail` | ✅ |"
+        ));
+        assert!(output
+            .contains("**Note**: `compile_fail` – The example is expected to fail to compile"));
+    }
+
+    #[test]
+    fn test_suite_kinds() {
+        let suite = |name: &str| {
+            format!(
+                r#"{{ "type": "suite", "event": "started", "test_count": 1 }}
+{{ "type": "test", "name": "{}", "event": "ok", "exec_time": 0.1 }}
+{{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.1 }}
+"#,
+                name
+            )
+        };
+        let doc = suite("src/lib.rs - foo (line 1)");
+        let input = [suite("tests::a"), suite("b"), doc.clone()].concat();
+        let output = render(&input, options());
+        assert!(output.contains(
+            "| #1 | Unit tests | 1 | 1 | 0 | 0s | 33% |\n| #2 | Integration tests | 1 | 1 | 0 | 0s | 33% |\n| #3 | Doc tests |"
+        ));
+        assert!(output.contains(
+            "| ***Unit tests*** | | |\n| [tests::a](#testsa) | ✅ | 0s | \n| ***Integration tests*** | | |\n| [b](#b) |"
+        ));
+        assert!(output.contains("| ***Doc tests*** | | |\n| **src/lib.rs** | | |\n"));
+
+        // the targets reported by cargo take precedence
+        let input = [
+            "     Running tests/api.rs (target/debug/deps/api-1234)\n",
+            &suite("tests::a"),
+            "     Running unittests src/lib.rs (target/debug/deps/foo-1234)\n",
+            &suite("tests::b"),
+        ]
+        .concat();
+        let output = render(&input, options());
+        assert!(output.contains(
+            "| `tests/api.rs` | Integration tests | 1 | 1 | 0 | 0s | 50% |\n| `unittests src/lib.rs` | Unit tests |"
+        ));
+        assert!(output.contains(
+            "| ***Unit tests*** | | |\n| [tests::b](#testsb) | ✅ | 0s | \n| ***Integration tests*** | | |\n| [tests::a](#testsa) |"
+        ));
+
+        let output = render(&suite("tests::a"), options());
+        assert!(!output.contains("**Suites:**"));
+        assert!(!output.contains("***Unit tests***"));
+    }
+
+    #[test]
+    fn test_wall_clock() {
+        let mut output = Vec::new();
+        {
+            let mut processor = Processor::new(&mut output, options());
+            for line in RUN.lines() {
+                processor.line(line).unwrap();
+            }
+            let now = Instant::now();
+            processor.received = Some((now, now + Duration::from_secs(90)));
+        }
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("**Total test time:** 0s · **Wall clock:** 1m 30s\n"));
+
+        assert!(!render(RUN, options()).contains("**Wall clock:**"));
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let input = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.5, "start_time": "2024-05-01T10:00:02+02:00" }
+{ "type": "test", "name": "tests::b::case_1", "event": "ok", "exec_time": 0.25, "start_time": "2024-05-01T08:00:01.5Z" }
+{ "type": "test", "name": "tests::b::case_2", "event": "failed", "exec_time": 0.25, "start_time": "2024-05-01T08:00:00Z" }
+{ "type": "test", "name": "tests::c", "event": "ok", "exec_time": 0.1 }
+{ "type": "suite", "event": "failed", "passed": 3, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 1.0 }
+"#;
+        let options = || ProcessOptions {
+            timestamps: true,
+            verify: true,
+            ..options()
+        };
+        let output = render(input, options());
+        assert!(output.contains(
+            r#"# Execution order
+
+| Started at | Name | Result | Duration |
+| ---------- | ---- | ------ | -------- |
+| 2024-05-01 08:00:00.000 UTC | [tests::b::case_2](#testsbcase_2) | ❌ | 0s |
+| 2024-05-01 08:00:01.500 UTC | [tests::b::case_1](#testsb) | ✅ | 0s |
+| 2024-05-01 08:00:02.000 UTC | [tests::a](#testsa) | ✅ | 0s |
+
+"#
+        ));
+        assert!(
+            output.contains("**Duration**: 0s\n\n**Started at**: 2024-05-01 08:00:02.000 UTC\n")
+        );
+        assert!(
+            output.contains("**Duration**: 0s\n\n**Started at**: 2024-05-01 08:00:00.000 UTC\n")
+        );
+        assert_eq!(output.matches("**Started at**").count(), 2);
+
+        let output = render(
+            input,
+            ProcessOptions {
+                timestamps: false,
+                ..options()
+            },
+        );
+        assert!(!output.contains("Started at"));
+        assert!(!render(RUN, options()).contains("Execution order"));
+
+        // external events are interleaved by their time, and listed by the tests running at the time
+        let event = |time: &str, message: &str| timeline::Event {
+            time: DateTime::parse_from_rfc3339(time).unwrap(),
+            message: message.into(),
+        };
+        let output = render(
+            input,
+            ProcessOptions {
+                timeline: vec![
+                    event("2024-05-01T08:00:00.1Z", "db | restarted"),
+                    event("2024-05-01T08:00:02Z", "deployed"),
+                    event("2024-05-01T09:00:00Z", "done"),
+                ],
+                ..options()
+            },
+        );
+        assert!(output.contains(
+            r#"| 2024-05-01 08:00:00.000 UTC | [tests::b::case_2](#testsbcase_2) | ❌ | 0s |
+| 2024-05-01 08:00:00.100 UTC | 📌 db \| restarted | | |
+| 2024-05-01 08:00:01.500 UTC | [tests::b::case_1](#testsb) | ✅ | 0s |
+| 2024-05-01 08:00:02.000 UTC | 📌 deployed | | |
+| 2024-05-01 08:00:02.000 UTC | [tests::a](#testsa) | ✅ | 0s |
+| 2024-05-01 09:00:00.000 UTC | 📌 done | | |
+"#
+        ));
+        assert!(output.contains(
+            "**Started at**: 2024-05-01 08:00:00.000 UTC\n\n**Events**:\n\n* 2024-05-01 08:00:00.100 UTC: db | restarted\n"
+        ));
+        assert!(output.contains(
+            "**Started at**: 2024-05-01 08:00:02.000 UTC\n\n**Events**:\n\n* 2024-05-01 08:00:02.000 UTC: deployed\n"
+        ));
+        assert_eq!(output.matches("**Events**").count(), 2);
+    }
+
+    #[test]
+    fn test_summary_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("summary.md");
+        let report = render(
+            RUN,
+            ProcessOptions {
+                summary_output: Some(path.to_string_lossy().into_owned()),
+                disable_front_matter: false,
+                ..options()
+            },
+        );
+        assert!(report.starts_with("---\n"));
+        assert!(report.contains("<!--more-->"));
+
+        // the summary has no front matter
+        let summary = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            summary,
+            render(
+                RUN,
+                ProcessOptions {
+                    summary: true,
+                    ..options()
+                }
+            )
+        );
+        assert!(!summary.contains("<!--more-->"));
+
+        // appending keeps what was written before
+        std::fs::write(&path, "# Build\n").unwrap();
+        render(
+            RUN,
+            ProcessOptions {
+                summary_output: Some(path.to_string_lossy().into_owned()),
+                summary_append: true,
+                ..options()
+            },
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("# Build\n{}", summary)
+        );
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let var = |name: &str| match name {
+            "BUILD_ID" => Some("42".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_template("https://ci.example.com/builds/{BUILD_ID}{EMPTY}", var).as_deref(),
+            Some("https://ci.example.com/builds/42")
+        );
+        assert_eq!(
+            expand_template("https://ci.example.com/{PROJECT}/{BUILD_ID}", var),
+            None
+        );
+        assert_eq!(
+            expand_template("https://ci.example.com/{}?q={ 1 }", var).as_deref(),
+            Some("https://ci.example.com/{}?q={ 1 }")
+        );
+    }
+
+    #[test]
+    fn test_custom_labels() {
+        let report = render(
+            RUN,
+            ProcessOptions {
+                lang: Lang::De,
+                custom_labels: CustomLabels {
+                    index: Some("Testfälle".into()),
+                    details: Some("Protokolle".into()),
+                    passed: Some("Grün".into()),
+                    ..Default::default()
+                },
+                ..options()
+            },
+        );
+        assert!(report.contains("| | Gesamt | Grün | Fehlgeschlagen |"));
+        assert!(report.contains("\n# Testfälle\n"));
+        assert!(report.contains("\n# Protokolle\n"));
+        assert!(!report.contains("# Übersicht"));
+        assert!(!report.contains("# Details"));
+    }
+
+    #[test]
+    #[cfg(feature = "store")]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[1.0, 2.0, 3.0, 8.0]), "▁▂▃█");
+        assert_eq!(sparkline(&[0.5, 0.5]), "▅▅");
+    }
+
+    #[test]
+    #[cfg(feature = "store")]
+    fn test_trend() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.db");
+        let options = || ProcessOptions {
+            store: Some(Store::open(&path, None).unwrap()),
+            trend_runs: 3,
+            ..options()
+        };
+        // the first run has no history yet
+        assert!(!render(RUN, options()).contains("**Trend:**"));
+        render(RETRY, options());
+        render(RUN, options());
+
+        let output = render(RETRY, options());
+        assert!(output.contains("**Trend:** Pass rate █▁█ 100% · Duration ▁█▁ 0s\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "store")]
+    fn test_duration_delta() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.db");
+        let options = || ProcessOptions {
+            store: Some(Store::open(&path, None).unwrap()),
+            trend_runs: 5,
+            duration_delta: true,
+            ..options()
+        };
+        render(RUN, options());
+
+        let output = render(&RUN.replace("0.2,", "0.25,"), options());
+        assert!(output.contains(
+            "| Name | Result | Duration | Change |\n| ---- | ------ | -------- | ------ |\n"
+        ));
+        assert!(output.contains("| ✅ | 0s | +0% | \n"));
+        assert!(output.contains("| ❌ | 0s | +25% | \n"));
+    }
+
+    #[test]
+    fn test_budgets() {
+        let input = [RUN, &RETRY.replace("tests::b", "c")].concat();
+        let output = render(
+            &input,
+            ProcessOptions {
+                precise: true,
+                budgets: vec![
+                    Budget::parse("integration=100ms").unwrap(),
+                    Budget::parse("total=1s").unwrap(),
+                ],
+                ..options()
+            },
+        );
+        assert!(output.contains(
+            "> ⚠️ **Over budget:** Integration tests took 200ms, exceeding the budget of 100ms.\n"
+        ));
+        assert!(!output.contains("**Over budget:** Total"));
+        assert!(output.contains("| #2 | Integration tests | 1 | 1 | 0 | ⚠️ 200ms |"));
+    }
+
+    #[test]
+    fn test_ignored() {
+        let input = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.1 }
+{ "type": "test", "name": "tests::slow", "event": "ignored", "message": "takes an hour" }
+{ "type": "test", "name": "tests::b", "event": "ignored" }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 2, "measured": 0, "filtered_out": 0, "exec_time": 0.1 }
+"#;
+        let output = render(input, options());
+        assert!(output.contains("| ✅ | 3 | 1 | 0 | 2 | 0 | 0s |"));
+        assert!(output.contains(
+            "# Ignored\n\n| Name | Reason |\n| ---- | ------ |\n| tests::slow | takes an hour |\n| tests::b |  |\n"
+        ));
+        assert!(!output.contains("Count mismatch"));
+
+        let output = render(
+            input,
+            ProcessOptions {
+                runs: RunMode::Merge,
+                ..options()
+            },
+        );
+        assert!(output.contains("| tests::slow | takes an hour |"));
+    }
+
+    #[test]
+    fn test_broken_record() {
+        let input = RUN.replace(r#""name": "tests::b", "#, "");
+        let mut processor = Processor::new(Vec::new(), options());
+        let err = input
+            .lines()
+            .try_for_each(|line| processor.line(line))
+            .unwrap_err();
+        assert!(matches!(err, Error::Parse { line: 5, .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn test_strict() {
+        let process = |lines: &[&str]| {
+            let mut processor = Processor::new(
+                Vec::new(),
+                ProcessOptions {
+                    strict: true,
+                    ..options()
+                },
+            );
+            processor.set_label(Some("unit.json".into()));
+            assert_eq!(processor.location(), "unit.json, line 0");
+            lines.iter().try_for_each(|line| processor.line(line))
+        };
+
+        let mut lines = RUN.lines().collect::<Vec<_>>();
+        assert!(process(&lines).is_ok());
+        lines.insert(
+            3,
+            r#"{ "type": "bench", "name": "b", "median": 10, "deviation": 1 }"#,
+        );
+        assert_eq!(
+            process(&lines).unwrap_err().to_string(),
+            "Invalid input, line 4: unknown record `bench`"
+        );
+        lines[3] = "running 2 tests";
+        assert_eq!(
+            process(&lines).unwrap_err().to_string(),
+            "Invalid input, line 4: not JSON"
+        );
+    }
+
+    #[test]
+    fn test_unknown_records() {
+        let mut lines = RUN.lines().collect::<Vec<_>>();
+        lines.splice(
+            5..5,
+            [
+                r#"{ "type": "bench", "name": "b", "median": 10, "deviation": 1 }"#,
+                r#"{ "type": "test", "event": "timeout", "name": "tests::a" }"#,
+                r#"{ "type": "bench", "name": "c", "median": 10, "deviation": 1 }"#,
+                r#"{ "reason": "build-finished" }"#,
+            ],
+        );
+        let input = lines.join("\n");
+        let mut output = Vec::new();
+        let ignored = {
+            let mut processor = Processor::new(&mut output, options());
+            for line in input.lines() {
+                processor.line(line).unwrap();
+            }
+            (
+                processor.ignored.unknown,
+                processor.ignored.format_kinds(|kind| kind.to_string()),
+            )
+        };
+        assert_eq!(ignored, (1, "bench: 2, test/timeout: 1".to_string()));
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "> ⚠️ **Unknown records:** The test output contained records which are not supported, and were ignored: `bench`: 2, `test/timeout`: 1.\n"
+        ));
+    }
+
+    #[test]
+    fn test_libtest_mimic() {
+        let input = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "[tidy] check_toph" }
+{ "type": "test", "name": "[tidy] check_toph", "event": "ok" }
+{ "type": "test", "event": "started", "name": "check_katara" }
+{ "type": "test", "name": "check_katara", "event": "failed", "message": "expected water" }
+{ "type": "test", "event": "started", "name": "long_computation" }
+{ "type": "test", "name": "long_computation", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.5 }
+"#;
+        let mut output = Vec::new();
+        let ignored = {
+            let mut processor = Processor::new(&mut output, options());
+            for line in input.lines() {
+                processor.line(line).unwrap();
+            }
+            processor.ignored.invalid + processor.ignored.unknown
+        };
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(ignored, 0);
+        assert!(output.contains("| ❌ | 3 | 1 | 1 | 1 | 0 | 0s |"));
+        assert!(output.contains("| [check_toph](#tidy-check_toph) `tidy` | ✅ | 0s |"));
+        assert!(output.contains("**Message**: expected water"));
+        assert!(!output.contains("Count mismatch"));
+    }
+
+    #[test]
+    fn test_max_name_length() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                max_name_length: Some(6),
+                ..options()
+            },
+        );
+        assert!(output.contains(r#"| [tests…](#testsa "tests::a") |"#));
+        assert!(output.contains(r#"## ✅ tests::a<a id="testsa"></a>"#));
+    }
+
+    #[test]
+    fn test_cases() {
+        let input = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "name": "tests::parse::case_1", "event": "ok", "exec_time": 1.0 }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 1.0 }
+{ "type": "test", "name": "tests::parse::case_2_empty_input", "event": "failed", "exec_time": 1.0, "stdout": "boom" }
+{ "type": "suite", "event": "failed", "passed": 2, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 3.0 }
+"#;
+        let output = render(input, options());
+        assert!(output.contains(
+            "| [tests::parse](#testsparse) (2 cases) | ❌ | 2s | \n| [tests::a](#testsa) | ✅ | 1s |"
+        ));
+        assert!(output.contains(
+            "| case_1 | ✅ | 1s |\n| [case_2_empty_input](#testsparsecase_2_empty_input) | ❌ | 1s |"
+        ));
+        assert!(output
+            .contains(r#"### ❌ case_2_empty_input<a id="testsparsecase_2_empty_input"></a>"#));
+        assert_eq!(
+            split_case("tests::foo::_empty_expects_none"),
+            Some(("tests::foo", "_empty_expects_none"))
+        );
+        assert_eq!(split_case("tests::case_b"), None);
+    }
+
+    #[test]
+    fn test_lang() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                lang: Lang::De,
+                ..options()
+            },
+        );
+        assert!(output
+            .contains("| | Gesamt | Bestanden | Fehlgeschlagen | Ignoriert | Gefiltert | Dauer |"));
+        assert!(output.contains("# Übersicht\n"));
+        assert!(output.contains("| Name | Ergebnis | Dauer |"));
+        assert!(output.contains("\n**Dauer**: 0s"));
+    }
+
+    #[test]
+    fn test_labels_front_matter() {
+        let output = render(
+            RUN,
+            ProcessOptions {
+                disable_front_matter: false,
+                labels: vec!["staging".into(), "linux".into()],
+                ..options()
+            },
+        );
+        assert!(output.contains(r#"UTC (staging, linux)""#));
+        assert!(output.contains(r#"tags: ["staging","linux"]"#));
+        assert!(output.contains("**Labels:** `staging` `linux`"));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let mut output = Vec::new();
+        {
+            let mut processor = Processor::new(
+                &mut output,
+                ProcessOptions {
+                    aggregate: true,
+                    ..options()
+                },
+            );
+            processor.set_label(Some("linux".to_string()));
+            for line in RUN.lines() {
+                processor.line(line).unwrap();
+            }
+            processor.set_label(Some("macos".to_string()));
+            for line in RETRY.lines() {
+                processor.line(line).unwrap();
+            }
+        }
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("| Platform | | Total |"));
+        assert!(output.contains("| Platform | Suite | Kind |"));
+        assert!(output.contains("| linux | ❌ | 2 | 1 | 1 | 0 | 0 | 0s |"));
+        assert!(output.contains("| macos | ✅ | 1 | 1 | 0 | 0 | 1 | 0s |"));
+        assert!(output.contains("| Name | linux | macos |"));
+        assert!(output.contains("| tests::a | ✅ | |"));
+        assert!(output.contains("| tests::b | [❌](#linux-testsb) | ✅ |"));
+        assert!(output.contains(r#"## ❌ tests::b<a id="linux-testsb"></a>"#));
+        assert!(!output.contains("# Details"));
+    }
+
+    #[test]
+    fn test_aggregate_identical_failures() {
+        let mut output = Vec::new();
+        {
+            let mut processor = Processor::new(
+                &mut output,
+                ProcessOptions {
+                    aggregate: true,
+                    ..options()
+                },
+            );
+            for label in ["linux", "macos", "windows"] {
+                processor.set_label(Some(label.to_string()));
+                for line in RUN.lines() {
+                    let line = match label {
+                        "windows" => line.replace("boom", "crash"),
+                        _ => line.to_string(),
+                    };
+                    processor.line(&line).unwrap();
+                }
+            }
+        }
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(
+            "| tests::b | [❌](#linux-testsb) | [❌](#linux-testsb) | [❌](#windows-testsb) |"
+        ));
+        assert!(output.contains("**Platforms**: linux, macos\n"));
+        assert!(output.contains("**Platforms**: windows\n"));
+        assert_eq!(output.matches("## ❌ tests::b").count(), 2);
+    }
+
+    #[test]
+    fn test_runs_separate() {
+        let output = render(&format!("{RUN}{RETRY}"), options());
+        assert!(output.contains("| 1 | ❌ | 2 | 1 | 1 | 0 | 0 | 0s |"));
+        assert!(output.contains("| 2 | ✅ | 1 | 1 | 0 | 0 | 1 | 0s |"));
+        assert!(output.contains("# Index (run 2)"));
+        assert!(output.contains("[tests::b](#run-2-testsb)"));
+    }
+
+    #[test]
+    fn test_runs_merge() {
+        let output = render(
+            &format!("{RUN}{RETRY}"),
+            ProcessOptions {
+                runs: RunMode::Merge,
+                ..options()
+            },
+        );
+        assert!(output.contains("| ✅ | 2 | 2 | 0 | 0 | 1 | 0s |"));
+        assert!(!output.contains("❌ tests::b"));
+    }
+
+    #[test]
+    fn test_runs_latest() {
+        let output = render(
+            &format!("{RUN}{RETRY}"),
+            ProcessOptions {
+                runs: RunMode::Latest,
+                ..options()
+            },
+        );
+        assert!(output.contains("| ✅ | 1 | 1 | 0 | 0 | 1 | 0s |"));
+        assert!(!output.contains("tests::a"));
+    }
+
+    #[test]
+    fn test_runs_workspace() {
+        // two crates with the same tests are not a retry
+        let ok = RUN.replace("test_count\": 2", "test_count\": 1");
+        let ok = ok
+            .lines()
+            .filter(|line| !line.contains("tests::b"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .replace(
+                "\"failed\", \"passed\": 1, \"failed\": 1",
+                "\"ok\", \"passed\": 1, \"failed\": 0",
+            );
+        let failed = ok
+            .replace("\"event\": \"ok\"", "\"event\": \"failed\"")
+            .replace(
+                "\"passed\": 1, \"failed\": 0",
+                "\"passed\": 0, \"failed\": 1",
+            );
+        let output = render(
+            &format!("{ok}\n{failed}\n"),
+            ProcessOptions {
+                runs: RunMode::Latest,
+                ..options()
+            },
+        );
+        assert!(output.contains("| ❌ | 2 | 1 | 1 | 0 | 0 | 0s |"));
+        assert!(!output.contains("(run 2)"));
+
+        // neither are suites which only repeat a part of the sequence
+        let output = render(&format!("{RUN}{RETRY}{RUN}"), options());
+        assert!(!output.contains("(run 2)"));
+
+        // with known binaries, the whole sequence repeating is a new run, even when passing
+        let binaries = format!(
+            "     Running unittests src/lib.rs (target/debug/deps/foo-1234)\n{ok}\n     Running tests/api.rs (target/debug/deps/api-1234)\n{RETRY}"
+        );
+        let output = render(&binaries.repeat(2), options());
+        assert!(output.contains("| 2 | ✅ | 2 | 2 | 0 | 0 | 1 | 0s |"));
+        assert!(output.contains("# Index (run 2)"));
+    }
 
     #[test]
     fn test_anchors() {
+        let make_anchor = |link| {
+            let mut anchor = "run-1-".to_string();
+            push_anchor(&mut anchor, link);
+            anchor.split_off(6)
+        };
         assert_eq!(make_anchor(""), "");
         assert_eq!(
             make_anchor("✅ tests::registry::test_registry_create_and_delete"),
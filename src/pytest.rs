@@ -0,0 +1,324 @@
+use crate::input::Converter;
+use serde_json::{json, Value};
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+enum Outcome {
+    #[default]
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// The result of a test, combined from its setup, call, and teardown phases.
+#[derive(Debug, Default)]
+struct TestResult {
+    outcome: Outcome,
+    duration: f64,
+    output: String,
+    /// The reason a test was skipped.
+    reason: Option<String>,
+}
+
+impl TestResult {
+    fn add_phase(&mut self, phase: &Value) {
+        self.duration += phase["duration"].as_f64().unwrap_or_default();
+
+        let outcome = match phase["outcome"].as_str() {
+            Some("failed") => Outcome::Failed,
+            Some("skipped") => Outcome::Skipped,
+            _ => Outcome::Passed,
+        };
+        match outcome {
+            Outcome::Failed => {
+                self.outcome = Outcome::Failed;
+                if let Some(text) = longrepr(&phase["longrepr"]) {
+                    self.output.push_str(&text);
+                    self.output.push('\n');
+                }
+            }
+            Outcome::Skipped if self.outcome == Outcome::Passed => {
+                self.outcome = Outcome::Skipped;
+                self.reason = skip_reason(&phase["longrepr"]);
+            }
+            _ => {}
+        }
+
+        // pytest-json-report
+        for stream in ["stdout", "stderr"] {
+            if let Some(text) = phase[stream].as_str() {
+                self.output.push_str(text);
+            }
+        }
+        // report-log: [["Captured stdout call", "…"]]
+        for section in phase["sections"].as_array().into_iter().flatten() {
+            if let (Some(title), Some(text)) = (section[0].as_str(), section[1].as_str()) {
+                self.output
+                    .push_str(&format!("----- {} -----\n{}\n", title, text));
+            }
+        }
+    }
+
+    fn to_line(&self, name: &str) -> String {
+        match self.outcome {
+            Outcome::Passed => {
+                json!({ "type": "test", "event": "ok", "name": name, "exec_time": self.duration })
+            }
+            Outcome::Failed => {
+                json!({ "type": "test", "event": "failed", "name": name, "exec_time": self.duration, "stdout": self.output })
+            }
+            Outcome::Skipped => {
+                json!({ "type": "test", "event": "ignored", "name": name, "message": self.reason })
+            }
+        }
+        .to_string()
+    }
+}
+
+/// Render the representation of a failure, either a string or the structure of report-log
+fn longrepr(value: &Value) -> Option<String> {
+    if let Some(text) = value.as_str() {
+        return Some(text.to_string());
+    }
+
+    let lines = value["reprtraceback"]["reprentries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|entry| entry["data"]["lines"].as_array().into_iter().flatten())
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>();
+    match lines.is_empty() {
+        false => Some(lines.join("\n")),
+        true => value["reprcrash"]["message"]
+            .as_str()
+            .map(ToString::to_string),
+    }
+}
+
+/// Extract the reason of a skipped test, from `[path, line, "Skipped: reason"]` or its string
+/// representation
+fn skip_reason(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::Array(parts) => parts.get(2)?.as_str()?.to_string(),
+        Value::String(text) => text
+            .trim_end_matches(')')
+            .rsplit_once(", ")?
+            .1
+            .trim_matches(['\'', '"'])
+            .to_string(),
+        _ => return None,
+    };
+    Some(text.strip_prefix("Skipped: ").unwrap_or(&text).to_string())
+}
+
+/// Converts the output of `pytest --report-log` or `pytest-json-report` into a single suite.
+#[derive(Debug, Default)]
+pub struct Pytest {
+    /// The tests of the report-log which didn't finish their teardown yet.
+    pending: Vec<(String, TestResult)>,
+    tests: Vec<(String, TestResult)>,
+    /// The lines of a JSON document, as created by `pytest-json-report`.
+    document: String,
+}
+
+impl Pytest {
+    fn report(&mut self, report: &Value) {
+        let Some(nodeid) = report["nodeid"].as_str() else {
+            return;
+        };
+
+        let idx = match self.pending.iter().position(|(name, _)| name == nodeid) {
+            Some(idx) => idx,
+            None => {
+                self.pending
+                    .push((nodeid.to_string(), TestResult::default()));
+                self.pending.len() - 1
+            }
+        };
+        self.pending[idx].1.add_phase(report);
+
+        if report["when"].as_str() == Some("teardown") {
+            let test = self.pending.remove(idx);
+            self.tests.push(test);
+        }
+    }
+
+    /// Convert the document of `pytest-json-report`
+    fn document(&mut self, document: &Value, out: &mut Vec<String>) {
+        for test in document["tests"].as_array().into_iter().flatten() {
+            let Some(nodeid) = test["nodeid"].as_str() else {
+                continue;
+            };
+            let mut result = TestResult::default();
+            for phase in ["setup", "call", "teardown"] {
+                if test[phase].is_object() {
+                    result.add_phase(&test[phase]);
+                }
+            }
+            self.tests.push((nodeid.to_string(), result));
+        }
+        self.suite(true, out);
+    }
+
+    /// Write all tests as a suite, finished if the session finished
+    fn suite(&mut self, finished: bool, out: &mut Vec<String>) {
+        let tests = std::mem::take(&mut self.tests)
+            .into_iter()
+            .chain(std::mem::take(&mut self.pending))
+            .collect::<Vec<_>>();
+        if tests.is_empty() {
+            return;
+        }
+
+        let count = |outcome| tests.iter().filter(|(_, t)| t.outcome == outcome).count();
+        let failed = count(Outcome::Failed);
+
+        out.push("     Running pytest".to_string());
+        out.push(
+            json!({ "type": "suite", "event": "started", "test_count": tests.len() }).to_string(),
+        );
+        out.extend(tests.iter().map(|(name, test)| test.to_line(name)));
+        if finished {
+            out.push(
+                json!({
+                    "type": "suite",
+                    "event": if failed > 0 { "failed" } else { "ok" },
+                    "passed": count(Outcome::Passed),
+                    "failed": failed,
+                    "ignored": count(Outcome::Skipped),
+                    "measured": 0,
+                    "filtered_out": 0,
+                    "exec_time": tests.iter().map(|(_, test)| test.duration).sum::<f64>(),
+                })
+                .to_string(),
+            );
+        }
+    }
+}
+
+impl Converter for Pytest {
+    fn line(&mut self, line: &str, out: &mut Vec<String>) -> anyhow::Result<()> {
+        if !self.document.is_empty() {
+            self.document.push_str(line);
+            return Ok(());
+        }
+
+        let value = match serde_json::from_str::<Value>(line) {
+            Ok(value) => value,
+            // the start of a document, spanning multiple lines
+            Err(_) if line.trim_start().starts_with('{') => {
+                self.document.push_str(line);
+                return Ok(());
+            }
+            Err(err) => {
+                log::debug!("Ignoring line: {:?} -> {}", err, line);
+                return Ok(());
+            }
+        };
+
+        match value["$report_type"].as_str() {
+            Some("TestReport") => self.report(&value),
+            // errors collecting tests, like a failing import
+            Some("CollectReport") if value["outcome"] == "failed" => {
+                let mut result = TestResult::default();
+                result.add_phase(&value);
+                if let Some(nodeid) = value["nodeid"].as_str() {
+                    self.tests.push((nodeid.to_string(), result));
+                }
+            }
+            Some("SessionFinish") => self.suite(true, out),
+            Some(_) => {}
+            // a document on a single line
+            None => self.document(&value, out),
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<String>) -> anyhow::Result<()> {
+        if !self.document.is_empty() {
+            let value = serde_json::from_str(&std::mem::take(&mut self.document))?;
+            self.document(&value, out);
+        }
+        self.suite(false, out);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn convert(input: &str) -> Vec<String> {
+        let mut converter = Pytest::default();
+        let mut out = vec![];
+        for line in input.lines() {
+            converter.line(line, &mut out).unwrap();
+        }
+        converter.finish(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_report_log() {
+        let input = r#"{"pytest_version": "8.0.0", "$report_type": "SessionStart"}
+{"nodeid": "test_api.py::test_ok", "when": "setup", "outcome": "passed", "duration": 0.25, "longrepr": null, "sections": [], "$report_type": "TestReport"}
+{"nodeid": "test_api.py::test_ok", "when": "call", "outcome": "passed", "duration": 0.5, "longrepr": null, "sections": [], "$report_type": "TestReport"}
+{"nodeid": "test_api.py::test_ok", "when": "teardown", "outcome": "passed", "duration": 0.25, "longrepr": null, "sections": [], "$report_type": "TestReport"}
+{"nodeid": "test_api.py::test_fail", "when": "setup", "outcome": "passed", "duration": 0.0, "longrepr": null, "sections": [], "$report_type": "TestReport"}
+{"nodeid": "test_api.py::test_fail", "when": "call", "outcome": "failed", "duration": 0.5, "longrepr": {"reprcrash": {"message": "assert 1 == 2"}, "reprtraceback": {"reprentries": [{"data": {"lines": [">       assert 1 == 2", "E       assert 1 == 2"]}}]}}, "sections": [["Captured stdout call", "hello"]], "$report_type": "TestReport"}
+{"nodeid": "test_api.py::test_fail", "when": "teardown", "outcome": "passed", "duration": 0.0, "longrepr": null, "sections": [], "$report_type": "TestReport"}
+{"nodeid": "test_api.py::test_skip", "when": "setup", "outcome": "skipped", "duration": 0.0, "longrepr": ["test_api.py", 12, "Skipped: needs a database"], "sections": [], "$report_type": "TestReport"}
+{"nodeid": "test_api.py::test_skip", "when": "teardown", "outcome": "passed", "duration": 0.0, "longrepr": null, "sections": [], "$report_type": "TestReport"}
+{"exitstatus": 1, "$report_type": "SessionFinish"}
+"#;
+        let out = convert(input);
+        assert_eq!(out.len(), 6);
+        assert_eq!(out[0], "     Running pytest");
+        assert_eq!(
+            out[2],
+            r#"{"event":"ok","exec_time":1.0,"name":"test_api.py::test_ok","type":"test"}"#
+        );
+        assert_eq!(
+            out[3],
+            r#"{"event":"failed","exec_time":0.5,"name":"test_api.py::test_fail","stdout":">       assert 1 == 2\nE       assert 1 == 2\n----- Captured stdout call -----\nhello\n","type":"test"}"#
+        );
+        assert_eq!(
+            out[4],
+            r#"{"event":"ignored","message":"needs a database","name":"test_api.py::test_skip","type":"test"}"#
+        );
+        assert!(out[5].contains(r#""event":"failed","exec_time":1.5,"failed":1"#));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let input = r#"{
+  "created": 1700000000.0,
+  "exitcode": 0,
+  "tests": [
+    {
+      "nodeid": "test_api.py::test_ok",
+      "outcome": "passed",
+      "setup": {"duration": 0.1, "outcome": "passed"},
+      "call": {"duration": 0.2, "outcome": "passed", "stdout": "hello"},
+      "teardown": {"duration": 0.1, "outcome": "passed"}
+    },
+    {
+      "nodeid": "test_api.py::test_skip",
+      "outcome": "skipped",
+      "setup": {"duration": 0.0, "outcome": "skipped", "longrepr": "('test_api.py', 3, 'Skipped: not today')"},
+      "teardown": {"duration": 0.0, "outcome": "passed"}
+    }
+  ]
+}
+"#;
+        let out = convert(input);
+        assert_eq!(out.len(), 5);
+        assert!(out[2].contains(r#""event":"ok""#));
+        assert_eq!(
+            out[3],
+            r#"{"event":"ignored","message":"not today","name":"test_api.py::test_skip","type":"test"}"#
+        );
+        assert!(out[4].contains(r#""event":"ok""#));
+    }
+}
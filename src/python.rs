@@ -0,0 +1,113 @@
+use crate::{
+    error::Error,
+    event::{self, Parser, Record},
+    processor::{self, Format, ProcessOptions},
+};
+use clap::ValueEnum;
+use pyo3::{
+    exceptions::{PyOSError, PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Parse { .. } => PyValueError::new_err(err.to_string()),
+            Error::Io(_) => PyOSError::new_err(err.to_string()),
+            Error::Render(_) => PyRuntimeError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// Parse the records of the JSON output of the tests into dictionaries, skipping other lines.
+///
+/// Broken records raise a `ValueError`.
+#[pyfunction]
+fn parse<'py>(py: Python<'py>, json: &str) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    let loads = py.import("json")?.getattr("loads")?;
+    let mut parser = Parser::default();
+    let mut records = vec![];
+    for (idx, line) in json.lines().enumerate() {
+        match parser.parse(line) {
+            Ok(Record::Unknown(_)) => {}
+            Ok(record) => records.push(loads.call1((record.to_line(),))?),
+            Err(err) if event::is_broken(line) => {
+                return Err(Error::Parse {
+                    line: idx as u64 + 1,
+                    message: err.to_string(),
+                }
+                .into())
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(records)
+}
+
+/// Render the report of the JSON output of the tests, like `markdown`, `comment`, `json`, or `html`.
+#[pyfunction]
+#[pyo3(signature = (json, format = "markdown", summary = false))]
+fn render(json: &str, format: &str, summary: bool) -> PyResult<String> {
+    let format = match Format::from_str(format, true) {
+        Ok(Format::Pdf) => return Err(PyValueError::new_err("PDF can't be rendered to a string")),
+        Ok(format) => format,
+        Err(err) => return Err(PyValueError::new_err(err)),
+    };
+
+    Ok(processor::render_str(
+        json,
+        ProcessOptions {
+            disable_front_matter: true,
+            format,
+            summary,
+            ..Default::default()
+        },
+    )?)
+}
+
+/// The Python module, like `import markdown_test_report`.
+#[pymodule]
+fn markdown_test_report(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(parse, module)?)?;
+    module.add_function(wrap_pyfunction!(render, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RUN: &str = r#"{ "type": "suite", "event": "started", "test_count": 1 }
+running 1 test
+{ "type": "test", "event": "started", "name": "tests::a" }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.1 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.1 }
+"#;
+
+    #[test]
+    fn test_parse() {
+        Python::initialize();
+        Python::attach(|py| {
+            let records = parse(py, RUN).unwrap();
+            assert_eq!(records.len(), 4);
+            assert_eq!(
+                records[2]
+                    .get_item("name")
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+                "tests::a"
+            );
+
+            let err = parse(py, r#"{ "type": "test", "event": "failed" }"#).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn test_render() {
+        assert!(render(RUN, "json", false).unwrap().starts_with('{'));
+        assert!(render(RUN, "pdf", false).is_err());
+        assert!(render(RUN, "csv", false).is_err());
+    }
+}
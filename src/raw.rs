@@ -0,0 +1,233 @@
+use crate::{input, redact::Redactor};
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+};
+
+/// How the raw input is embedded.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// As it is, in a code block
+    #[default]
+    Plain,
+    /// Compressed using gzip, encoded as base64
+    Gzip,
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode data as base64, wrapping lines the way `base64` does
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len() * 4 / 3 + 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for idx in 0..4 {
+            match idx <= chunk.len() {
+                true => encoded.push(BASE64[(n >> (18 - idx * 6) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 + 1);
+    for (idx, c) in encoded.chars().enumerate() {
+        if idx > 0 && idx % 76 == 0 {
+            wrapped.push('\n');
+        }
+        wrapped.push(c);
+    }
+    wrapped
+}
+
+/// A fence for a code block, longer than any run of backticks in the content
+pub fn fence(content: &str) -> String {
+    let longest = content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// The options of the `[addon.raw]` section of the configuration.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    pub encoding: Encoding,
+    pub position: Option<crate::processor::Position>,
+}
+
+/// Embeds the original inputs in collapsed sections, so that they can be recovered later.
+#[derive(Debug)]
+pub struct RawInput {
+    encoding: Encoding,
+    /// The inputs, and if they are streamed and can't be read again.
+    inputs: Vec<(PathBuf, bool)>,
+    /// Credentials in the inputs are replaced, like in the rest of the report.
+    redactor: Redactor,
+}
+
+impl RawInput {
+    pub fn new(encoding: Encoding, inputs: Vec<PathBuf>, redactor: Redactor) -> Self {
+        Self {
+            encoding,
+            redactor,
+            inputs: inputs
+                .into_iter()
+                .map(|path| {
+                    let streamed = input::is_streamed(&path);
+                    (path, streamed)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl super::Addon for RawInput {
+    fn position(&self) -> super::Position {
+        super::Position::Footer
+    }
+
+    fn render(&self, write: &mut dyn Write) -> anyhow::Result<()> {
+        writeln!(write)?;
+        writeln!(write, "# Raw input")?;
+
+        for (path, streamed) in &self.inputs {
+            let name = path.display();
+            writeln!(write)?;
+            if *streamed {
+                writeln!(write, "`{}` was streamed, and can't be embedded.", name)?;
+                continue;
+            }
+
+            let mut data = vec![];
+            input::open(path)?.read_to_end(&mut data)?;
+            let text = String::from_utf8_lossy(&data);
+            let text = self.redactor.redact(&text);
+
+            let content = match self.encoding {
+                Encoding::Plain => {
+                    writeln!(write, "<details>\n<summary>{}</summary>\n", name)?;
+                    text.into_owned()
+                }
+                Encoding::Gzip => {
+                    writeln!(
+                        write,
+                        "<details>\n<summary>{} (gzip, base64)</summary>\n",
+                        name
+                    )?;
+                    let mut encoder = GzEncoder::new(vec![], Compression::best());
+                    encoder.write_all(text.as_bytes())?;
+                    base64(&encoder.finish()?)
+                }
+            };
+
+            let fence = fence(&content);
+            writeln!(write, "{}", fence)?;
+            write!(write, "{}", content)?;
+            if !content.ends_with('\n') {
+                writeln!(write)?;
+            }
+            writeln!(write, "{}", fence)?;
+            writeln!(write)?;
+            writeln!(write, "</details>")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Addon;
+    use flate2::read::GzDecoder;
+
+    /// Decode the content of an embedded gzip section.
+    fn decode(out: &str) -> String {
+        let encoded = out.split("```\n").nth(1).unwrap().replace('\n', "");
+        let mut data = vec![];
+        for chunk in encoded.trim_end_matches('=').as_bytes().chunks(4) {
+            let n = chunk.iter().enumerate().fold(0u32, |n, (idx, c)| {
+                let value = BASE64.iter().position(|b| b == c).unwrap() as u32;
+                n | value << (18 - idx * 6)
+            });
+            data.extend(&n.to_be_bytes()[1..chunk.len()]);
+        }
+        let mut decoded = String::new();
+        GzDecoder::new(&data[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        decoded
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff; 60]).lines().next().unwrap().len(), 76);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"{ \"type\": \"suite\" }\n```\n").unwrap();
+
+        let mut out = vec![];
+        RawInput::new(
+            Encoding::Plain,
+            vec![file.path().into()],
+            Redactor::default(),
+        )
+        .render(&mut out)
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\n````\n{ \"type\": \"suite\" }\n```\n````\n\n</details>\n"));
+
+        let mut out = vec![];
+        RawInput::new(
+            Encoding::Gzip,
+            vec![file.path().into()],
+            Redactor::default(),
+        )
+        .render(&mut out)
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let decoded = decode(&out);
+        assert_eq!(decoded, "{ \"type\": \"suite\" }\n```\n");
+    }
+
+    #[test]
+    fn test_redact() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"{ \"stdout\": \"Authorization: Bearer abc.def\" }\n")
+            .unwrap();
+
+        for encoding in [Encoding::Plain, Encoding::Gzip] {
+            let mut out = vec![];
+            RawInput::new(
+                encoding,
+                vec![file.path().into()],
+                Redactor::new(vec![], true),
+            )
+            .render(&mut out)
+            .unwrap();
+            let out = String::from_utf8(out).unwrap();
+            let content = match encoding {
+                Encoding::Plain => out,
+                Encoding::Gzip => decode(&out),
+            };
+            assert!(!content.contains("abc.def"));
+            assert!(content.contains("Bearer [REDACTED]"));
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use regex::Regex;
+use std::borrow::Cow;
+
+/// A rule rewriting the displayed name of tests, in the form of `s/pattern/replacement/[g]`.
+#[derive(Debug, Clone)]
+pub struct Rename {
+    pattern: Regex,
+    replacement: String,
+    /// Replace all matches, instead of only the first one.
+    global: bool,
+}
+
+impl Rename {
+    /// Parse a rule, using the first character after the `s` as delimiter.
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let mut chars = rule.chars();
+        let delimiter = match (chars.next(), chars.next()) {
+            (Some('s'), Some(delimiter)) if !delimiter.is_alphanumeric() => delimiter,
+            _ => return Err(format!("expected s/pattern/replacement/, found: {}", rule)),
+        };
+
+        let parts = split(chars.as_str(), delimiter);
+        let (pattern, replacement, flags) = match parts.as_slice() {
+            [pattern, replacement, flags] => (pattern, replacement, flags),
+            _ => return Err(format!("expected s/pattern/replacement/, found: {}", rule)),
+        };
+
+        let global = match flags.as_str() {
+            "" => false,
+            "g" => true,
+            flags => return Err(format!("unsupported flags: {}", flags)),
+        };
+
+        Ok(Self {
+            pattern: Regex::new(pattern).map_err(|err| err.to_string())?,
+            replacement: sed_replacement(replacement),
+            global,
+        })
+    }
+
+    pub fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self.global {
+            true => self.pattern.replace_all(name, self.replacement.as_str()),
+            false => self.pattern.replace(name, self.replacement.as_str()),
+        }
+    }
+}
+
+/// Split a rule at the delimiters, which are part of the pattern or the replacement when escaped.
+fn split(rule: &str, delimiter: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = rule.chars();
+    while let Some(c) = chars.next() {
+        let pattern = parts.len() == 1;
+        let part = parts.last_mut().expect("at least one part");
+        match c {
+            '\\' => match chars.next() {
+                // the delimiter might be special in the pattern, but is meant literally
+                Some(c) if c == delimiter && pattern => {
+                    part.push_str(&regex::escape(&c.to_string()))
+                }
+                Some(c) if c == delimiter => part.push(c),
+                Some(c) => {
+                    part.push('\\');
+                    part.push(c);
+                }
+                None => part.push('\\'),
+            },
+            c if c == delimiter => parts.push(String::new()),
+            c => part.push(c),
+        }
+    }
+    parts
+}
+
+/// Convert the `\1` style group references of sed to the `${1}` style of the regex crate.
+fn sed_replacement(replacement: &str) -> String {
+    let mut result = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(digit)) if digit.is_ascii_digit() => {
+                result.push_str(&format!("${{{}}}", digit));
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            ('$', _) => result.push_str("$$"),
+            (c, _) => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rename() {
+        let rename = Rename::parse("s/^integration:://").unwrap();
+        assert_eq!(rename.apply("integration::tests::a"), "tests::a");
+        assert_eq!(rename.apply("unit::tests::a"), "unit::tests::a");
+
+        let rename = Rename::parse("s|::|.|g").unwrap();
+        assert_eq!(rename.apply("a::b::c"), "a.b.c");
+
+        let rename = Rename::parse(r"s/test_(\w+)/\1$/").unwrap();
+        assert_eq!(rename.apply("tests::test_foo"), "tests::foo$");
+
+        // escaped delimiters and backslashes
+        let rename = Rename::parse(r"s/a\/b/c\/d/").unwrap();
+        assert_eq!(rename.apply("x/a/b"), "x/c/d");
+        let rename = Rename::parse(r"s|a\|b|-|g").unwrap();
+        assert_eq!(rename.apply("a|b ab"), "- ab");
+        let rename = Rename::parse(r"s/(x)/\\\1/").unwrap();
+        assert_eq!(rename.apply("x"), r"\x");
+        let rename = Rename::parse(r"s/\./_/g").unwrap();
+        assert_eq!(rename.apply("a.b"), "a_b");
+
+        assert!(Rename::parse("foo").is_err());
+        assert!(Rename::parse("s/a/b").is_err());
+        assert!(Rename::parse("s/(/b/").is_err());
+        assert!(Rename::parse("s/a/b/x").is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::event::{test, Parser, Record};
+use crate::input;
+use crate::invocation::Invocation;
+use std::{borrow::Cow, path::Path};
+
+/// The test runner to re-run the tests with.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum Runner {
+    /// `cargo test`, once for each test target
+    #[default]
+    Cargo,
+    /// `cargo nextest run`, using a filter expression
+    Nextest,
+}
+
+/// The failed tests of a target.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Failed {
+    /// The target reported by cargo, like `tests/api.rs`.
+    pub target: Option<String>,
+    pub names: Vec<String>,
+}
+
+/// Collect the failed tests of an input, grouped by their target.
+///
+/// When tests were run multiple times, the last result of a test wins.
+pub fn failed(path: &Path) -> anyhow::Result<Vec<Failed>> {
+    let mut parser = Parser::default();
+    let mut target = None;
+    let mut failed = Vec::<Failed>::new();
+
+    input::read_lines(input::open(path)?, |line| {
+        let event = match parser.parse(line) {
+            Ok(Record::Test(event)) => event,
+            Ok(Record::Suite(_) | Record::Unknown(_)) => return Ok(()),
+            Err(_) => {
+                if let Some(invocation) = Invocation::parse(line) {
+                    target = invocation.target;
+                }
+                return Ok(());
+            }
+        };
+
+        let (name, failing) = match event {
+            test::Event::Failed { name, .. } => (name, true),
+            test::Event::Ok { name, .. } => (name, false),
+            _ => return Ok(()),
+        };
+        let idx = match failed.iter().position(|f| f.target == target) {
+            Some(idx) => idx,
+            None => {
+                failed.push(Failed {
+                    target: target.clone(),
+                    names: vec![],
+                });
+                failed.len() - 1
+            }
+        };
+        let names = &mut failed[idx].names;
+        names.retain(|existing| *existing != name);
+        if failing {
+            names.push(name);
+        }
+        Ok(())
+    })?;
+
+    failed.retain(|f| !f.names.is_empty());
+    Ok(failed)
+}
+
+/// The arguments of cargo selecting a target, like `--test api` for `tests/api.rs`
+fn target_args(target: &str) -> Vec<String> {
+    let name = |path: &str| {
+        Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+
+    if target.starts_with("doc-tests ") {
+        return vec!["--doc".into()];
+    }
+    let path = target.strip_prefix("unittests ").unwrap_or(target);
+    match path.split_once('/').map(|(dir, _)| dir) {
+        Some("tests") => vec!["--test".into(), name(path)],
+        Some("benches") => vec!["--bench".into(), name(path)],
+        Some("examples") => vec!["--example".into(), name(path)],
+        _ if path.contains("/bin/") => vec!["--bin".into(), name(path)],
+        _ if path.ends_with("main.rs") => vec!["--bins".into()],
+        _ => vec!["--lib".into()],
+    }
+}
+
+/// The command lines re-running the failed tests
+pub fn commands(runner: Runner, failed: &[Failed]) -> Vec<Vec<String>> {
+    match runner {
+        Runner::Cargo => failed
+            .iter()
+            .map(|f| {
+                let mut args = vec!["cargo".to_string(), "test".to_string()];
+                args.extend(f.target.as_deref().map(target_args).unwrap_or_default());
+                args.extend(["--".to_string(), "--exact".to_string()]);
+                args.extend(f.names.iter().cloned());
+                args
+            })
+            .collect(),
+        Runner::Nextest => {
+            let filter = failed
+                .iter()
+                .flat_map(|f| &f.names)
+                .map(|name| format!("test(={})", name))
+                .collect::<Vec<_>>();
+            if filter.is_empty() {
+                return vec![];
+            }
+            vec![vec![
+                "cargo".into(),
+                "nextest".into(),
+                "run".into(),
+                "-E".into(),
+                filter.join(" | "),
+            ]]
+        }
+    }
+}
+
+/// Quote an argument for a POSIX shell, if required
+pub fn quote(arg: &str) -> Cow<'_, str> {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c);
+    match !arg.is_empty() && arg.chars().all(plain) {
+        true => Cow::Borrowed(arg),
+        false => Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_rerun() {
+        let input = r#"     Running unittests src/lib.rs (target/debug/deps/foo-1234)
+{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "name": "tests::a", "event": "failed", "exec_time": 0.25 }
+{ "type": "test", "name": "tests::b", "event": "failed", "exec_time": 0.25 }
+     Running tests/api.rs (target/debug/deps/api-1234)
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "users", "event": "failed", "exec_time": 0.25 }
+     Running unittests src/lib.rs (target/debug/deps/foo-1234)
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "tests::b", "event": "ok", "exec_time": 0.25 }
+"#;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(input.as_bytes()).unwrap();
+        let failed = failed(file.path()).unwrap();
+
+        assert_eq!(
+            commands(Runner::Cargo, &failed),
+            vec![
+                vec!["cargo", "test", "--lib", "--", "--exact", "tests::a"],
+                vec!["cargo", "test", "--test", "api", "--", "--exact", "users"],
+            ]
+        );
+        assert_eq!(
+            commands(Runner::Nextest, &failed),
+            vec![vec![
+                "cargo",
+                "nextest",
+                "run",
+                "-E",
+                "test(=tests::a) | test(=users)"
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_target_args() {
+        assert_eq!(target_args("unittests src/main.rs"), vec!["--bins"]);
+        assert_eq!(
+            target_args("unittests src/bin/tool.rs"),
+            vec!["--bin", "tool"]
+        );
+        assert_eq!(target_args("doc-tests foo"), vec!["--doc"]);
+        assert_eq!(target_args("benches/speed.rs"), vec!["--bench", "speed"]);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("tests::a"), "tests::a");
+        assert_eq!(
+            quote("src/lib.rs - foo (line 1)"),
+            "'src/lib.rs - foo (line 1)'"
+        );
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+}
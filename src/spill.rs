@@ -0,0 +1,71 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+/// The location of spilled data in the spill file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Spilled {
+    offset: u64,
+    len: usize,
+}
+
+/// A temporary file, keeping captured test output out of memory.
+///
+/// The file gets deleted when it is dropped.
+#[derive(Debug)]
+pub struct Spill {
+    file: File,
+    len: u64,
+}
+
+impl Spill {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            file: tempfile::tempfile()?,
+            len: 0,
+        })
+    }
+
+    /// Append data to the spill file
+    pub fn store(&mut self, data: &str) -> anyhow::Result<Spilled> {
+        self.file.seek(SeekFrom::Start(self.len))?;
+        self.file.write_all(data.as_bytes())?;
+
+        let spilled = Spilled {
+            offset: self.len,
+            len: data.len(),
+        };
+        self.len += data.len() as u64;
+
+        Ok(spilled)
+    }
+
+    /// Load previously stored data
+    pub fn load(&self, spilled: Spilled) -> anyhow::Result<String> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(spilled.offset))?;
+
+        let mut data = vec![0u8; spilled.len];
+        file.read_exact(&mut data)?;
+
+        Ok(String::from_utf8(data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_load() {
+        let mut spill = Spill::new().unwrap();
+        let foo = spill.store("foo").unwrap();
+        let empty = spill.store("").unwrap();
+        let bar = spill.store("bär").unwrap();
+
+        assert_eq!(spill.load(bar).unwrap(), "bär");
+        assert_eq!(spill.load(empty).unwrap(), "");
+        assert_eq!(spill.load(foo).unwrap(), "foo");
+    }
+}
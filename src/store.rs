@@ -0,0 +1,441 @@
+use crate::model;
+use anyhow::Context;
+use chrono::Utc;
+use rusqlite::{
+    params_from_iter,
+    types::{Value as SqlValue, ValueRef},
+    Connection, Params,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// The version of the schema, stored as the `user_version` of the database.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    created TEXT NOT NULL,
+    sha TEXT,
+    labels TEXT NOT NULL,
+    name TEXT,
+    outcome TEXT NOT NULL,
+    passed INTEGER NOT NULL,
+    failed INTEGER NOT NULL,
+    ignored INTEGER NOT NULL,
+    duration REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS suites (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    target TEXT,
+    kind TEXT NOT NULL,
+    label TEXT,
+    outcome TEXT NOT NULL,
+    passed INTEGER NOT NULL,
+    failed INTEGER NOT NULL,
+    ignored INTEGER NOT NULL,
+    duration REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tests (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    name TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    duration REAL,
+    label TEXT
+);
+CREATE INDEX IF NOT EXISTS tests_name ON tests (name);
+"#;
+
+/// A SQLite database, storing the results of all runs.
+#[derive(Debug, Clone)]
+pub struct Store {
+    path: PathBuf,
+    /// The commit the results are stored for.
+    sha: Option<String>,
+}
+
+impl Store {
+    /// Open the store, creating the database and its schema if required
+    pub fn open(path: &Path, sha: Option<String>) -> anyhow::Result<Self> {
+        let store = Self {
+            path: path.to_path_buf(),
+            sha,
+        };
+        let mut connection = store.connect()?;
+        let version: u32 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        match version {
+            0 => {
+                let transaction = connection.transaction()?;
+                transaction.execute_batch(SCHEMA)?;
+                transaction.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+                transaction.commit()?;
+            }
+            SCHEMA_VERSION => {}
+            version => anyhow::bail!(
+                "{} uses version {} of the schema, expected {}",
+                path.display(),
+                version,
+                SCHEMA_VERSION
+            ),
+        }
+        Ok(store)
+    }
+
+    fn connect(&self) -> anyhow::Result<Connection> {
+        Connection::open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))
+    }
+
+    /// Run a query, returning the rows as JSON objects
+    fn query(&self, sql: &str, params: impl Params) -> anyhow::Result<Vec<Value>> {
+        let connection = self.connect()?;
+        let mut statement = connection.prepare(sql)?;
+        let names = statement
+            .column_names()
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let rows = statement.query_map(params, |row| {
+            let mut object = serde_json::Map::new();
+            for (idx, name) in names.iter().enumerate() {
+                object.insert(name.clone(), json_value(row.get_ref(idx)?));
+            }
+            Ok(Value::Object(object))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Insert the runs of a report
+    pub fn insert(&self, report: &model::Report) -> anyhow::Result<()> {
+        self.insert_runs(&report_runs(report, self.sha.as_deref()))
+    }
+
+    /// Insert runs in the format of the export, in a single transaction
+    fn insert_runs(&self, runs: &[Value]) -> anyhow::Result<()> {
+        let mut connection = self.connect()?;
+        let transaction = connection.transaction()?;
+        for run in runs {
+            insert(&transaction, "runs", RUN_COLUMNS, None, run)?;
+            let id = transaction.last_insert_rowid();
+            for suite in run["suites"].as_array().into_iter().flatten() {
+                insert(&transaction, "suites", SUITE_COLUMNS, Some(id), suite)?;
+            }
+            for test in run["tests"].as_array().into_iter().flatten() {
+                insert(&transaction, "tests", TEST_COLUMNS, Some(id), test)?;
+            }
+        }
+        transaction
+            .commit()
+            .with_context(|| format!("Failed to write to {}", self.path.display()))
+    }
+
+    /// The passed and failed tests, and the duration of the last runs, oldest first
+    pub fn trend(&self, runs: usize) -> anyhow::Result<Vec<(u64, u64, f64)>> {
+        let rows = self.query(
+            "SELECT passed, failed, duration FROM (SELECT * FROM runs ORDER BY id DESC LIMIT ?1) ORDER BY id",
+            [runs as i64],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                (
+                    row["passed"].as_u64().unwrap_or_default(),
+                    row["failed"].as_u64().unwrap_or_default(),
+                    row["duration"].as_f64().unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    /// The average duration of each test, in seconds, over the last runs
+    pub fn average_durations(&self, runs: usize) -> anyhow::Result<HashMap<String, f64>> {
+        let rows = self.query(
+            "SELECT name, avg(duration) AS duration FROM tests \
+            WHERE duration > 0 AND run_id IN (SELECT id FROM runs ORDER BY id DESC LIMIT ?1) \
+            GROUP BY name",
+            [runs as i64],
+        )?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| Some((row["name"].as_str()?.to_string(), row["duration"].as_f64()?)))
+            .collect())
+    }
+
+    /// Export all runs, with their suites and tests
+    pub fn export(&self) -> anyhow::Result<Value> {
+        let select = |table: &str, columns: &[&str]| {
+            let columns = match table {
+                "runs" => columns.join(", "),
+                _ => format!("run_id, {}", columns.join(", ")),
+            };
+            self.query(
+                &format!("SELECT id, {} FROM {} ORDER BY id", columns, table),
+                [],
+            )
+        };
+        // group the rows once, instead of scanning them for each run
+        let by_run = |rows: Vec<Value>| {
+            let mut grouped = HashMap::<i64, Vec<Value>>::new();
+            for mut row in rows {
+                let run_id = row.as_object_mut().and_then(|row| {
+                    row.remove("id");
+                    row.remove("run_id")
+                });
+                if let Some(run_id) = run_id.as_ref().and_then(Value::as_i64) {
+                    grouped.entry(run_id).or_default().push(row);
+                }
+            }
+            grouped
+        };
+        let mut suites = by_run(select("suites", SUITE_COLUMNS)?);
+        let mut tests = by_run(select("tests", TEST_COLUMNS)?);
+
+        let runs = select("runs", RUN_COLUMNS)?
+            .into_iter()
+            .map(|mut run| {
+                let id = run.as_object_mut().and_then(|run| run.remove("id"));
+                let id = id.as_ref().and_then(Value::as_i64).unwrap_or_default();
+                run["suites"] = suites.remove(&id).unwrap_or_default().into();
+                run["tests"] = tests.remove(&id).unwrap_or_default().into();
+                run
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({ "version": SCHEMA_VERSION, "runs": runs }))
+    }
+
+    /// Import runs of an export, appending them to the existing ones. Returns the number of runs.
+    pub fn import(&self, history: &Value) -> anyhow::Result<usize> {
+        if history["version"] != SCHEMA_VERSION {
+            anyhow::bail!(
+                "Unsupported version of the history: {}, expected {}",
+                history["version"],
+                SCHEMA_VERSION
+            );
+        }
+        let runs = history["runs"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("The history contains no runs"))?;
+        self.insert_runs(runs)?;
+        Ok(runs.len())
+    }
+}
+
+const RUN_COLUMNS: &[&str] = &[
+    "created", "sha", "labels", "name", "outcome", "passed", "failed", "ignored", "duration",
+];
+const SUITE_COLUMNS: &[&str] = &[
+    "target", "kind", "label", "outcome", "passed", "failed", "ignored", "duration",
+];
+const TEST_COLUMNS: &[&str] = &["name", "outcome", "duration", "label"];
+
+/// The JSON value of a column
+fn json_value(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(value) => value.into(),
+        ValueRef::Real(value) => value.into(),
+        ValueRef::Text(value) | ValueRef::Blob(value) => {
+            String::from_utf8_lossy(value).into_owned().into()
+        }
+    }
+}
+
+/// The SQL value of a JSON value, bound as parameter
+fn sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(value) => SqlValue::Integer(*value as i64),
+        Value::Number(value) => match value.as_i64() {
+            Some(value) => SqlValue::Integer(value),
+            None => SqlValue::Real(value.as_f64().unwrap_or_default()),
+        },
+        Value::String(value) => SqlValue::Text(value.clone()),
+        value => SqlValue::Text(value.to_string()),
+    }
+}
+
+/// Insert a row, belonging to the run if given
+fn insert(
+    connection: &Connection,
+    table: &str,
+    columns: &[&str],
+    run_id: Option<i64>,
+    row: &Value,
+) -> rusqlite::Result<()> {
+    let mut names = columns.to_vec();
+    let mut values = columns
+        .iter()
+        .map(|column| sql_value(&row[column]))
+        .collect::<Vec<_>>();
+    if let Some(run_id) = run_id {
+        names.insert(0, "run_id");
+        values.insert(0, SqlValue::Integer(run_id));
+    }
+    let placeholders = (1..=names.len())
+        .map(|idx| format!("?{}", idx))
+        .collect::<Vec<_>>();
+    connection
+        .prepare_cached(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            names.join(", "),
+            placeholders.join(", ")
+        ))?
+        .execute(params_from_iter(values))?;
+    Ok(())
+}
+
+/// The runs of a report, in the format of the export
+fn report_runs(report: &model::Report, sha: Option<&str>) -> Vec<Value> {
+    let created = Utc::now().to_rfc3339();
+    report
+        .runs
+        .iter()
+        .map(|run| {
+            let summary = run.summary.as_ref();
+            let count = |f: fn(&model::Summary) -> u64| summary.map(f).unwrap_or_default();
+            json!({
+                "created": created,
+                "sha": sha,
+                "labels": report.labels.join(","),
+                "name": run.name,
+                "outcome": summary.map(|summary| summary.outcome).unwrap_or(report.outcome),
+                "passed": count(|summary| summary.passed),
+                "failed": count(|summary| summary.failed),
+                "ignored": count(|summary| summary.ignored),
+                "duration": summary.map(|summary| summary.duration).unwrap_or_default(),
+                "suites": run.suites.iter().map(|suite| json!({
+                    "target": suite.target,
+                    "kind": suite.kind,
+                    "label": suite.label,
+                    "outcome": suite.summary.outcome,
+                    "passed": suite.summary.passed,
+                    "failed": suite.summary.failed,
+                    "ignored": suite.summary.ignored,
+                    "duration": suite.summary.duration,
+                })).collect::<Vec<_>>(),
+                "tests": run.tests.iter().map(|test| json!({
+                    "name": test.name,
+                    "outcome": test.outcome,
+                    "duration": test.duration,
+                    "label": test.label,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> model::Report {
+        model::Report {
+            version: model::VERSION,
+            outcome: "failed",
+            labels: vec![],
+            partial: false,
+            runs: vec![model::Run {
+                name: None,
+                summary: None,
+                suites: vec![],
+                tests: vec![model::Test {
+                    name: "it's".into(),
+                    outcome: "ok",
+                    duration: Some(0.25),
+                    label: None,
+                    message: None,
+                    output: None,
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.db");
+        let store = Store::open(&path, Some("abc".into())).unwrap();
+        store.insert(&report()).unwrap();
+        // re-opening keeps the data
+        let store = Store::open(&path, None).unwrap();
+        store.insert(&report()).unwrap();
+
+        let rows = store
+            .query("SELECT run_id, name, duration FROM tests ORDER BY id", [])
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                json!({ "run_id": 1, "name": "it's", "duration": 0.25 }),
+                json!({ "run_id": 2, "name": "it's", "duration": 0.25 }),
+            ]
+        );
+        let rows = store
+            .query("SELECT sha, labels, name, outcome, duration FROM runs", [])
+            .unwrap();
+        assert_eq!(
+            rows[0],
+            json!({ "sha": "abc", "labels": "", "name": null, "outcome": "failed", "duration": 0.0 })
+        );
+        assert_eq!(store.trend(5).unwrap(), vec![(0, 0, 0.0), (0, 0, 0.0)]);
+        assert_eq!(
+            store.average_durations(1).unwrap(),
+            HashMap::from([("it's".to_string(), 0.25)])
+        );
+
+        // a newer schema
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(Store::open(&path, None).is_err());
+    }
+
+    #[test]
+    fn test_export_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(&dir.path().join("a.db"), Some("abc".into())).unwrap();
+        store.insert(&report()).unwrap();
+        let history = store.export().unwrap();
+        assert_eq!(history["runs"][0]["sha"], "abc");
+        assert_eq!(
+            history["runs"][0]["tests"],
+            serde_json::json!([{ "name": "it's", "outcome": "ok", "duration": 0.25, "label": null }])
+        );
+
+        // merging the same history twice
+        let merged = Store::open(&dir.path().join("b.db"), None).unwrap();
+        assert_eq!(merged.import(&history).unwrap(), 1);
+        assert_eq!(merged.import(&history).unwrap(), 1);
+        let exported = merged.export().unwrap();
+        assert_eq!(exported["runs"][0], history["runs"][0]);
+        assert_eq!(exported["runs"][1], history["runs"][0]);
+
+        // values are bound, not part of the statements
+        let mut tricky = history.clone();
+        tricky["runs"][0]["labels"] = "'); DROP TABLE runs; --".into();
+        assert_eq!(merged.import(&tricky).unwrap(), 1);
+        assert_eq!(
+            merged.export().unwrap()["runs"][2]["labels"],
+            "'); DROP TABLE runs; --"
+        );
+
+        // a broken history isn't imported partially
+        let mut broken = history.clone();
+        let runs = broken["runs"].as_array_mut().unwrap();
+        runs.push(json!({ "created": "now" }));
+        assert!(merged.import(&broken).is_err());
+        assert_eq!(
+            merged.export().unwrap()["runs"].as_array().unwrap().len(),
+            3
+        );
+        assert!(merged.import(&json!({ "version": 0, "runs": [] })).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{
+    error::Error,
+    processor::{ProcessOptions, Processor, Report},
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Processes records which are read asynchronously, like from a network connection, without
+/// blocking a thread while waiting for them.
+///
+/// The report is rendered into memory, and written once finished.
+pub struct AsyncProcessor {
+    processor: Processor<Vec<u8>>,
+}
+
+impl AsyncProcessor {
+    pub fn new(options: ProcessOptions) -> Self {
+        Self {
+            processor: Processor::new(Vec::new(), options),
+        }
+    }
+
+    /// Set the label of the input which gets processed next
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.processor.set_label(label);
+    }
+
+    /// Set if the input which gets processed next is streamed while the tests are running
+    pub fn set_streamed(&mut self, streamed: bool) {
+        self.processor.set_streamed(streamed);
+    }
+
+    /// Process all lines of the input, until it ends.
+    pub async fn read<R>(&mut self, read: R) -> Result<(), Error>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        let mut lines = read.lines();
+        while let Some(line) = lines.next_line().await? {
+            self.processor.line(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Render the report and write it, returning the overall outcome.
+    pub async fn finish<W>(self, mut write: W) -> Result<Report, Error>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let (report, output) = self.processor.finish_output()?;
+        write.write_all(&output).await?;
+        write.flush().await?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processor::Outcome;
+
+    #[tokio::test]
+    async fn test_process() {
+        let input = r#"{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "event": "started", "name": "tests::a" }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.1 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.1 }
+"#;
+        let mut processor = AsyncProcessor::new(ProcessOptions {
+            disable_front_matter: true,
+            footer: false,
+            ..Default::default()
+        });
+        processor.read(input.as_bytes()).await.unwrap();
+
+        let mut output = vec![];
+        let report = processor.finish(&mut output).await.unwrap();
+        assert_eq!(report.outcome, Outcome::Ok);
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains("| [tests::a](#testsa) | ✅ | 0s |"),
+            "{}",
+            output
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use crate::input;
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+use std::path::Path;
+
+/// An external event, like a restart of the database, shown along the tests running at the time.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Event {
+    pub time: DateTime<FixedOffset>,
+    pub message: String,
+}
+
+/// Load the events of a timeline, a JSON array, ordered by their time.
+pub fn load(path: &Path) -> anyhow::Result<Vec<Event>> {
+    let mut events: Vec<Event> = serde_json::from_reader(input::open(path)?)
+        .with_context(|| format!("Failed to read the timeline from {}", path.display()))?;
+    events.sort_by_key(|event| event.time);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.json");
+        std::fs::write(
+            &path,
+            r#"[
+  { "time": "2024-05-01T10:00:05+02:00", "message": "db restarted" },
+  { "time": "2024-05-01T08:00:01Z", "message": "deployed v1.2" }
+]"#,
+        )
+        .unwrap();
+
+        let events = load(&path).unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["deployed v1.2", "db restarted"]
+        );
+
+        std::fs::write(&path, r#"[{ "time": "yesterday", "message": "a" }]"#).unwrap();
+        assert!(load(&path).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::processor::{make_azure_anchor, Flavor};
+use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
+};
+
+/// A problem of a generated report.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Problem {
+    /// The line of the report, starting with 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// The anchor GitHub creates for a heading.
+fn github_anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Split the row of a table into its cells, respecting escaped pipes.
+fn cells(row: &str) -> usize {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = match row.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => row,
+    };
+    let mut escaped = false;
+    let mut count = 1;
+    for c in row.chars() {
+        match c {
+            '|' if !escaped => count += 1,
+            _ => {}
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    count
+}
+
+fn is_delimiter(row: &str) -> bool {
+    let row = row.trim().trim_matches('|');
+    row.split('|').all(|cell| {
+        let cell = cell.trim();
+        let cell = cell.strip_prefix(':').unwrap_or(cell);
+        let cell = cell.strip_suffix(':').unwrap_or(cell);
+        !cell.is_empty() && cell.chars().all(|c| c == '-')
+    })
+}
+
+/// A table, while its rows are checked.
+struct Table {
+    line: usize,
+    columns: usize,
+    rows: usize,
+}
+
+/// Check a generated markdown report: the tables must be well-formed, headings unique, and links
+/// within the report must have a matching anchor.
+pub fn verify(markdown: &str, flavor: Flavor) -> Vec<Problem> {
+    let link = Regex::new(r"\]\(#([^\s)]*)").expect("valid pattern");
+    let anchor = Regex::new(r#"<a (?:id|name)="([^"]*)""#).expect("valid pattern");
+    let tag = Regex::new(r"<[^>]*>").expect("valid pattern");
+
+    let mut problems = vec![];
+    let mut problem = |line: usize, message: String| problems.push(Problem { line, message });
+
+    let mut anchors = HashSet::new();
+    let mut headings = HashMap::<String, usize>::new();
+    let mut links = vec![];
+
+    let mut fence: Option<String> = None;
+    let mut pre = false;
+    let mut table: Option<Table> = None;
+    let mut lines = markdown
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line));
+
+    // the front matter
+    if markdown.starts_with("---\n") {
+        lines.next();
+        for (_, line) in lines.by_ref() {
+            if line == "---" {
+                break;
+            }
+        }
+    }
+
+    for (number, line) in lines {
+        // the content of code blocks and preformatted text isn't markdown
+        if let Some(open) = &fence {
+            if line.trim_end().starts_with(open.as_str())
+                && line.trim_end().chars().all(|c| c == '`')
+            {
+                fence = None;
+            }
+            continue;
+        }
+        if pre {
+            pre = !line.contains("</pre>");
+            continue;
+        }
+        if line.starts_with("```") {
+            fence = Some(line.chars().take_while(|c| *c == '`').collect());
+            table = None;
+            continue;
+        }
+        if line.starts_with("<pre") {
+            pre = !line.contains("</pre>");
+            table = None;
+            continue;
+        }
+
+        if line.starts_with('|') {
+            match &mut table {
+                None => {
+                    table = Some(Table {
+                        line: number,
+                        columns: cells(line),
+                        rows: 0,
+                    })
+                }
+                Some(table) if table.rows == 0 => {
+                    table.rows += 1;
+                    if !is_delimiter(line) {
+                        problem(number, "a table is missing its delimiter row".into());
+                    } else if cells(line) != table.columns {
+                        problem(
+                            number,
+                            format!(
+                                "the delimiter row has {} columns, the header of line {} has {}",
+                                cells(line),
+                                table.line,
+                                table.columns
+                            ),
+                        );
+                    }
+                }
+                Some(table) => {
+                    table.rows += 1;
+                    if cells(line) != table.columns {
+                        problem(
+                            number,
+                            format!(
+                                "the row has {} columns, the header of line {} has {}",
+                                cells(line),
+                                table.line,
+                                table.columns
+                            ),
+                        );
+                    }
+                }
+            }
+        } else {
+            if let Some(Table { line, rows: 0, .. }) = table {
+                problem(line, "a table is missing its delimiter row".into());
+            }
+            table = None;
+        }
+
+        for captures in anchor.captures_iter(line) {
+            anchors.insert(captures[1].to_string());
+        }
+        for captures in link.captures_iter(line) {
+            links.push((number, captures[1].to_string()));
+        }
+
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            let heading = tag.replace_all(line[level..].trim(), "").into_owned();
+            // headings with an anchor of their own can be told apart
+            let anchored = anchor.is_match(line);
+            match headings.get(&heading) {
+                Some(_) if anchored => {}
+                Some(first) => problem(
+                    number,
+                    format!("duplicate heading '{}', first on line {}", heading, first),
+                ),
+                None => {
+                    headings.insert(heading.clone(), number);
+                }
+            }
+            anchors.insert(match flavor {
+                Flavor::Github => github_anchor(&heading),
+                Flavor::AzureDevops => make_azure_anchor(&heading),
+            });
+        }
+    }
+
+    if let Some(Table { line, rows: 0, .. }) = table {
+        problem(line, "a table is missing its delimiter row".into());
+    }
+    for (number, link) in links {
+        if !anchors.contains(&link) {
+            problem(number, format!("the link to '#{}' has no anchor", link));
+        }
+    }
+
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(cells("| a | b |"), 2);
+        assert_eq!(cells("| `a \\| b` | c | "), 2);
+        assert_eq!(cells("| | a |"), 2);
+        assert!(is_delimiter("| --- | :---: | --: |"));
+        assert!(!is_delimiter("| a | --- |"));
+        assert_eq!(github_anchor("❌ tests::b"), "-testsb");
+    }
+
+    #[test]
+    fn test_verify() {
+        let report = r##"---
+title: "# Not a heading"
+---
+
+| Name | Result |
+| ---- | ------ |
+| [a](#a) | ✅ |
+| [b](#b "b") | ✅ | 0s |
+| [c](#c) | ✅ |
+
+| Name |
+| [d](#details) |
+
+# Details
+
+## a<a id="a"></a>
+
+```
+| not | a table
+# not a heading
+```
+
+<pre>
+[x](#x)
+</pre>
+
+## b
+
+## a<a id="a-1"></a>
+
+## b
+"##;
+        let problems = verify(report, Flavor::Github)
+            .into_iter()
+            .map(|problem| problem.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                "line 8: the row has 3 columns, the header of line 5 has 2",
+                "line 9: the link to '#c' has no anchor",
+                "line 12: a table is missing its delimiter row",
+                "line 31: duplicate heading 'b', first on line 27",
+            ]
+        );
+    }
+}
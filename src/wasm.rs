@@ -0,0 +1,44 @@
+use crate::{
+    error::Error,
+    processor::{self, ProcessOptions},
+};
+use wasm_bindgen::prelude::*;
+
+/// Render the markdown report of the JSON output of the tests, like pasted into a web page.
+///
+/// Broken input throws an error.
+#[wasm_bindgen]
+pub fn render_markdown(json: &str) -> Result<String, JsError> {
+    render(json).map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn render(json: &str) -> Result<String, Error> {
+    processor::render_str(
+        json,
+        ProcessOptions {
+            disable_front_matter: true,
+            ..Default::default()
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let report = render(
+            r#"{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "event": "started", "name": "tests::a" }
+{ "type": "test", "name": "tests::a", "event": "failed", "exec_time": 0.1, "stdout": "boom" }
+{ "type": "suite", "event": "failed", "passed": 0, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.1 }
+"#,
+        )
+        .unwrap();
+        assert!(report.contains("## ❌ tests::a"), "{}", report);
+
+        let err = render(r#"{ "type": "test", "event": "failed" }"#).unwrap_err();
+        assert!(matches!(err, Error::Parse { line: 1, .. }));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::http::{self, Secret};
+use crate::processor::{Notification, Notifier};
+
+/// A header of the request, like `Authorization: Bearer token`.
+#[derive(Debug, Clone)]
+pub struct Header {
+    name: String,
+    /// Headers are commonly used for credentials.
+    value: Secret,
+}
+
+impl Header {
+    pub fn parse(header: &str) -> Result<Self, String> {
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| format!("expected NAME: VALUE, found: {}", header))?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("invalid header name: {}", name));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            value: Secret(value.trim().to_string()),
+        })
+    }
+}
+
+/// Posts the results as a JSON document, like the one of `--format json`.
+#[derive(Debug)]
+pub struct Webhook {
+    /// The URLs of webhooks commonly contain their credential.
+    url: Secret,
+    headers: Vec<Header>,
+}
+
+impl Webhook {
+    pub fn new(url: String, headers: Vec<Header>) -> Self {
+        Self {
+            url: Secret(url),
+            headers,
+        }
+    }
+}
+
+impl Notifier for Webhook {
+    fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+        let headers = self
+            .headers
+            .iter()
+            .map(|header| (header.name.as_str(), header.value.0.clone()))
+            .collect::<Vec<_>>();
+        http::send(
+            "POST",
+            &self.url.0,
+            &headers,
+            &serde_json::to_value(notification.report)?,
+        )?;
+        log::info!("Posted the results to {}", http::host(&self.url.0));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let header = Header::parse("Authorization: Bearer a:b").unwrap();
+        assert_eq!(header.name, "Authorization");
+        assert_eq!(header.value.0, "Bearer a:b");
+        assert_eq!(format!("{:?}", header.value), "***");
+
+        assert!(Header::parse("Authorization").is_err());
+        assert!(Header::parse("Bad Name: value").is_err());
+        assert!(Header::parse(": value").is_err());
+    }
+}
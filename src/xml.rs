@@ -0,0 +1,9 @@
+use quick_xml::events::BytesStart;
+
+/// Get the unescaped value of an XML attribute
+pub fn attribute(element: &BytesStart, name: &str) -> anyhow::Result<Option<String>> {
+    Ok(match element.try_get_attribute(name)? {
+        Some(value) => Some(value.unescape_value()?.into_owned()),
+        None => None,
+    })
+}
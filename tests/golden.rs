@@ -0,0 +1,121 @@
+//! Compare the reports rendered from the fixtures with the golden files.
+//!
+//! After an intended change of the output, update the golden files using:
+//!
+//! ```shell
+//! BLESS=1 cargo test --test golden
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The cases: the name of the golden file, the fixture, and additional arguments.
+const CASES: &[(&str, &str, &[&str])] = &[
+    ("libtest", "libtest.json", &[]),
+    ("libtest-summary", "libtest.json", &["--summary"]),
+    (
+        "libtest-azure-devops",
+        "libtest.json",
+        &["--flavor", "azure-devops"],
+    ),
+    ("libtest-comment", "libtest.json", &["--format", "comment"]),
+    ("libtest-merge", "libtest.json", &["--runs", "merge"]),
+    ("nextest", "nextest.json", &[]),
+];
+
+/// Environment variables which change the output, like the link to the job.
+const ENV: &[&str] = &[
+    "GITHUB_RUN_ID",
+    "GITHUB_REPOSITORY",
+    "GITHUB_SERVER_URL",
+    "GITHUB_SHA",
+];
+
+fn dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name)
+}
+
+fn render(fixture: &str, args: &[&str]) -> String {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_markdown-test-report"));
+    // run in the directory of the fixtures, keeping paths relative and ignoring any configuration
+    command.current_dir(dir("fixtures")).args([
+        fixture,
+        "--output",
+        "-",
+        "--no-git",
+        "--no-front-matter",
+        "--no-footer",
+        "--quiet",
+    ]);
+    command.args(args);
+    if !args.contains(&"--format") {
+        command.arg("--verify-output");
+    }
+    for name in ENV {
+        command.env_remove(name);
+    }
+
+    let output = command.output().expect("run markdown-test-report");
+    assert!(
+        output.status.success(),
+        "rendering {} {:?} failed: {}",
+        fixture,
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("valid UTF-8")
+}
+
+/// The first line which differs, for a readable failure.
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for number in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(expected), Some(actual)) if expected == actual => continue,
+            (None, None) => break,
+            (expected, actual) => {
+                return format!(
+                    "line {}:\n  expected: {:?}\n    actual: {:?}",
+                    number, expected, actual
+                )
+            }
+        }
+    }
+    "trailing whitespace".into()
+}
+
+#[test]
+fn golden() {
+    let bless = std::env::var_os("BLESS").is_some();
+    let mut failures = vec![];
+
+    for (name, fixture, args) in CASES {
+        let actual = render(fixture, args);
+        let path = dir("golden").join(format!("{}.md", name));
+        if bless {
+            std::fs::write(&path, &actual).expect("write golden file");
+            continue;
+        }
+
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{} differs, at {}",
+                path.display(),
+                first_difference(&expected, &actual)
+            )),
+            Err(err) => failures.push(format!("{}: {}", path.display(), err)),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\n\nIf the changes are intended, update the golden files using: BLESS=1 cargo test --test golden",
+        failures.join("\n")
+    );
+}